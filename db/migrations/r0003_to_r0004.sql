@@ -0,0 +1,47 @@
+-- ordinal-only symbols used to be identified by the final component of their DLL's path and their
+-- ordinal, which conflated same-named DLLs in different directories (e.g.
+-- "system32\api-ms-win-core-file-l1-1-0.dll" and "system32\downlevel\api-ms-win-core-file-l1-1-0.dll")
+-- identify them by the full DLL path and their ordinal instead
+
+-- create the path-based symbols
+INSERT OR IGNORE INTO symbols
+    (raw_name, dll_name, ordinal, friendly_name, is_meta_func)
+SELECT DISTINCT
+    NULL, dll.path, sym.ordinal, sym.friendly_name, sym.is_meta_func
+FROM
+    symbols sym
+    INNER JOIN symbol_dll_os sdo
+        ON sdo.sym_id = sym.sym_id
+    INNER JOIN dlls dll
+        ON dll.dll_id = sdo.dll_id
+WHERE
+    sym.dll_name IS NOT NULL;
+
+-- point the relationships at them
+UPDATE symbol_dll_os
+SET sym_id = (
+    SELECT new_sym.sym_id
+    FROM
+        symbols old_sym
+        INNER JOIN dlls dll
+            ON dll.dll_id = symbol_dll_os.dll_id
+        INNER JOIN symbols new_sym
+            ON new_sym.dll_name = dll.path
+            AND new_sym.ordinal = old_sym.ordinal
+    WHERE
+        old_sym.sym_id = symbol_dll_os.sym_id
+)
+WHERE
+    sym_id IN (SELECT sym_id FROM symbols WHERE dll_name IS NOT NULL);
+
+-- drop the name-based symbols that are no longer referenced
+DELETE FROM symbols
+WHERE
+    dll_name IS NOT NULL
+    AND NOT EXISTS (
+        SELECT 1
+        FROM symbol_dll_os sdo
+        WHERE sdo.sym_id = symbols.sym_id
+    );
+
+UPDATE schema_version SET ver=4;
@@ -0,0 +1,3 @@
+ALTER TABLE operating_systems ADD parent_os_id INTEGER NULL DEFAULT NULL REFERENCES operating_systems (os_id);
+
+UPDATE schema_version SET ver=5;
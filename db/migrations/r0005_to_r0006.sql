@@ -0,0 +1,14 @@
+-- precomputed first characters listed in the index on the root page
+-- category is 'dll' for DLL paths, 'func' for function names and 'ordinal_dll' for the paths of DLLs
+-- with ordinal-only functions
+--
+-- whload recomputes these whenever it changes the data
+CREATE TABLE index_start_chars
+( category TEXT NOT NULL CHECK(category IN ('dll', 'func', 'ordinal_dll'))
+, start_chars TEXT NOT NULL
+, PRIMARY KEY (category, start_chars)
+);
+
+-- whload fills the table by running reindex.sql after migrating
+
+UPDATE schema_version SET ver=6;
@@ -0,0 +1,4 @@
+-- allow prefix searches on the name displayed for a symbol (as used by autocompletion)
+CREATE INDEX idx_symbols_display_name ON symbols (COALESCE(friendly_name, raw_name));
+
+UPDATE schema_version SET ver=7;
@@ -0,0 +1,3 @@
+ALTER TABLE operating_systems ADD build_number TEXT NULL DEFAULT NULL;
+
+UPDATE schema_version SET ver=8;
@@ -0,0 +1,3 @@
+ALTER TABLE symbol_dll_os ADD architecture TEXT NULL DEFAULT NULL;
+
+UPDATE schema_version SET ver=9;
@@ -0,0 +1,3 @@
+ALTER TABLE symbols ADD deprecated INTEGER NOT NULL DEFAULT 0 CHECK(deprecated IN (0, 1));
+
+UPDATE schema_version SET ver=10;
@@ -0,0 +1,5 @@
+-- the earliest operating system containing each symbol
+-- (whload fills it by running reindex.sql after migrating)
+ALTER TABLE symbols ADD first_os_id INTEGER NULL DEFAULT NULL REFERENCES operating_systems (os_id);
+
+UPDATE schema_version SET ver=11;
@@ -0,0 +1,4 @@
+-- allow looking up symbols by raw name regardless of case (as done if a symbol is not found)
+CREATE INDEX idx_symbols_raw_name_nocase ON symbols (raw_name COLLATE NOCASE);
+
+UPDATE schema_version SET ver=12;
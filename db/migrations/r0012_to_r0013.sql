@@ -0,0 +1,5 @@
+-- the number of distinct symbols in each operating system, as shown on the root page
+-- (whload fills it by running reindex.sql after migrating)
+ALTER TABLE operating_systems ADD symbol_count INTEGER NOT NULL DEFAULT 0;
+
+UPDATE schema_version SET ver=13;
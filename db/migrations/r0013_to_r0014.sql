@@ -0,0 +1,5 @@
+-- when whload last recomputed the derived data, in milliseconds since the Unix epoch
+-- (whload fills it by running reindex.sql after migrating)
+ALTER TABLE schema_version ADD reindexed_at INTEGER NULL DEFAULT NULL;
+
+UPDATE schema_version SET ver=14;
@@ -0,0 +1,5 @@
+-- the position of the operating system in chronological order
+-- (whload fills it by running reindex.sql after migrating)
+ALTER TABLE operating_systems ADD sort_order INTEGER NOT NULL DEFAULT 0;
+
+UPDATE schema_version SET ver=15;
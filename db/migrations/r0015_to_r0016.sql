@@ -0,0 +1,28 @@
+-- identify the rows of symbol_dll_os by the architecture too, so that a DLL may export the same
+-- symbol under multiple architectures in the same operating system
+-- (SQLite cannot change the primary key of a table, so the table is rebuilt; primary keys cannot
+-- contain expressions, so a unique index takes the place of the primary key)
+CREATE TABLE symbol_dll_os_new
+( sym_id INTEGER NOT NULL REFERENCES symbols (sym_id)
+, dll_id INTEGER NOT NULL REFERENCES dlls (dll_id)
+, os_id INTEGER NOT NULL REFERENCES operating_systems (os_id)
+, ordinal INTEGER NULL
+, architecture TEXT NULL DEFAULT NULL
+);
+
+INSERT INTO symbol_dll_os_new
+    (sym_id, dll_id, os_id, ordinal, architecture)
+SELECT
+    sym_id, dll_id, os_id, ordinal, architecture
+FROM
+    symbol_dll_os;
+
+DROP TABLE symbol_dll_os;
+ALTER TABLE symbol_dll_os_new RENAME TO symbol_dll_os;
+
+CREATE UNIQUE INDEX idx_sdo_key ON symbol_dll_os (sym_id, dll_id, os_id, COALESCE(architecture, ''));
+CREATE INDEX idx_sdo_do ON symbol_dll_os (dll_id, os_id);
+CREATE INDEX idx_sdo_so ON symbol_dll_os (sym_id, os_id);
+CREATE INDEX idx_sdo_o ON symbol_dll_os (os_id);
+
+UPDATE schema_version SET ver=16;
@@ -0,0 +1,122 @@
+-- recomputes the data derived from the symbols and operating systems: the first characters listed
+-- in the index on the root page, the number of symbols in each operating system, the chronological
+-- order of the operating systems and the earliest operating system containing each symbol; also
+-- records when this happened
+
+DELETE FROM index_start_chars;
+
+-- first characters of DLL paths
+INSERT INTO index_start_chars
+    (category, start_chars)
+SELECT DISTINCT
+    'dll', SUBSTR(path, 1, 1)
+FROM
+    dlls;
+
+-- first characters of function names
+-- (raw names for named functions, friendly names for ordinal functions)
+-- except "?", there's a lot of those due to C++ name mangling, take two characters in this case
+INSERT INTO index_start_chars
+    (category, start_chars)
+SELECT DISTINCT
+    'func',
+    CASE SUBSTR(name, 1, 1)
+        WHEN '?' THEN SUBSTR(name, 1, 2)
+        ELSE SUBSTR(name, 1, 1)
+    END
+FROM
+    (
+        SELECT
+            COALESCE(raw_name, friendly_name) name
+        FROM
+            symbols
+        WHERE
+            raw_name IS NOT NULL
+            OR friendly_name IS NOT NULL
+    );
+
+-- first characters of the file names of DLLs with ordinal-only functions
+-- (dll_name is the full path; RTRIM strips the file name, leaving the directory whose length is
+-- skipped; keep in sync with the ordinal-only symbol list of the web application)
+INSERT INTO index_start_chars
+    (category, start_chars)
+SELECT DISTINCT
+    'ordinal_dll', SUBSTR(dll_name, LENGTH(RTRIM(dll_name, REPLACE(dll_name, '\', ''))) + 1, 1)
+FROM
+    symbols
+WHERE
+    dll_name IS NOT NULL;
+
+-- number of distinct symbols in each operating system
+UPDATE operating_systems
+SET symbol_count = (
+    SELECT
+        COUNT(DISTINCT sdo.sym_id)
+    FROM
+        symbol_dll_os sdo
+    WHERE
+        sdo.os_id = operating_systems.os_id
+);
+
+-- chronological order of the operating systems
+-- (build numbers are compared component by component, each zero-padded to the same width, so that
+-- e.g. 10.0.9200 comes before 10.0.19045)
+UPDATE operating_systems
+SET sort_order = ordered.sort_order
+FROM (
+    WITH RECURSIVE build_components (os_id, remaining, sort_key) AS (
+        SELECT
+            os_id, build_number || '.', ''
+        FROM
+            operating_systems
+        WHERE
+            build_number IS NOT NULL
+        UNION ALL
+        SELECT
+            os_id,
+            SUBSTR(remaining, INSTR(remaining, '.') + 1),
+            sort_key || printf('%020d.', CAST(SUBSTR(remaining, 1, INSTR(remaining, '.') - 1) AS INTEGER))
+        FROM
+            build_components
+        WHERE
+            remaining <> ''
+    )
+    SELECT
+        os.os_id,
+        ROW_NUMBER() OVER (
+            ORDER BY
+                os.release_date ASC NULLS LAST,
+                bc.sort_key ASC NULLS LAST,
+                COALESCE(os.long_name, os.short_name) ASC,
+                os.short_name ASC
+        ) sort_order
+    FROM
+        operating_systems os
+        LEFT JOIN build_components bc
+            ON bc.os_id = os.os_id
+            AND bc.remaining = ''
+) ordered
+WHERE
+    ordered.os_id = operating_systems.os_id;
+
+-- earliest operating system containing each symbol
+-- (only operating systems with a release date are considered)
+UPDATE symbols
+SET first_os_id = (
+    SELECT
+        os.os_id
+    FROM
+        symbol_dll_os sdo
+        INNER JOIN operating_systems os
+            ON os.os_id = sdo.os_id
+    WHERE
+        sdo.sym_id = symbols.sym_id
+        AND os.release_date IS NOT NULL
+    ORDER BY
+        os.sort_order ASC
+    LIMIT 1
+);
+
+-- when the data last changed
+UPDATE schema_version
+SET reindexed_at = CAST(unixepoch('subsec') * 1000 AS INTEGER);
@@ -1,6 +1,7 @@
 -- Windows symbols are identified in two ways:
 -- 1. name (preferred), generally unique
--- 2. DLL name and ordinal, since ordinals are only unique per DLL
+-- 2. DLL path and ordinal, since ordinals are only unique per DLL (the full path is stored in
+--    dll_name because same-named DLLs in different directories may export different functions)
 --
 -- named symbols may also have ordinals within their DLL, but those are subject to change between
 -- versions; such ordinals are stored in symbol_dll_os as a curiosity
@@ -11,6 +12,15 @@
 -- is_meta_func is 1 for functions that are not part of an API but of a meta-API (e.g. "create an
 -- instance of the class with the name specified as a string at runtime"); it might make sense to
 -- hide/ignore these in most cases
+--
+-- deprecated is 1 for symbols whose use Microsoft discourages even though they are still exported
+--
+-- first_os_id is the operating system with the earliest release date containing the symbol, if any
+-- of the operating systems containing it has a release date; whload recomputes it whenever it
+-- changes the data
+--
+-- the UNIQUE constraints are backed by indexes, so lookups by raw_name and by (dll_name, ordinal)
+-- (as well as by dll_name alone) need no additional indexes
 CREATE TABLE symbols
 ( sym_id INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT
 , raw_name TEXT NULL
@@ -18,6 +28,8 @@ CREATE TABLE symbols
 , ordinal INTEGER NULL
 , friendly_name TEXT NULL DEFAULT NULL
 , is_meta_func INTEGER NOT NULL DEFAULT 0 CHECK(is_meta_func IN (0, 1))
+, deprecated INTEGER NOT NULL DEFAULT 0 CHECK(deprecated IN (0, 1))
+, first_os_id INTEGER NULL DEFAULT NULL REFERENCES operating_systems (os_id)
 , UNIQUE (raw_name)
 , UNIQUE (dll_name, ordinal)
 , CHECK ( (raw_name IS NOT NULL AND dll_name IS NULL AND ordinal IS NULL)
@@ -25,46 +37,93 @@ CREATE TABLE symbols
         )
 );
 
+-- allow prefix searches on the name displayed for a symbol (as used by autocompletion)
+CREATE INDEX idx_symbols_display_name ON symbols (COALESCE(friendly_name, raw_name));
+
+-- allow looking up symbols by raw name regardless of case (as done if a symbol is not found)
+CREATE INDEX idx_symbols_raw_name_nocase ON symbols (raw_name COLLATE NOCASE);
+
+-- parent_os_id is set if the operating system is an edition (e.g. Home, Pro, Server) of another
+-- operating system; editions share most of their symbols with their base operating system
+--
+-- build_number is the version and build number of the operating system (e.g. 10.0.19045); it
+-- distinguishes operating systems released on the same date
+--
+-- symbol_count is the number of distinct symbols in the operating system, as shown on the root page;
+-- whload recomputes it whenever it changes the data
+--
+-- sort_order is the position of the operating system in chronological order (by release date, then
+-- by build number compared numerically, then by name; operating systems without a release date
+-- come last); whload recomputes it whenever it changes the data, and all lists of operating systems
+-- are ordered by it
 CREATE TABLE operating_systems
 ( os_id INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT
 , short_name TEXT NOT NULL UNIQUE
 , long_name TEXT NULL DEFAULT NULL
 , release_date TEXT NULL DEFAULT NULL
 , has_icon INTEGER NOT NULL DEFAULT 0 CHECK(has_icon IN (0, 1))
+, parent_os_id INTEGER NULL DEFAULT NULL REFERENCES operating_systems (os_id)
+, build_number TEXT NULL DEFAULT NULL
+, symbol_count INTEGER NOT NULL DEFAULT 0
+, sort_order INTEGER NOT NULL DEFAULT 0
 , CHECK (release_date IS NULL OR date(release_date) = release_date)
 );
 
+-- the UNIQUE constraint on path is backed by an index used for lookups by path
 CREATE TABLE dlls
 ( dll_id INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT
 , path TEXT NOT NULL UNIQUE
 , secondary_platform INTEGER NOT NULL CHECK(secondary_platform IN (0, 1))
 );
 
+-- architecture is the processor architecture (e.g. x86, x64, arm64) of the DLL from which the symbol
+-- was exported, if known; it is more specific than the secondary_platform flag of the DLL
+--
+-- a DLL may export the same symbol under multiple architectures in the same operating system, so
+-- the architecture is part of the key; primary keys cannot contain expressions, so the key is a
+-- unique index treating an unknown architecture like any other
 CREATE TABLE symbol_dll_os
 ( sym_id INTEGER NOT NULL REFERENCES symbols (sym_id)
 , dll_id INTEGER NOT NULL REFERENCES dlls (dll_id)
 , os_id INTEGER NOT NULL REFERENCES operating_systems (os_id)
 , ordinal INTEGER NULL
-, PRIMARY KEY (sym_id, dll_id, os_id)
+, architecture TEXT NULL DEFAULT NULL
 );
+CREATE UNIQUE INDEX idx_sdo_key ON symbol_dll_os (sym_id, dll_id, os_id, COALESCE(architecture, ''));
 
 -- allow slicing and dicing symbol_dll_os from all sides:
--- (sym_id) prefix_of (sym_id, dll_id, os_id) PRIMARY KEY
+-- (sym_id) prefix_of (sym_id, dll_id, os_id, architecture) INDEX key
 -- (dll_id) prefix_of (dll_id, os_id) INDEX do
 -- (os_id) is INDEX o
--- (sym_id, dll_id) prefix_of (sym_id, dll_id, os_id) PRIMARY KEY
+-- (sym_id, dll_id) prefix_of (sym_id, dll_id, os_id, architecture) INDEX key
 -- (sym_id, os_id) is INDEX so
 -- (dll_id, os_id) is INDEX do
--- (sym_id, dll_id, os_id) is PRIMARY KEY
+-- (sym_id, dll_id, os_id) prefix_of (sym_id, dll_id, os_id, architecture) INDEX key
 CREATE INDEX idx_sdo_do ON symbol_dll_os (dll_id, os_id);
 CREATE INDEX idx_sdo_so ON symbol_dll_os (sym_id, os_id);
 CREATE INDEX idx_sdo_o ON symbol_dll_os (os_id);
 
+-- precomputed first characters listed in the index on the root page
+-- category is 'dll' for DLL paths, 'func' for function names and 'ordinal_dll' for the file names
+-- (without directory) of DLLs with ordinal-only functions
+--
+-- whload recomputes these whenever it changes the data
+CREATE TABLE index_start_chars
+( category TEXT NOT NULL CHECK(category IN ('dll', 'func', 'ordinal_dll'))
+, start_chars TEXT NOT NULL
+, PRIMARY KEY (category, start_chars)
+);
+
 -- schema version logic
+--
+-- reindexed_at is when whload last recomputed the derived data (in milliseconds since the Unix
+-- epoch); since whload does so whenever it changes the data, the web application uses it to notice
+-- changes even while they are still in the write-ahead log
 CREATE TABLE schema_version
 ( ver INTEGER NOT NULL
+, reindexed_at INTEGER NULL DEFAULT NULL
 );
-INSERT INTO schema_version (ver) VALUES (3);
+INSERT INTO schema_version (ver) VALUES (16);
 CREATE TRIGGER trig_schema_version_no_insert
     BEFORE INSERT ON schema_version
     BEGIN
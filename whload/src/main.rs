@@ -2,12 +2,14 @@
 mod ms_cpp_filt;
 
 
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 use std::fs::File;
-use std::io::{BufRead, BufReader, Seek, SeekFrom};
-use std::path::PathBuf;
+use std::io::{BufRead, BufReader, BufWriter, Seek, SeekFrom, Write};
+use std::num::NonZeroUsize;
+use std::path::{Path, PathBuf};
 
 use clap::Parser;
+use indicatif::{ProgressBar, ProgressFinish, ProgressStyle};
 use rusqlite::{Connection, OpenFlags, OptionalExtension, Params, Statement};
 
 
@@ -23,6 +25,30 @@ enum Mode {
     /// Add demangled name variants to entries in an existing database.
     #[cfg(feature = "ms_cpp_filt")]
     DemangleDb(DemangleDbOpts),
+
+    /// Mark an operating system as an edition of another operating system.
+    SetOsParent(SetOsParentOpts),
+
+    /// Set the long name, release date, build number or icon availability of an operating system.
+    SetOsMetadata(SetOsMetadataOpts),
+
+    /// Recompute the precomputed data: the index displayed on the root page, the number of symbols
+    /// in each operating system, the chronological order of the operating systems and the earliest
+    /// operating system of each symbol.
+    Reindex(ReindexOpts),
+
+    /// Remove an operating system along with the symbols and DLLs only it contains.
+    Prune(PruneOpts),
+
+    /// Recompute the precomputed data, update the query planner statistics and compact the
+    /// database.
+    Maintain(MaintainOpts),
+
+    /// Write the symbols in the database to a list in the format read by `load`.
+    Export(ExportOpts),
+
+    /// Check the database for inconsistencies that the web application cannot cope with.
+    Verify(VerifyOpts),
 }
 
 #[derive(Parser)]
@@ -30,8 +56,87 @@ struct LoadOpts {
     /// The path to the SQLite database in which to store the API information.
     pub database_path: PathBuf,
 
-    /// The list of API calls.
-    pub list_path: PathBuf,
+    /// The lists of API calls.
+    ///
+    /// Lists whose names end in `.gz` are decompressed using gzip. Each list is loaded within its
+    /// own savepoint; if loading a list fails, only the changes made by that list are rolled back.
+    ///
+    /// A line may mark its DLL as belonging to a secondary platform. A DLL listed as belonging to
+    /// the primary platform by any line, in this or an earlier load, stays or becomes primary.
+    ///
+    /// The processor architecture of each symbol is taken from the line or, for DLLs of the
+    /// primary platform, from a suffix of the operating system name such as `windows11-arm64`.
+    #[arg(required = true)]
+    pub list_paths: Vec<PathBuf>,
+
+    /// Continue loading the remaining lists if loading a list fails, and commit the lists that
+    /// were loaded successfully. By default, nothing is committed if any list fails to load.
+    #[arg(long)]
+    pub continue_on_error: bool,
+
+    /// Fail loading a list if it contains a malformed line, instead of skipping the line.
+    #[arg(long)]
+    pub strict: bool,
+
+    /// The number of threads with which to demangle the names of new symbols before loading them.
+    /// Defaults to the number of available CPU cores.
+    #[arg(short, long)]
+    pub jobs: Option<NonZeroUsize>,
+
+    /// Before loading, remove all symbols from the operating systems appearing in the lists, so
+    /// that the lists replace their previous data instead of adding to it.
+    ///
+    /// The symbols of an operating system are removed when loading the first list containing it,
+    /// and only if that list is loaded successfully. Symbols and DLLs which thereby no longer appear
+    /// in any operating system are kept in the database.
+    #[arg(long)]
+    pub replace_os: bool,
+
+    /// Load the lists without committing anything to the database, reporting how the numbers of
+    /// entries in the database would have changed.
+    #[arg(long)]
+    pub dry_run: bool,
+
+    /// Report progress as plain lines of text instead of a progress bar, e.g. when logging to a
+    /// file.
+    #[arg(long)]
+    pub plain_progress: bool,
+
+    /// Speed up loading at the expense of durability by switching the database to write-ahead
+    /// logging, syncing less often and enlarging the page cache.
+    ///
+    /// Write-ahead logging remains enabled for the database after loading. Read-only connections
+    /// (such as those of the web application) then need the `-shm` and `-wal` files next to the
+    /// database, or write access to its directory to create them. Run `PRAGMA journal_mode=DELETE`
+    /// on the database to switch back.
+    #[arg(long)]
+    pub fast: bool,
+
+    /// A list of symbols to mark as deprecated once the lists have been loaded, one per line: the
+    /// raw name of a named symbol or `<dll_path>#<ordinal>` for an ordinal-only symbol (e.g.
+    /// `system32\shell32.dll#680`).
+    ///
+    /// Symbols are never unmarked; symbols not found in the database are reported and skipped.
+    #[arg(long)]
+    pub deprecated: Option<PathBuf>,
+
+    /// A tab-separated list of friendly names to assign once the lists have been loaded, one
+    /// symbol per line: the raw name of a named symbol or `<dll_path>#<ordinal>` for an
+    /// ordinal-only symbol, then the friendly name.
+    ///
+    /// The friendly names replace those obtained by demangling. Symbols not found in the database
+    /// are reported and skipped.
+    #[arg(long)]
+    pub overrides: Option<PathBuf>,
+
+    /// A tab-separated list of the long names of operating systems, one per line: the short name
+    /// (as used in the lists), then the long name (e.g. `Windows 10 version 1507`).
+    ///
+    /// Operating systems inserted while loading are given their long name; operating systems
+    /// already in the database are given their long name if they have none yet. A conflicting long
+    /// name already in the database is reported and kept; use `set-os-metadata` to change it.
+    #[arg(long)]
+    pub os_names: Option<PathBuf>,
 }
 
 #[cfg(feature = "ms_cpp_filt")]
@@ -39,6 +144,37 @@ struct LoadOpts {
 struct DemangleOpts {
     /// The name to demangle.
     pub name: String,
+
+    /// Do not output the return types of functions.
+    #[arg(long)]
+    pub no_return_type: bool,
+
+    /// Only output the name of the symbol, without its type, arguments or qualifiers.
+    #[arg(long)]
+    pub name_only: bool,
+
+    /// Do not output access specifiers (`public`, `protected`, `private`) of class members.
+    #[arg(long)]
+    pub no_access_specifiers: bool,
+}
+#[cfg(feature = "ms_cpp_filt")]
+impl DemangleOpts {
+    /// Returns the demangling flags selected by the options, starting from the LLVM-style defaults.
+    pub fn demangle_flags(&self) -> msvc_demangler::DemangleFlags {
+        use msvc_demangler::DemangleFlags;
+
+        let mut flags = DemangleFlags::llvm();
+        if self.no_return_type {
+            flags |= DemangleFlags::NO_FUNCTION_RETURNS;
+        }
+        if self.name_only {
+            flags |= DemangleFlags::NAME_ONLY;
+        }
+        if self.no_access_specifiers {
+            flags |= DemangleFlags::NO_ACCESS_SPECIFIERS;
+        }
+        flags
+    }
 }
 
 #[cfg(feature = "ms_cpp_filt")]
@@ -48,34 +184,105 @@ struct DemangleDbOpts {
     pub database_path: PathBuf,
 }
 
+#[derive(Parser)]
+struct SetOsParentOpts {
+    /// The path to the SQLite database containing the operating systems.
+    pub database_path: PathBuf,
+
+    /// The short name of the operating system which is an edition of another.
+    pub os_short_name: String,
+
+    /// The short name of the base operating system. If not given, the operating system is no longer
+    /// marked as an edition.
+    pub parent_short_name: Option<String>,
+}
+
+#[derive(Parser)]
+struct SetOsMetadataOpts {
+    /// The path to the SQLite database containing the operating systems.
+    pub database_path: PathBuf,
+
+    /// The short name of the operating system whose metadata to set.
+    pub os_short_name: String,
+
+    /// The name of the operating system as displayed to users.
+    #[arg(long)]
+    pub long_name: Option<String>,
+
+    /// The date on which the operating system was released, in ISO 8601 format (YYYY-MM-DD).
+    /// Operating systems are sorted by this date.
+    #[arg(long, value_parser = parse_release_date)]
+    pub release_date: Option<String>,
+
+    /// The version and build number of the operating system as dot-separated numbers (e.g.
+    /// 10.0.19045). Operating systems released on the same date are sorted by this number.
+    #[arg(long, value_parser = parse_build_number)]
+    pub build_number: Option<String>,
+
+    /// Whether an icon for the operating system is available.
+    #[arg(long)]
+    pub has_icon: Option<bool>,
+}
+
+#[derive(Parser)]
+struct ReindexOpts {
+    /// The path to the SQLite database whose precomputed data to recompute.
+    pub database_path: PathBuf,
+}
+
+#[derive(Parser)]
+struct PruneOpts {
+    /// The path to the SQLite database from which to remove the operating system.
+    pub database_path: PathBuf,
+
+    /// The short name of the operating system to remove.
+    pub os_short_name: String,
+}
+
+#[derive(Parser)]
+struct MaintainOpts {
+    /// The path to the SQLite database to maintain.
+    pub database_path: PathBuf,
+}
+
+#[derive(Parser)]
+struct ExportOpts {
+    /// The path to the SQLite database whose symbols to export.
+    pub database_path: PathBuf,
+
+    /// The path of the list to write.
+    pub out_path: PathBuf,
+
+    /// The short name of the operating system whose symbols to export. If not given, the symbols
+    /// of all operating systems are exported.
+    #[arg(long)]
+    pub os_short_name: Option<String>,
+}
+
+#[derive(Parser)]
+struct VerifyOpts {
+    /// The path to the SQLite database to verify.
+    pub database_path: PathBuf,
+}
+
 
-fn run_get_id_query<P: Params>(statement: &mut Statement, params: P) -> Option<i64> {
+fn run_get_id_query<P: Params>(statement: &mut Statement, params: P) -> Result<Option<i64>, String> {
     statement
         .query_one(
             params,
-            |row| {
-                let entry_id: i64 = row
-                    .get(0)
-                    .expect("failed to obtain ID from column 0");
-                Ok(entry_id)
-            },
+            |row| row.get(0),
         )
         .optional()
-        .expect("failed to run get-ID query")
+        .map_err(|e| format!("failed to run get-ID query: {}", e))
 }
 
-fn run_insert_id_query<P: Params>(statement: &mut Statement, params: P) -> i64 {
+fn run_insert_id_query<P: Params>(statement: &mut Statement, params: P) -> Result<i64, String> {
     statement
         .query_one(
             params,
-            |row| {
-                let entry_id: i64 = row
-                    .get(0)
-                    .expect("failed to obtain ID from column 0");
-                Ok(entry_id)
-            },
+            |row| row.get(0),
         )
-        .expect("failed to run insert-ID query")
+        .map_err(|e| format!("failed to run insert-ID query: {}", e))
 }
 
 fn main() {
@@ -95,21 +302,73 @@ fn main() {
         Mode::DemangleDb(opts) => {
             do_demangle_db(opts);
         },
+
+        Mode::SetOsParent(opts) => {
+            do_set_os_parent(opts);
+        },
+
+        Mode::SetOsMetadata(opts) => {
+            do_set_os_metadata(opts);
+        },
+
+        Mode::Reindex(opts) => {
+            do_reindex(opts);
+        },
+
+        Mode::Prune(opts) => {
+            do_prune(opts);
+        },
+
+        Mode::Maintain(opts) => {
+            do_maintain(opts);
+        },
+
+        Mode::Export(opts) => {
+            do_export(opts);
+        },
+
+        Mode::Verify(opts) => {
+            do_verify(opts);
+        },
     }
 }
 
 #[cfg(feature = "ms_cpp_filt")]
 fn do_demangle(opts: DemangleOpts) {
-    match crate::ms_cpp_filt::demangle_cpp_name(&opts.name) {
+    match crate::ms_cpp_filt::demangle_cpp_name(&opts.name, opts.demangle_flags()) {
         Ok(d) => println!("ISOK {}", d),
         Err(e) => println!("FAIL {}", e),
     }
 }
 
-fn do_load(opts: LoadOpts) {
+/// The migrations of the database schema; `MIGRATIONS[n]` updates the schema from version n+1 to
+/// version n+2.
+const MIGRATIONS: [&str; 15] = [
+    include_str!("../../db/migrations/r0001_to_r0002.sql"),
+    include_str!("../../db/migrations/r0002_to_r0003.sql"),
+    include_str!("../../db/migrations/r0003_to_r0004.sql"),
+    include_str!("../../db/migrations/r0004_to_r0005.sql"),
+    include_str!("../../db/migrations/r0005_to_r0006.sql"),
+    include_str!("../../db/migrations/r0006_to_r0007.sql"),
+    include_str!("../../db/migrations/r0007_to_r0008.sql"),
+    include_str!("../../db/migrations/r0008_to_r0009.sql"),
+    include_str!("../../db/migrations/r0009_to_r0010.sql"),
+    include_str!("../../db/migrations/r0010_to_r0011.sql"),
+    include_str!("../../db/migrations/r0011_to_r0012.sql"),
+    include_str!("../../db/migrations/r0012_to_r0013.sql"),
+    include_str!("../../db/migrations/r0013_to_r0014.sql"),
+    include_str!("../../db/migrations/r0014_to_r0015.sql"),
+    include_str!("../../db/migrations/r0015_to_r0016.sql"),
+];
+
+/// The current schema version, to which [`open_database`] updates the database.
+const MAX_SUPPORTED_SCHEMA: i64 = 1 + MIGRATIONS.len() as i64;
+
+/// Opens the database, creating or updating its schema as necessary.
+fn open_database(database_path: &Path) -> Connection {
     // open the SQLite database
-    let mut db = Connection::open_with_flags(
-        &opts.database_path,
+    let db = Connection::open_with_flags(
+        database_path,
         OpenFlags::SQLITE_OPEN_READ_WRITE
             | OpenFlags::SQLITE_OPEN_CREATE
             | OpenFlags::SQLITE_OPEN_EXRESCODE
@@ -127,8 +386,7 @@ fn do_load(opts: LoadOpts) {
     }
 
     // migration-execution logic
-    const MAX_SUPPORTED_SCHEMA: i64 = 3;
-    let schema_version: i64 = db.query_one(
+    let mut schema_version: i64 = db.query_one(
         "SELECT ver FROM schema_version",
         [],
         |r| r.get(0)
@@ -137,15 +395,22 @@ fn do_load(opts: LoadOpts) {
     if schema_version <= 0 {
         panic!("database has invalid schema version {}", schema_version);
     }
-    if schema_version == 1 {
-        eprintln!("updating database to schema version 2");
-        db.execute_batch(include_str!("../../db/migrations/r0001_to_r0002.sql"))
-            .expect("failed to update database schema from version 1 to 2");
+    let needs_migration = schema_version < MAX_SUPPORTED_SCHEMA;
+    while schema_version < MAX_SUPPORTED_SCHEMA {
+        eprintln!("updating database to schema version {}", schema_version + 1);
+        let migration = MIGRATIONS[usize::try_from(schema_version - 1).unwrap()];
+        if let Err(e) = db.execute_batch(migration) {
+            panic!(
+                "failed to update database schema from version {} to {}: {}",
+                schema_version, schema_version + 1, e,
+            );
+        }
+        schema_version += 1;
     }
-    if schema_version == 2 {
-        eprintln!("updating database to schema version 2");
-        db.execute_batch(include_str!("../../db/migrations/r0002_to_r0003.sql"))
-            .expect("failed to update database schema from version 2 to 3");
+    if needs_migration {
+        // migrations only change the structure; fill in the derived data they may have added
+        eprintln!("recomputing derived data");
+        reindex(&db);
     }
     if schema_version > MAX_SUPPORTED_SCHEMA {
         eprintln!(
@@ -159,301 +424,2178 @@ fn do_load(opts: LoadOpts) {
     db.pragma_update(None, "foreign_keys", true)
         .expect("failed to enable foreign-key enforcement");
 
-    // start a transaction
-    let txn = db.transaction()
-        .expect("failed to start transaction");
+    db
+}
 
-    {
-        // prepare a few statements we will be using
-        let mut query_os = txn
+/// Opens an existing database for reading without changing it, as needed by commands which only
+/// read from the database.
+///
+/// Unlike [`open_database`], neither creates nor migrates the database: reading from a mistyped path
+/// must not leave an empty database behind, and migrating also recomputes the derived data. Outputs
+/// the reason and returns `None` if the database does not exist, cannot be read or does not have
+/// the current schema version.
+fn open_database_read_only(database_path: &Path) -> Option<Connection> {
+    let db_res = Connection::open_with_flags(
+        database_path,
+        OpenFlags::SQLITE_OPEN_READ_ONLY
+            | OpenFlags::SQLITE_OPEN_EXRESCODE
+            | OpenFlags::SQLITE_OPEN_NO_MUTEX
+    );
+    let db = match db_res {
+        Ok(db) => db,
+        Err(e) => {
+            eprintln!("failed to open database {}: {}", database_path.display(), e);
+            return None;
+        },
+    };
+
+    let schema_version: i64 = match db.query_one("SELECT ver FROM schema_version", [], |r| r.get(0)) {
+        Ok(v) => v,
+        Err(e) => {
+            eprintln!("failed to query database {} for schema version: {}", database_path.display(), e);
+            return None;
+        },
+    };
+    if schema_version != MAX_SUPPORTED_SCHEMA {
+        eprintln!(
+            "database {} has schema version {} but version {} is expected; run a command which changes the database (e.g. reindex) to update it",
+            database_path.display(), schema_version, MAX_SUPPORTED_SCHEMA,
+        );
+        return None;
+    }
+
+    Some(db)
+}
+
+/// The size of the page cache when loading with `--fast`.
+///
+/// Negative values are in units of KiB; this is 256 MiB.
+const FAST_LOAD_CACHE_SIZE: i64 = -256 * 1024;
+
+/// The byte order mark, encoded in UTF-8.
+const UTF8_BYTE_ORDER_MARK: &[u8] = "\u{FEFF}".as_bytes();
+
+/// The number of symbol-DLL-operating system relationships inserted at once while loading symbols.
+///
+/// Each relationship takes up five parameters; SQLite supports up to 32766 parameters per statement.
+const RELATIONSHIP_BATCH_SIZE: usize = 500;
+
+/// The processor architectures which may be assigned to a symbol-DLL-operating system relationship.
+const ARCHITECTURES: [&str; 8] = ["x86", "x64", "arm", "arm64", "ia64", "alpha", "mips", "ppc"];
+
+/// A symbol-DLL-operating system relationship waiting to be inserted: symbol ID, DLL ID, operating
+/// system ID, ordinal and architecture.
+type Relationship = (i64, i64, i64, Option<u64>, Option<&'static str>);
+
+/// The statements used while loading symbols.
+struct LoadStatements<'c> {
+    query_os: Statement<'c>,
+    insert_os: Statement<'c>,
+    query_dll: Statement<'c>,
+    insert_dll: Statement<'c>,
+    mark_dll_primary: Statement<'c>,
+    query_named_symbol: Statement<'c>,
+    insert_named_symbol: Statement<'c>,
+    query_dll_ordinal_symbol: Statement<'c>,
+    insert_dll_ordinal_symbol: Statement<'c>,
+    insert_relationship: Statement<'c>,
+    insert_relationship_batch: Statement<'c>,
+}
+impl<'c> LoadStatements<'c> {
+    pub fn prepare(db: &'c Connection) -> Self {
+        let query_os = db
             .prepare("SELECT os_id FROM operating_systems WHERE short_name = ?1")
             .expect("failed to prepare query_os statement");
-        let mut insert_os = txn
-            .prepare("INSERT INTO operating_systems (short_name, long_name) VALUES (?1, NULL) RETURNING os_id")
+        let insert_os = db
+            .prepare("INSERT INTO operating_systems (short_name, long_name) VALUES (?1, ?2) RETURNING os_id")
             .expect("failed to prepare insert_os statement");
-        let mut query_dll = txn
+        let query_dll = db
             .prepare("SELECT dll_id FROM dlls WHERE path = ?1")
             .expect("failed to prepare query_dll statement");
-        let mut insert_dll = txn
+        let insert_dll = db
             .prepare("INSERT INTO dlls (path, secondary_platform) VALUES (?1, ?2) RETURNING dll_id")
             .expect("failed to prepare insert_dll statement");
-        let mut query_named_symbol = txn
+        let mark_dll_primary = db
+            .prepare("UPDATE dlls SET secondary_platform = 0 WHERE dll_id = ?1 AND secondary_platform <> 0")
+            .expect("failed to prepare mark_dll_primary statement");
+        let query_named_symbol = db
             .prepare("SELECT sym_id FROM symbols WHERE raw_name = ?1")
             .expect("failed to prepare query_named_symbol statement");
-        let mut insert_named_symbol = txn
+        let insert_named_symbol = db
             .prepare("INSERT INTO symbols (raw_name, dll_name, ordinal, friendly_name) VALUES (?1, NULL, NULL, ?2) RETURNING sym_id")
             .expect("failed to prepare query insert_named_symbol");
-        let mut query_dll_ordinal_symbol = txn
+        let query_dll_ordinal_symbol = db
             .prepare("SELECT sym_id FROM symbols WHERE dll_name = ?1 AND ordinal = ?2")
             .expect("failed to prepare query_dll_ordinal_symbol statement");
-        let mut insert_dll_ordinal_symbol = txn
+        let insert_dll_ordinal_symbol = db
             .prepare("INSERT INTO symbols (raw_name, dll_name, ordinal, friendly_name) VALUES (NULL, ?1, ?2, NULL) RETURNING sym_id")
             .expect("failed to prepare query insert_dll_ordinal_symbol");
-        let mut insert_relationship = txn
-            .prepare("INSERT OR IGNORE INTO symbol_dll_os (sym_id, dll_id, os_id, ordinal) VALUES (?1, ?2, ?3, ?4)")
+        let insert_relationship = db
+            .prepare("INSERT OR IGNORE INTO symbol_dll_os (sym_id, dll_id, os_id, ordinal, architecture) VALUES (?1, ?2, ?3, ?4, ?5)")
             .expect("failed to prepare query insert_relationship");
+        let insert_relationship_batch_values = vec!["(?, ?, ?, ?, ?)"; RELATIONSHIP_BATCH_SIZE].join(", ");
+        let insert_relationship_batch = db
+            .prepare(&format!("INSERT OR IGNORE INTO symbol_dll_os (sym_id, dll_id, os_id, ordinal, architecture) VALUES {}", insert_relationship_batch_values))
+            .expect("failed to prepare query insert_relationship_batch");
+        Self {
+            query_os,
+            insert_os,
+            query_dll,
+            insert_dll,
+            mark_dll_primary,
+            query_named_symbol,
+            insert_named_symbol,
+            query_dll_ordinal_symbol,
+            insert_dll_ordinal_symbol,
+            insert_relationship,
+            insert_relationship_batch,
+        }
+    }
 
-        // cache
-        let mut op_sys_to_id: BTreeMap<String, i64> = BTreeMap::new();
-        let mut dll_to_id: BTreeMap<String, i64> = BTreeMap::new();
-        let mut symbol_name_to_id: BTreeMap<String, i64> = BTreeMap::new();
-        let mut symbol_dll_to_ordinal_to_id: BTreeMap<String, BTreeMap<u64, i64>> = BTreeMap::new();
-
-        // start crunching
-        let list_file = File::open(&opts.list_path)
-            .expect("failed to open list file");
-        let mut list_reader = BufReader::new(list_file);
-
-        let file_length = list_reader.seek(SeekFrom::End(0))
-            .expect("failed to seek to the end of the input file");
-        list_reader.seek(SeekFrom::Start(0))
-            .expect("failed to seek to the start of the input file");
-
-        let mut line = String::new();
-        let mut last_file_percentage = 0;
-        let mut file_bytes_read = 0;
-        loop {
-            line.clear();
-            let bytes_read = list_reader.read_line(&mut line)
-                .expect("failed to read line");
-            if bytes_read == 0 {
-                // EOF
-                break;
+    /// Inserts the given relationships into the database, in batches of `RELATIONSHIP_BATCH_SIZE`
+    /// where possible.
+    pub fn insert_relationships(&mut self, relationships: &[Relationship]) -> Result<(), rusqlite::Error> {
+        let mut batches = relationships.chunks_exact(RELATIONSHIP_BATCH_SIZE);
+        for batch in &mut batches {
+            for (i, (symbol_id, dll_id, op_sys_id, ordinal_opt, architecture_opt)) in batch.iter().enumerate() {
+                // parameters are numbered starting at 1
+                self.insert_relationship_batch.raw_bind_parameter(5*i + 1, symbol_id)?;
+                self.insert_relationship_batch.raw_bind_parameter(5*i + 2, dll_id)?;
+                self.insert_relationship_batch.raw_bind_parameter(5*i + 3, op_sys_id)?;
+                self.insert_relationship_batch.raw_bind_parameter(5*i + 4, ordinal_opt)?;
+                self.insert_relationship_batch.raw_bind_parameter(5*i + 5, architecture_opt)?;
             }
+            self.insert_relationship_batch.raw_execute()?;
+        }
+        for relationship in batches.remainder() {
+            self.insert_relationship.execute(*relationship)?;
+        }
+        Ok(())
+    }
+}
 
-            // output progress
-            file_bytes_read += u64::try_from(bytes_read).unwrap();
-            let now_file_percentage = (file_bytes_read * 100) / file_length;
-            if last_file_percentage < now_file_percentage {
-                last_file_percentage = now_file_percentage;
-                eprintln!("{}%", now_file_percentage);
-            }
+/// The IDs of database entries already known while loading symbols.
+#[derive(Default)]
+struct LoadCache {
+    op_sys_to_id: BTreeMap<String, i64>,
+    dll_to_id: BTreeMap<String, i64>,
+    primary_dll_ids: BTreeSet<i64>,
+    symbol_name_to_id: BTreeMap<String, i64>,
+    symbol_dll_to_ordinal_to_id: BTreeMap<String, BTreeMap<u64, i64>>,
+}
 
-            // strip trailing newlines
-            while line.ends_with(&['\r', '\n']) {
-                line.pop();
-            }
-            if line.len() == 0 {
-                continue;
-            }
+/// The numbers of entries of each kind in the database.
+struct RowCounts {
+    operating_systems: i64,
+    dlls: i64,
+    named_symbols: i64,
+    ordinal_symbols: i64,
+    relationships: i64,
+}
+impl RowCounts {
+    pub fn obtain(db: &Connection) -> Self {
+        db
+            .query_one(
+                "
+                    SELECT
+                        (SELECT COUNT(*) FROM operating_systems),
+                        (SELECT COUNT(*) FROM dlls),
+                        (SELECT COUNT(*) FROM symbols WHERE raw_name IS NOT NULL),
+                        (SELECT COUNT(*) FROM symbols WHERE raw_name IS NULL),
+                        (SELECT COUNT(*) FROM symbol_dll_os)
+                ",
+                (),
+                |row| Ok(Self {
+                    operating_systems: row.get(0)?,
+                    dlls: row.get(1)?,
+                    named_symbols: row.get(2)?,
+                    ordinal_symbols: row.get(3)?,
+                    relationships: row.get(4)?,
+                }),
+            )
+            .expect("failed to count entries")
+    }
 
-            let fields: Vec<&str> = line.split("\t").collect();
-            if fields.len() != 3 {
-                panic!("line {:?} does not have 3 fields", line);
-            }
+    /// Returns a description of each kind of entry along with its count in `self` and in `other`.
+    pub fn describe_with(&self, other: &Self) -> [(&'static str, i64, i64); 5] {
+        [
+            ("operating system(s)", self.operating_systems, other.operating_systems),
+            ("DLL(s)", self.dlls, other.dlls),
+            ("named symbol(s)", self.named_symbols, other.named_symbols),
+            ("ordinal-only symbol(s)", self.ordinal_symbols, other.ordinal_symbols),
+            ("symbol-DLL-operating system relationship(s)", self.relationships, other.relationships),
+        ]
+    }
+}
+
+fn do_load(opts: LoadOpts) {
+    if !load_lists(&opts) {
+        std::process::exit(1);
+    }
+}
+
+/// Loads the lists as specified by the options, returning whether all of them (and the files of
+/// deprecated symbols and friendly name overrides) have been processed successfully.
+fn load_lists(opts: &LoadOpts) -> bool {
+    let os_long_names = match opts.os_names.as_deref() {
+        Some(os_names_path) => match read_os_long_names(os_names_path) {
+            Ok(names) => names,
+            Err(e) => {
+                eprintln!("failed to read operating system names from {}: {}", os_names_path.display(), e);
+                std::process::exit(1);
+            },
+        },
+        None => BTreeMap::new(),
+    };
+
+    let mut db = open_database(&opts.database_path);
+
+    if opts.fast {
+        db.pragma_update_and_check(None, "journal_mode", "WAL", |_row| Ok(()))
+            .expect("failed to enable write-ahead logging");
+        db.pragma_update(None, "synchronous", "NORMAL")
+            .expect("failed to reduce synchronization");
+        db.pragma_update(None, "cache_size", FAST_LOAD_CACHE_SIZE)
+            .expect("failed to enlarge page cache");
+    }
+
+    // start a transaction
+    let txn = db.transaction()
+        .expect("failed to start transaction");
+
+    let mut succeeded: Vec<&Path> = Vec::with_capacity(opts.list_paths.len());
+    let mut failed: Vec<(&Path, String)> = Vec::new();
+    let mut skipped_lines: u64 = 0;
+    let counts_before_opt = if opts.dry_run {
+        Some(RowCounts::obtain(&txn))
+    } else {
+        None
+    };
+    {
+        // prepare a few statements we will be using
+        // (prepared statements remain valid across savepoints)
+        let mut statements = LoadStatements::prepare(&txn);
 
-            let path_parts: Vec<String> = serde_json::from_str(&fields[0])
-                .expect("failed to parse field 0 as JSON");
-            if path_parts.len() != 1 {
-                panic!("expected a single-part file path");
+        // cache
+        let mut cache = LoadCache::default();
+
+        // the operating systems whose symbols have already been replaced by those of a list loaded
+        // previously, which must not be removed again
+        let mut replaced_os_names: BTreeSet<String> = BTreeSet::new();
+
+        if !os_long_names.is_empty() {
+            apply_existing_os_long_names(&txn, &os_long_names);
+        }
+
+        let jobs = opts.jobs
+            .or_else(|| std::thread::available_parallelism().ok())
+            .unwrap_or(NonZeroUsize::MIN);
+
+        for list_path in &opts.list_paths {
+            eprintln!("loading {}", list_path.display());
+
+            // each file gets a savepoint so that a failure only rolls back that file
+            txn.execute_batch("SAVEPOINT load_file")
+                .expect("failed to create savepoint");
+            let result = read_list(list_path, opts.strict)
+                .and_then(|contents| {
+                    let new_os_names: BTreeSet<String> = if opts.replace_os {
+                        contents.lines.iter()
+                            .map(|numbered_line| &numbered_line.line.operating_system)
+                            .filter(|os_name| !replaced_os_names.contains(*os_name))
+                            .cloned()
+                            .collect()
+                    } else {
+                        BTreeSet::new()
+                    };
+                    remove_os_symbols(&txn, &new_os_names)?;
+
+                    // demangling is expensive; do it for all the new symbols at once, in parallel
+                    let demangled_names = demangle_new_symbols(&contents, &mut statements, jobs)?;
+                    load_file(&contents, opts.plain_progress, &demangled_names, &os_long_names, &mut statements, &mut cache)?;
+                    Ok((contents.skipped_lines, new_os_names))
+                });
+            match result {
+                Ok((skipped, new_os_names)) => {
+                    skipped_lines += skipped;
+                    replaced_os_names.extend(new_os_names);
+                    txn.execute_batch("RELEASE load_file")
+                        .expect("failed to release savepoint");
+                    succeeded.push(list_path);
+                },
+                Err(e) => {
+                    eprintln!("failed to load {}: {}", list_path.display(), e);
+                    txn.execute_batch("ROLLBACK TO load_file; RELEASE load_file")
+                        .expect("failed to roll back to savepoint");
+
+                    // the cache may contain IDs of rows that have just been rolled back
+                    cache = LoadCache::default();
+
+                    failed.push((list_path, e));
+                    if !opts.continue_on_error {
+                        break;
+                    }
+                },
             }
-            let dll_path = &path_parts[0];
+        }
 
-            let symbol_name_opt = if fields[2].len() > 0 {
-                Some(fields[2])
-            } else {
-                None
-            };
-            let ordinal_opt: Option<u64> = if fields[1].len() > 0 {
-                Some(
-                    fields[1]
-                        .parse()
-                        .expect("failed to parse ordinal")
-                )
-            } else {
-                None
-            };
+        // (only if the lists are to be committed)
+        let lists_loaded = failed.is_empty() || opts.continue_on_error;
+        if lists_loaded && let Some(deprecated_path) = opts.deprecated.as_deref() {
+            eprintln!("marking deprecated symbols from {}", deprecated_path.display());
 
-            // decode the operating system from the path
-            let dll_path_lower = dll_path
-                .to_lowercase()
-                .replace("/", "\\");
-            let path_pieces: Vec<&str> = dll_path_lower
-                .split("\\")
-                .collect();
-            if path_pieces.len() < 2 {
-                panic!("expected at least two path pieces");
+            // like a list, a failure must not leave some of the symbols marked
+            txn.execute_batch("SAVEPOINT mark_deprecated")
+                .expect("failed to create savepoint");
+            match mark_deprecated_symbols(&txn, deprecated_path) {
+                Ok(unknown_count) => {
+                    txn.execute_batch("RELEASE mark_deprecated")
+                        .expect("failed to release savepoint");
+                    if unknown_count > 0 {
+                        eprintln!("skipped {} unknown deprecated symbol(s)", unknown_count);
+                    }
+                },
+                Err(e) => {
+                    eprintln!("failed to mark deprecated symbols: {}", e);
+                    txn.execute_batch("ROLLBACK TO mark_deprecated; RELEASE mark_deprecated")
+                        .expect("failed to roll back to savepoint");
+                    failed.push((deprecated_path, e));
+                },
             }
-            let operating_system = path_pieces[0];
-            let dll_path = path_pieces[1..].join("\\");
+        }
+        if lists_loaded && let Some(overrides_path) = opts.overrides.as_deref() {
+            eprintln!("overriding friendly names from {}", overrides_path.display());
 
-            // find operating system ID
-            let op_sys_id = if let Some(osi) = op_sys_to_id.get(operating_system) {
-                *osi
-            } else {
-                let op_sys_id_opt = run_get_id_query(
-                    &mut query_os,
-                    [operating_system],
-                );
-                let op_sys_id = match op_sys_id_opt {
-                    Some(osi) => osi,
-                    None => {
-                        run_insert_id_query(
-                            &mut insert_os,
-                            [operating_system],
-                        )
-                    },
-                };
-                op_sys_to_id.insert(
-                    operating_system.to_owned(),
-                    op_sys_id,
-                );
-                op_sys_id
-            };
+            txn.execute_batch("SAVEPOINT override_names")
+                .expect("failed to create savepoint");
+            match override_friendly_names(&txn, overrides_path) {
+                Ok(unknown_count) => {
+                    txn.execute_batch("RELEASE override_names")
+                        .expect("failed to release savepoint");
+                    if unknown_count > 0 {
+                        eprintln!("skipped {} unknown overridden symbol(s)", unknown_count);
+                    }
+                },
+                Err(e) => {
+                    eprintln!("failed to override friendly names: {}", e);
+                    txn.execute_batch("ROLLBACK TO override_names; RELEASE override_names")
+                        .expect("failed to roll back to savepoint");
+                    failed.push((overrides_path, e));
+                },
+            }
+        }
+    }
 
-            // find DLL ID
-            let dll_id = if let Some(di) = dll_to_id.get(&dll_path) {
-                *di
-            } else {
-                let dll_id_opt = run_get_id_query(
-                    &mut query_dll,
-                    [dll_path.as_str()],
-                );
-                const NOT_A_SECONDARY_PLATFORM: bool = false;
-                let dll_id = match dll_id_opt {
-                    Some(di) => di,
-                    None => {
-                        run_insert_id_query(
-                            &mut insert_dll,
-                            (dll_path.as_str(), NOT_A_SECONDARY_PLATFORM),
-                        )
-                    },
-                };
-                dll_to_id.insert(dll_path.clone(), dll_id);
-                dll_id
-            };
+    if let Some(counts_before) = counts_before_opt {
+        let counts_after = RowCounts::obtain(&txn);
+        txn.rollback()
+            .expect("rolling back transaction failed");
 
-            // find symbol ID
-            let symbol_id = if let Some(symbol_name) = symbol_name_opt {
-                // this is a named symbol
-                if let Some(sid) = symbol_name_to_id.get(symbol_name) {
-                    *sid
-                } else {
-                    let named_id_opt = run_get_id_query(
-                        &mut query_named_symbol,
-                        [symbol_name],
-                    );
-                    let sym_id = match named_id_opt {
-                        Some(ni) => ni,
-                        None => {
-                            // we don't know this symbol yet
-                            // try demangling it to obtain a friendly name
-                            let friendly_name = try_demangle(symbol_name);
-
-                            run_insert_id_query(
-                                &mut insert_named_symbol,
-                                (symbol_name, friendly_name),
-                            )
-                        },
-                    };
-                    symbol_name_to_id.insert(symbol_name.to_owned(), sym_id);
-                    sym_id
-                }
-            } else if let Some(ordinal) = ordinal_opt {
-                // this is an unnamed symbol with an ordinal within its DLL
-                let final_dll_name = *path_pieces.last().unwrap();
-                let sid_opt = symbol_dll_to_ordinal_to_id
-                    .get(final_dll_name)
-                    .and_then(|otoid| otoid.get(&ordinal));
-                if let Some(sid) = sid_opt {
-                    *sid
-                } else {
-                    let ordinal_id_opt = run_get_id_query(
-                        &mut query_dll_ordinal_symbol,
-                        (final_dll_name, ordinal),
-                    );
-                    let sid = match ordinal_id_opt {
-                        Some(oi) => oi,
-                        None => {
-                            run_insert_id_query(
-                                &mut insert_dll_ordinal_symbol,
-                                (final_dll_name, ordinal),
-                            )
-                        }
-                    };
-                    symbol_dll_to_ordinal_to_id
-                        .entry(final_dll_name.to_owned())
-                        .or_insert_with(|| BTreeMap::new())
-                        .insert(ordinal, sid);
-                    sid
-                }
-            } else {
-                panic!("symbol in {:?} with neither name nor ordinal", path_parts);
-            };
+        eprintln!("dry run: nothing has been committed; the database would have changed by:");
+        for (description, before, after) in counts_before.describe_with(&counts_after) {
+            eprintln!("  {:+} {}", after - before, description);
+        }
+    } else if failed.is_empty() || opts.continue_on_error {
+        // the data has changed; update the index
+        reindex(&txn);
+
+        // and we're done
+        txn.commit()
+            .expect("committing transaction failed");
+    } else {
+        txn.rollback()
+            .expect("rolling back transaction failed");
+    }
 
-            // now insert a new row that merges it all
-            if let Err(e) = insert_relationship.execute((symbol_id, dll_id, op_sys_id, ordinal_opt)) {
-                panic!("failed to add relationship: {:?}/{:?}#{:?}, {}, {}: {:?}", symbol_name_opt, path_parts, ordinal_opt, operating_system, dll_path, e);
+    // report
+    if skipped_lines > 0 {
+        eprintln!("skipped {} malformed line(s)", skipped_lines);
+    }
+    if !failed.is_empty() {
+        if opts.continue_on_error {
+            eprintln!("loaded {} file(s):", succeeded.len());
+            for path in &succeeded {
+                eprintln!("  {}", path.display());
             }
+        } else {
+            eprintln!("no files have been loaded");
         }
+        eprintln!("failed to load {} file(s):", failed.len());
+        for (path, error) in &failed {
+            eprintln!("  {}: {}", path.display(), error);
+        }
+        return false;
     }
+    true
+}
 
-    // and we're done
-    txn.commit()
-        .expect("committing transaction failed");
+/// Opens a list file for reading, returning the reader and, if known in advance, the number of
+/// bytes that will be read.
+///
+/// A UTF-8 byte order mark at the start of the list (as written by some Windows tools) is skipped.
+fn open_list(list_path: &Path) -> Result<(Box<dyn BufRead>, Option<u64>), String> {
+    let list_file = File::open(list_path)
+        .map_err(|e| format!("failed to open list file: {}", e))?;
+    let is_gzip = list_path.extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("gz"));
+
+    // the length of the decompressed data is unknown, so progress can only be given in bytes for
+    // plain-text files
+    let (mut list_reader, file_length_opt): (Box<dyn BufRead>, Option<u64>) = if is_gzip {
+        (open_gzip(list_file)?, None)
+    } else {
+        let mut file_reader = BufReader::new(list_file);
+        let file_length = file_reader.seek(SeekFrom::End(0))
+            .map_err(|e| format!("failed to seek to the end of the input file: {}", e))?;
+        file_reader.seek(SeekFrom::Start(0))
+            .map_err(|e| format!("failed to seek to the start of the input file: {}", e))?;
+        (Box::new(file_reader), Some(file_length))
+    };
+
+    // otherwise, the byte order mark ends up in the name of the first operating system
+    let start = list_reader.fill_buf()
+        .map_err(|e| format!("failed to read the start of the input file: {}", e))?;
+    if start.starts_with(UTF8_BYTE_ORDER_MARK) {
+        list_reader.consume(UTF8_BYTE_ORDER_MARK.len());
+        let bom_length = u64::try_from(UTF8_BYTE_ORDER_MARK.len()).unwrap();
+        Ok((list_reader, file_length_opt.map(|fl| fl - bom_length)))
+    } else {
+        Ok((list_reader, file_length_opt))
+    }
 }
 
-#[cfg(feature = "ms_cpp_filt")]
-fn try_demangle(symbol: &str) -> Option<String> {
-    crate::ms_cpp_filt::demangle_cpp_name(symbol).ok()
+/// Wraps a gzip-compressed list file into a reader that decompresses it.
+#[cfg(feature = "gzip")]
+fn open_gzip(list_file: File) -> Result<Box<dyn BufRead>, String> {
+    Ok(Box::new(BufReader::new(flate2::read::GzDecoder::new(list_file))))
 }
 
-#[cfg(not(feature = "ms_cpp_filt"))]
-fn try_demangle(_symbol: &str) -> Option<String> {
-    None
+/// Fails because gzip support has not been compiled in.
+#[cfg(not(feature = "gzip"))]
+fn open_gzip(_list_file: File) -> Result<Box<dyn BufRead>, String> {
+    Err("cannot read gzip-compressed list file: whload has been built without the gzip feature".to_owned())
 }
 
-#[cfg(feature = "ms_cpp_filt")]
-fn do_demangle_db(opts: DemangleDbOpts) {
-    // open the SQLite database
-    let mut db = Connection::open_with_flags(
-        &opts.database_path,
-        OpenFlags::SQLITE_OPEN_READ_WRITE
-            | OpenFlags::SQLITE_OPEN_CREATE
-            | OpenFlags::SQLITE_OPEN_EXRESCODE
-            | OpenFlags::SQLITE_OPEN_NO_MUTEX
-    )
-        .expect("failed to open SQLite database");
+/// A line of a list file, describing a symbol exported by a DLL in an operating system.
+struct ListLine {
+    pub operating_system: String,
+    pub dll_path: String,
+    pub ordinal: Option<u64>,
+    pub symbol_name: Option<String>,
+    pub secondary_platform: bool,
+    pub architecture: Option<&'static str>,
+}
 
-    // enable foreign-key enforcement
-    db.pragma_update(None, "foreign_keys", true)
-        .expect("failed to enable foreign-key enforcement");
+/// Parses a line of a list file.
+///
+/// The line consists of three tab-separated fields: a JSON array containing the path of the DLL
+/// (whose first component is the operating system), the ordinal and the symbol name. Either the
+/// ordinal or the symbol name may be empty, but not both.
+///
+/// The path may be given as a single element (`["windows10\\system32\\kernel32.dll"]`) or split
+/// across multiple elements (`["windows10", "system32", "kernel32.dll"]`), which are joined with
+/// backslashes.
+///
+/// Leading and trailing ASCII whitespace is stripped from the symbol name, so that symbols differing
+/// only by stray whitespace are considered the same. A symbol name consisting only of whitespace
+/// makes the line malformed.
+///
+/// An optional fourth field specifies the platform of the DLL: `secondary` if the DLL belongs to a
+/// secondary platform (e.g. a 32-bit DLL on a 64-bit operating system), `primary` or empty if not.
+///
+/// An optional fifth field specifies the processor architecture of the DLL (one of
+/// [`ARCHITECTURES`]). If it is missing or empty, DLLs of the primary platform take on the
+/// architecture given as a suffix of the operating system (e.g. `windows11-arm64`), if any; the
+/// architecture of DLLs of secondary platforms remains unknown.
+fn parse_list_line(line: &str) -> Result<ListLine, String> {
+    let fields: Vec<&str> = line.split("\t").collect();
+    if fields.len() < 3 || fields.len() > 5 {
+        return Err(format!("expected 3 to 5 fields, found {}", fields.len()));
+    }
 
-    // start a transaction
-    let txn = db.transaction()
-        .expect("failed to start transaction");
+    let path_parts: Vec<String> = serde_json::from_str(fields[0])
+        .map_err(|e| format!("failed to parse field 0 as JSON: {}", e))?;
+    if path_parts.is_empty() {
+        return Err("expected a file path, found an empty array".to_owned());
+    }
+    let dll_path = path_parts.join("\\");
 
-    {
-        // prepare a few statements we will be using
-        let mut query_named_symbols_without_friendly_name = txn
-            .prepare("SELECT sym_id, raw_name FROM symbols WHERE raw_name IS NOT NULL AND friendly_name IS NULL")
-            .expect("failed to prepare query_named_symbols_without_friendly_name statement");
-        let mut set_symbol_friendly_name = txn
-            .prepare("UPDATE symbols SET friendly_name = ?1 WHERE sym_id = ?2")
-            .expect("failed to prepare query set_symbol_friendly_name");
+    let symbol_name = if !fields[2].is_empty() {
+        let trimmed_name = fields[2].trim_ascii();
+        if trimmed_name.is_empty() {
+            return Err(format!("symbol name {:?} consists only of whitespace", fields[2]));
+        }
+        Some(trimmed_name.to_owned())
+    } else {
+        None
+    };
+    let ordinal: Option<u64> = if !fields[1].is_empty() {
+        Some(
+            fields[1]
+                .parse()
+                .map_err(|e| format!("failed to parse ordinal {:?}: {}", fields[1], e))?
+        )
+    } else {
+        None
+    };
+    if symbol_name.is_none() && ordinal.is_none() {
+        return Err(format!("symbol in {:?} with neither name nor ordinal", dll_path));
+    }
+    let secondary_platform = match fields.get(3).copied() {
+        None | Some("") | Some("primary") => false,
+        Some("secondary") => true,
+        Some(other) => return Err(format!("unknown platform {:?}", other)),
+    };
+    let given_architecture = match fields.get(4).copied() {
+        None | Some("") => None,
+        Some(arch) => match find_architecture(arch) {
+            Some(known_arch) => Some(known_arch),
+            None => return Err(format!("unknown architecture {:?}", arch)),
+        },
+    };
 
-        let mut symbol_rows = query_named_symbols_without_friendly_name.query(())
-            .expect("failed to query named symbols");
-        loop {
-            let row_opt = symbol_rows.next()
-                .expect("failed to obtain row");
-            let Some(row) = row_opt else { break };
+    // decode the operating system from the path
+    let dll_path_lower = dll_path
+        .to_lowercase()
+        .replace("/", "\\");
+    let path_pieces: Vec<&str> = dll_path_lower
+        .split("\\")
+        .collect();
+    if path_pieces.len() < 2 {
+        return Err(format!("expected at least two path pieces in {:?}", dll_path));
+    }
+    let architecture = if given_architecture.is_some() {
+        given_architecture
+    } else if !secondary_platform {
+        path_pieces[0]
+            .rsplit_once('-')
+            .and_then(|(_os, suffix)| find_architecture(suffix))
+    } else {
+        None
+    };
 
-            let symbol_id: i64 = row.get(0)
-                .expect("failed to obtain symbol ID from row");
-            let raw_name: String = row.get(1)
-                .expect("failed to obtain raw name from row");
+    Ok(ListLine {
+        operating_system: path_pieces[0].to_owned(),
+        dll_path: path_pieces[1..].join("\\"),
+        ordinal,
+        symbol_name,
+        secondary_platform,
+        architecture,
+    })
+}
 
-            let Ok(demangled) = crate::ms_cpp_filt::demangle_cpp_name(&raw_name)
-                else { continue };
+/// Returns the entry of [`ARCHITECTURES`] matching the given architecture name, ignoring case.
+fn find_architecture(name: &str) -> Option<&'static str> {
+    ARCHITECTURES
+        .iter()
+        .copied()
+        .find(|arch| arch.eq_ignore_ascii_case(name))
+}
 
-            set_symbol_friendly_name
-                .execute((demangled.as_str(), symbol_id))
-                .expect("failed to set friendly name");
+/// Reports the progress of loading a list file.
+enum LoadProgress {
+    /// Plain lines of text: percentages if the length of the file is known, line counts otherwise.
+    Plain {
+        file_length_opt: Option<u64>,
+        file_bytes_read: u64,
+        last_percentage: u64,
+    },
+
+    /// A progress bar if the length of the file is known, a spinner otherwise. Cleared once loading
+    /// the file has finished.
+    Bar(ProgressBar),
+}
+impl LoadProgress {
+    /// How often to report progress as plain text if the length of the list is unknown.
+    const PLAIN_LINE_INTERVAL: u64 = 100_000;
+
+    pub fn new(plain: bool, file_length_opt: Option<u64>) -> Self {
+        if plain {
+            return Self::Plain {
+                file_length_opt,
+                file_bytes_read: 0,
+                last_percentage: 0,
+            };
         }
+
+        let bar = match file_length_opt {
+            Some(file_length) => {
+                let style = ProgressStyle::with_template("{wide_bar} {bytes}/{total_bytes} ({eta})")
+                    .expect("invalid progress bar template");
+                ProgressBar::new(file_length)
+                    .with_style(style)
+            },
+            None => {
+                let style = ProgressStyle::with_template("{spinner} {human_pos} lines")
+                    .expect("invalid spinner template");
+                ProgressBar::new_spinner()
+                    .with_style(style)
+            },
+        };
+        Self::Bar(bar.with_finish(ProgressFinish::AndClear))
     }
 
-    // and we're done
-    txn.commit()
-        .expect("committing transaction failed");
+    /// Registers that a line of the given length, with the given (one-based) line number, has been
+    /// read.
+    pub fn advance(&mut self, bytes_read: u64, line_number: u64) {
+        match self {
+            Self::Plain { file_length_opt: Some(file_length), file_bytes_read, last_percentage } => {
+                *file_bytes_read += bytes_read;
+                let now_percentage = (*file_bytes_read * 100) / *file_length;
+                if *last_percentage < now_percentage {
+                    *last_percentage = now_percentage;
+                    eprintln!("{}%", now_percentage);
+                }
+            },
+            Self::Plain { file_length_opt: None, .. } => {
+                if line_number.is_multiple_of(Self::PLAIN_LINE_INTERVAL) {
+                    eprintln!("{} lines", line_number);
+                }
+            },
+            Self::Bar(bar) => {
+                if bar.length().is_some() {
+                    bar.inc(bytes_read);
+                } else {
+                    bar.set_position(line_number);
+                }
+            },
+        }
+    }
+}
+
+/// A well-formed line of a list file along with its position in the file.
+struct NumberedListLine {
+    /// The one-based number of the line.
+    pub line_number: u64,
+
+    /// The number of bytes read for this line, including those of any blank or malformed lines
+    /// immediately preceding it.
+    pub bytes_read: u64,
+
+    pub line: ListLine,
+}
+
+/// The contents of a list file, read into memory so that the file is only read (and decompressed)
+/// once even though it is processed in multiple passes.
+struct ListContents {
+    pub lines: Vec<NumberedListLine>,
+    pub file_length_opt: Option<u64>,
+    pub skipped_lines: u64,
+}
+
+/// Reads and parses a list file.
+///
+/// If `strict` is set, a malformed line is an error; otherwise, it is reported and skipped.
+fn read_list(list_path: &Path, strict: bool) -> Result<ListContents, String> {
+    let (mut list_reader, file_length_opt) = open_list(list_path)?;
+
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    let mut line_number: u64 = 0;
+    let mut skipped_lines: u64 = 0;
+    let mut pending_bytes: u64 = 0;
+    loop {
+        line.clear();
+        line_number += 1;
+        let bytes_read = list_reader.read_line(&mut line)
+            .map_err(|e| format!("failed to read line: {}", e))?;
+        if bytes_read == 0 {
+            // EOF
+            break;
+        }
+        pending_bytes += u64::try_from(bytes_read).unwrap();
+
+        // strip trailing newlines
+        while line.ends_with(['\r', '\n']) {
+            line.pop();
+        }
+        if line.is_empty() {
+            continue;
+        }
+
+        match parse_list_line(&line) {
+            Ok(list_line) => {
+                lines.push(NumberedListLine {
+                    line_number,
+                    bytes_read: pending_bytes,
+                    line: list_line,
+                });
+                pending_bytes = 0;
+            },
+            Err(e) => {
+                if strict {
+                    return Err(format!("line {}: {}", line_number, e));
+                }
+                eprintln!(
+                    "{}:{}: skipping malformed line {:?}: {}",
+                    list_path.display(), line_number, line, e,
+                );
+                skipped_lines += 1;
+            },
+        }
+    }
+
+    Ok(ListContents {
+        lines,
+        file_length_opt,
+        skipped_lines,
+    })
+}
+
+/// Loads the symbols from the contents of a single list file.
+///
+/// If `plain_progress` is set, progress is reported as lines of text instead of a progress bar. The
+/// friendly names of new symbols are taken from `demangled_names` if present there.
+///
+/// On error, the database may contain a part of the file's symbols; the caller is responsible for
+/// rolling them back.
+fn load_file(contents: &ListContents, plain_progress: bool, demangled_names: &BTreeMap<String, Option<String>>, os_long_names: &BTreeMap<String, String>, statements: &mut LoadStatements, cache: &mut LoadCache) -> Result<(), String> {
+    let mut progress = LoadProgress::new(plain_progress, contents.file_length_opt);
+
+    let mut line_number: u64 = 0;
+    let mut relationships: Vec<Relationship> = Vec::with_capacity(RELATIONSHIP_BATCH_SIZE);
+    for numbered_line in &contents.lines {
+        line_number = numbered_line.line_number;
+        let list_line = &numbered_line.line;
+
+        // output progress
+        progress.advance(numbered_line.bytes_read, line_number);
+
+        let operating_system = list_line.operating_system.as_str();
+        let dll_path = list_line.dll_path.as_str();
+        let symbol_name_opt = list_line.symbol_name.as_deref();
+        let ordinal_opt = list_line.ordinal;
+
+        // find operating system ID
+        let op_sys_id = if let Some(osi) = cache.op_sys_to_id.get(operating_system) {
+            *osi
+        } else {
+            let op_sys_id_opt = run_get_id_query(
+                &mut statements.query_os,
+                [operating_system],
+            )?;
+            let op_sys_id = match op_sys_id_opt {
+                Some(osi) => osi,
+                None => {
+                    run_insert_id_query(
+                        &mut statements.insert_os,
+                        (operating_system, os_long_names.get(operating_system)),
+                    )?
+                },
+            };
+            cache.op_sys_to_id.insert(
+                operating_system.to_owned(),
+                op_sys_id,
+            );
+            op_sys_id
+        };
+
+        // find DLL ID
+        let dll_id = if let Some(di) = cache.dll_to_id.get(dll_path) {
+            *di
+        } else {
+            let dll_id_opt = run_get_id_query(
+                &mut statements.query_dll,
+                [dll_path],
+            )?;
+            let dll_id = match dll_id_opt {
+                Some(di) => di,
+                None => {
+                    run_insert_id_query(
+                        &mut statements.insert_dll,
+                        (dll_path, list_line.secondary_platform),
+                    )?
+                },
+            };
+            cache.dll_to_id.insert(dll_path.to_owned(), dll_id);
+            dll_id
+        };
+
+        // a DLL listed as primary anywhere is primary; it only belongs to a secondary platform if
+        // it is listed as such everywhere
+        if !list_line.secondary_platform && cache.primary_dll_ids.insert(dll_id) {
+            statements.mark_dll_primary
+                .execute([dll_id])
+                .map_err(|e| format!("failed to mark DLL as primary: {}", e))?;
+        }
+
+        // find symbol ID
+        let symbol_id = if let Some(symbol_name) = symbol_name_opt {
+            // this is a named symbol
+            if let Some(sid) = cache.symbol_name_to_id.get(symbol_name) {
+                *sid
+            } else {
+                let named_id_opt = run_get_id_query(
+                    &mut statements.query_named_symbol,
+                    [symbol_name],
+                )?;
+                let sym_id = match named_id_opt {
+                    Some(ni) => ni,
+                    None => {
+                        // we don't know this symbol yet
+                        // try demangling it to obtain a friendly name
+                        let friendly_name = match demangled_names.get(symbol_name) {
+                            Some(demangled) => demangled.clone(),
+                            None => try_demangle(symbol_name),
+                        };
+
+                        run_insert_id_query(
+                            &mut statements.insert_named_symbol,
+                            (symbol_name, friendly_name),
+                        )?
+                    },
+                };
+                cache.symbol_name_to_id.insert(symbol_name.to_owned(), sym_id);
+                sym_id
+            }
+        } else if let Some(ordinal) = ordinal_opt {
+            // this is an unnamed symbol with an ordinal within its DLL
+            // (identified by the full DLL path, since same-named DLLs in different directories
+            // need not export the same functions at the same ordinals)
+            let sid_opt = cache.symbol_dll_to_ordinal_to_id
+                .get(dll_path)
+                .and_then(|otoid| otoid.get(&ordinal));
+            if let Some(sid) = sid_opt {
+                *sid
+            } else {
+                let ordinal_id_opt = run_get_id_query(
+                    &mut statements.query_dll_ordinal_symbol,
+                    (dll_path, ordinal),
+                )?;
+                let sid = match ordinal_id_opt {
+                    Some(oi) => oi,
+                    None => {
+                        run_insert_id_query(
+                            &mut statements.insert_dll_ordinal_symbol,
+                            (dll_path, ordinal),
+                        )?
+                    }
+                };
+                cache.symbol_dll_to_ordinal_to_id
+                    .entry(dll_path.to_owned())
+                    .or_default()
+                    .insert(ordinal, sid);
+                sid
+            }
+        } else {
+            unreachable!("parse_list_line lets no symbol without name or ordinal through");
+        };
+
+        // now queue up a new row that merges it all
+        relationships.push((symbol_id, dll_id, op_sys_id, ordinal_opt, list_line.architecture));
+        if relationships.len() == RELATIONSHIP_BATCH_SIZE {
+            if let Err(e) = statements.insert_relationships(&relationships) {
+                return Err(format!("failed to add relationships up to line {}: {:?}", line_number, e));
+            }
+            relationships.clear();
+        }
+    }
+
+    if let Err(e) = statements.insert_relationships(&relationships) {
+        return Err(format!("failed to add relationships up to line {}: {:?}", line_number, e));
+    }
+
+    Ok(())
+}
+
+/// Marks the symbols listed in the given file as deprecated, returning the number of listed symbols
+/// that are not in the database.
+///
+/// Each line contains the raw name of a named symbol or `<dll_path>#<ordinal>` for an ordinal-only
+/// symbol; a line is only taken as the latter if no named symbol matches it.
+fn mark_deprecated_symbols(db: &Connection, deprecated_path: &Path) -> Result<u64, String> {
+    let (list_reader, _file_length_opt) = open_list(deprecated_path)?;
+    let mut mark_named = db
+        .prepare("UPDATE symbols SET deprecated = 1 WHERE raw_name = ?1")
+        .map_err(|e| format!("failed to prepare mark_named statement: {}", e))?;
+    let mut mark_dll_ordinal = db
+        .prepare("UPDATE symbols SET deprecated = 1 WHERE dll_name = ?1 AND ordinal = ?2")
+        .map_err(|e| format!("failed to prepare mark_dll_ordinal statement: {}", e))?;
+
+    let mut unknown_count: u64 = 0;
+    for (line_index, line_res) in list_reader.lines().enumerate() {
+        let line = line_res
+            .map_err(|e| format!("failed to read line {}: {}", line_index + 1, e))?;
+        let name = line.trim_ascii();
+        if name.is_empty() {
+            continue;
+        }
+
+        let mut marked_count = mark_named.execute([name])
+            .map_err(|e| format!("failed to mark {:?} as deprecated: {}", name, e))?;
+        if marked_count == 0 && let Some((dll_path, ordinal)) = parse_dll_ordinal(name) {
+            // it is an ordinal-only symbol after all
+            marked_count = mark_dll_ordinal.execute((dll_path.as_str(), ordinal))
+                .map_err(|e| format!("failed to mark {:?} as deprecated: {}", name, e))?;
+        }
+        if marked_count == 0 {
+            unknown_count += 1;
+        }
+    }
+    Ok(unknown_count)
+}
+
+/// Sets the friendly names of the symbols listed in the given file, returning the number of listed
+/// symbols that are not in the database.
+///
+/// Each line contains the raw name of a named symbol or `<dll_path>#<ordinal>` for an ordinal-only
+/// symbol, a tab and the friendly name; a symbol is only taken as ordinal-only if no named symbol
+/// matches it.
+fn override_friendly_names(db: &Connection, overrides_path: &Path) -> Result<u64, String> {
+    let (list_reader, _file_length_opt) = open_list(overrides_path)?;
+    let mut override_named = db
+        .prepare("UPDATE symbols SET friendly_name = ?2 WHERE raw_name = ?1")
+        .map_err(|e| format!("failed to prepare override_named statement: {}", e))?;
+    let mut override_dll_ordinal = db
+        .prepare("UPDATE symbols SET friendly_name = ?3 WHERE dll_name = ?1 AND ordinal = ?2")
+        .map_err(|e| format!("failed to prepare override_dll_ordinal statement: {}", e))?;
+
+    let mut unknown_count: u64 = 0;
+    for (line_index, line_res) in list_reader.lines().enumerate() {
+        let line_number = line_index + 1;
+        let line = line_res
+            .map_err(|e| format!("failed to read line {}: {}", line_number, e))?;
+        let line = line.trim_end_matches('\r');
+        if line.trim_ascii().is_empty() {
+            continue;
+        }
+
+        let Some((name, friendly_name)) = line.split_once('\t')
+            else { return Err(format!("line {}: expected 2 tab-separated fields", line_number)) };
+        let name = name.trim_ascii();
+        let friendly_name = friendly_name.trim_ascii();
+        if name.is_empty() || friendly_name.is_empty() || friendly_name.contains('\t') {
+            return Err(format!("line {}: expected 2 non-empty tab-separated fields", line_number));
+        }
+
+        let mut updated_count = override_named.execute((name, friendly_name))
+            .map_err(|e| format!("failed to override friendly name of {:?}: {}", name, e))?;
+        if updated_count == 0 && let Some((dll_path, ordinal)) = parse_dll_ordinal(name) {
+            // it is an ordinal-only symbol after all
+            updated_count = override_dll_ordinal.execute((dll_path.as_str(), ordinal, friendly_name))
+                .map_err(|e| format!("failed to override friendly name of {:?}: {}", name, e))?;
+        }
+        if updated_count == 0 {
+            unknown_count += 1;
+        }
+    }
+    Ok(unknown_count)
+}
+
+/// Reads the list of long names of operating systems passed to `load --os-names`, returning a map
+/// from short name to long name.
+///
+/// A short name listed more than once with different long names is an error; a long name shared by
+/// multiple operating systems is reported but accepted.
+fn read_os_long_names(os_names_path: &Path) -> Result<BTreeMap<String, String>, String> {
+    let (list_reader, _file_length_opt) = open_list(os_names_path)?;
+
+    let mut os_long_names: BTreeMap<String, String> = BTreeMap::new();
+    for (line_index, line_res) in list_reader.lines().enumerate() {
+        let line_number = line_index + 1;
+        let line = line_res
+            .map_err(|e| format!("failed to read line {}: {}", line_number, e))?;
+        let line = line.trim_end_matches('\r');
+        if line.trim_ascii().is_empty() {
+            continue;
+        }
+
+        let Some((short_name, long_name)) = line.split_once('\t')
+            else { return Err(format!("line {}: expected 2 tab-separated fields", line_number)) };
+        let short_name = short_name.trim_ascii();
+        let long_name = long_name.trim_ascii();
+        if short_name.is_empty() || long_name.is_empty() || long_name.contains('\t') {
+            return Err(format!("line {}: expected 2 non-empty tab-separated fields", line_number));
+        }
+
+        if let Some(previous_long_name) = os_long_names.get(short_name) {
+            if previous_long_name != long_name {
+                return Err(format!(
+                    "line {}: conflicting long names {:?} and {:?} for operating system {:?}",
+                    line_number, previous_long_name, long_name, short_name,
+                ));
+            }
+            continue;
+        }
+        os_long_names.insert(short_name.to_owned(), long_name.to_owned());
+    }
+
+    let mut long_name_to_short_names: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+    for (short_name, long_name) in &os_long_names {
+        long_name_to_short_names
+            .entry(long_name.as_str())
+            .or_default()
+            .push(short_name.as_str());
+    }
+    for (long_name, short_names) in &long_name_to_short_names {
+        if short_names.len() > 1 {
+            eprintln!(
+                "warning: long name {:?} is shared by operating systems {}",
+                long_name, short_names.join(", "),
+            );
+        }
+    }
+
+    Ok(os_long_names)
+}
+
+/// Gives the operating systems already in the database without a long name their long name from
+/// the given map, and reports those whose long name differs from the one in the map.
+fn apply_existing_os_long_names(db: &Connection, os_long_names: &BTreeMap<String, String>) {
+    let mut query_os_long_name = db
+        .prepare("SELECT long_name FROM operating_systems WHERE short_name = ?1")
+        .expect("failed to prepare query_os_long_name statement");
+    let mut set_os_long_name = db
+        .prepare("UPDATE operating_systems SET long_name = ?2 WHERE short_name = ?1")
+        .expect("failed to prepare set_os_long_name statement");
+
+    for (short_name, long_name) in os_long_names {
+        let existing_opt: Option<Option<String>> = query_os_long_name
+            .query_one([short_name], |row| row.get(0))
+            .optional()
+            .expect("failed to query long name of operating system");
+        match existing_opt {
+            None => {
+                // not in the database (yet); it is given its long name when it is inserted
+            },
+            Some(None) => {
+                set_os_long_name.execute((short_name, long_name))
+                    .expect("failed to set long name of operating system");
+            },
+            Some(Some(existing)) if existing != *long_name => {
+                eprintln!(
+                    "warning: operating system {:?} already has the long name {:?}, not {:?}; keeping it",
+                    short_name, existing, long_name,
+                );
+            },
+            Some(Some(_)) => {
+                // already has the same long name
+            },
+        }
+    }
+}
+
+/// Parses a reference to an ordinal-only symbol of the form `<dll_path>#<ordinal>`, normalizing the
+/// DLL path the same way as `parse_list_line`.
+fn parse_dll_ordinal(name: &str) -> Option<(String, u64)> {
+    let (dll_path, ordinal_str) = name.rsplit_once('#')?;
+    let ordinal: u64 = ordinal_str.parse().ok()?;
+    let dll_path_lower = dll_path
+        .to_lowercase()
+        .replace("/", "\\");
+    Some((dll_path_lower, ordinal))
+}
+
+/// Removes all symbols from the given operating systems.
+fn remove_os_symbols(db: &Connection, os_names: &BTreeSet<String>) -> Result<(), String> {
+    if os_names.is_empty() {
+        return Ok(());
+    }
+
+    let mut delete_os_symbols = db
+        .prepare_cached("DELETE FROM symbol_dll_os WHERE os_id = (SELECT os_id FROM operating_systems WHERE short_name = ?1)")
+        .map_err(|e| format!("failed to prepare delete_os_symbols statement: {}", e))?;
+    for os_name in os_names {
+        let removed_count = delete_os_symbols
+            .execute([os_name])
+            .map_err(|e| format!("failed to remove symbols of operating system {:?}: {}", os_name, e))?;
+        if removed_count > 0 {
+            eprintln!("removed {} symbol(s) from {}", removed_count, os_name);
+        }
+    }
+    Ok(())
+}
+
+/// Recomputes the data derived from the symbols and operating systems: the index and the symbol
+/// counts on the root page, the chronological order of the operating systems and the earliest
+/// operating system containing each symbol. Also records when the data last changed.
+fn reindex(db: &Connection) {
+    db.execute_batch(include_str!("../../db/reindex.sql"))
+        .expect("failed to recompute derived data");
+}
+
+/// Demangles the names of the symbols in the given list contents which are not yet in the database,
+/// using the given number of threads.
+#[cfg(feature = "ms_cpp_filt")]
+fn demangle_new_symbols(contents: &ListContents, statements: &mut LoadStatements, jobs: NonZeroUsize) -> Result<BTreeMap<String, Option<String>>, String> {
+    // collect the names of the new symbols
+    let mut new_names: BTreeSet<&str> = BTreeSet::new();
+    for numbered_line in &contents.lines {
+        let Some(symbol_name) = numbered_line.line.symbol_name.as_deref()
+            else { continue };
+        if new_names.contains(symbol_name) {
+            continue;
+        }
+        let known = run_get_id_query(&mut statements.query_named_symbol, [symbol_name])?
+            .is_some();
+        if !known {
+            new_names.insert(symbol_name);
+        }
+    }
+    if new_names.is_empty() {
+        return Ok(BTreeMap::new());
+    }
+
+    // split them up evenly between the threads
+    let names: Vec<&str> = new_names.into_iter().collect();
+    let chunk_size = names.len().div_ceil(jobs.get());
+    eprintln!("demangling {} new symbol name(s) using {} thread(s)", names.len(), jobs);
+    let demangled_names = std::thread::scope(|scope| {
+        let workers: Vec<_> = names
+            .chunks(chunk_size)
+            .map(|chunk| scope.spawn(move || {
+                chunk.iter()
+                    .map(|name| (name.to_string(), try_demangle(name)))
+                    .collect::<Vec<_>>()
+            }))
+            .collect();
+        workers
+            .into_iter()
+            .flat_map(|worker| worker.join().expect("demangling thread panicked"))
+            .collect()
+    });
+    Ok(demangled_names)
+}
+
+/// Returns no demangled names, since demangling support has not been compiled in.
+#[cfg(not(feature = "ms_cpp_filt"))]
+fn demangle_new_symbols(_contents: &ListContents, _statements: &mut LoadStatements, _jobs: NonZeroUsize) -> Result<BTreeMap<String, Option<String>>, String> {
+    Ok(BTreeMap::new())
+}
+
+#[cfg(feature = "ms_cpp_filt")]
+fn try_demangle(symbol: &str) -> Option<String> {
+    crate::ms_cpp_filt::demangle_cpp_name(symbol, msvc_demangler::DemangleFlags::llvm()).ok()
+}
+
+#[cfg(not(feature = "ms_cpp_filt"))]
+fn try_demangle(_symbol: &str) -> Option<String> {
+    None
+}
+
+#[cfg(feature = "ms_cpp_filt")]
+fn do_demangle_db(opts: DemangleDbOpts) {
+    let mut db = open_database(&opts.database_path);
+
+    // start a transaction
+    let txn = db.transaction()
+        .expect("failed to start transaction");
+
+    let mut demangled_count: usize = 0;
+    let mut failed_count: usize = 0;
+    {
+        // prepare a few statements we will be using
+        let mut query_named_symbols_without_friendly_name = txn
+            .prepare("SELECT sym_id, raw_name FROM symbols WHERE raw_name IS NOT NULL AND friendly_name IS NULL")
+            .expect("failed to prepare query_named_symbols_without_friendly_name statement");
+        let mut set_symbol_friendly_name = txn
+            .prepare("UPDATE symbols SET friendly_name = ?1 WHERE sym_id = ?2")
+            .expect("failed to prepare query set_symbol_friendly_name");
+
+        // collect the symbols first, so that we aren't updating the table we are iterating over
+        // (this also tells us how many there are, for progress output)
+        let symbols: Vec<(i64, String)> = query_named_symbols_without_friendly_name
+            .query_map((), |row| Ok((row.get(0)?, row.get(1)?)))
+            .expect("failed to query named symbols")
+            .collect::<Result<_, _>>()
+            .expect("failed to obtain row");
+
+        let mut last_percentage = 0;
+        for (i, (symbol_id, raw_name)) in symbols.iter().enumerate() {
+            // output progress
+            let now_percentage = ((i + 1) * 100) / symbols.len();
+            if last_percentage < now_percentage {
+                last_percentage = now_percentage;
+                eprintln!("{}%", now_percentage);
+            }
+
+            let Ok(demangled) = crate::ms_cpp_filt::demangle_cpp_name(raw_name, msvc_demangler::DemangleFlags::llvm()) else {
+                failed_count += 1;
+                continue;
+            };
+
+            set_symbol_friendly_name
+                .execute((demangled.as_str(), symbol_id))
+                .expect("failed to set friendly name");
+            demangled_count += 1;
+        }
+    }
+
+    // the index lists symbols by their friendly names; this also tells the web server that the
+    // data has changed
+    reindex(&txn);
+
+    // and we're done
+    txn.commit()
+        .expect("committing transaction failed");
+
+    eprintln!(
+        "demangled {} symbol(s); {} symbol(s) are not mangled C++ names or could not be demangled",
+        demangled_count, failed_count,
+    );
+}
+
+fn do_set_os_parent(opts: SetOsParentOpts) {
+    let mut db = open_database(&opts.database_path);
+
+    // start a transaction
+    let txn = db.transaction()
+        .expect("failed to start transaction");
+
+    {
+        let mut query_os = txn
+            .prepare("SELECT os_id, parent_os_id FROM operating_systems WHERE short_name = ?1")
+            .expect("failed to prepare query_os statement");
+        let mut find_os = |short_name: &str| -> (i64, Option<i64>) {
+            let os_opt = query_os
+                .query_one(
+                    [short_name],
+                    |row| Ok((row.get(0)?, row.get(1)?)),
+                )
+                .optional()
+                .expect("failed to run query_os");
+            match os_opt {
+                Some(os) => os,
+                None => {
+                    eprintln!("operating system {:?} not found", short_name);
+                    std::process::exit(1);
+                },
+            }
+        };
+
+        let (os_id, _) = find_os(&opts.os_short_name);
+        let parent_os_id_opt = if let Some(parent_short_name) = opts.parent_short_name.as_deref() {
+            let (parent_os_id, grandparent_os_id_opt) = find_os(parent_short_name);
+            if parent_os_id == os_id {
+                eprintln!("an operating system cannot be an edition of itself");
+                std::process::exit(1);
+            }
+            if grandparent_os_id_opt.is_some() {
+                eprintln!("{:?} is itself an edition; editions of editions are not supported", parent_short_name);
+                std::process::exit(1);
+            }
+
+            let has_editions: bool = txn
+                .query_one(
+                    "SELECT EXISTS (SELECT 1 FROM operating_systems WHERE parent_os_id = ?1)",
+                    [os_id],
+                    |row| row.get(0),
+                )
+                .expect("failed to query for editions");
+            if has_editions {
+                eprintln!("{:?} has editions itself; editions of editions are not supported", opts.os_short_name);
+                std::process::exit(1);
+            }
+
+            Some(parent_os_id)
+        } else {
+            None
+        };
+
+        txn
+            .execute(
+                "UPDATE operating_systems SET parent_os_id = ?1 WHERE os_id = ?2",
+                (parent_os_id_opt, os_id),
+            )
+            .expect("failed to set parent operating system");
+    }
+
+    // nothing derived depends on the parent, but this tells the web server that the data has changed
+    reindex(&txn);
+
+    // and we're done
+    txn.commit()
+        .expect("committing transaction failed");
+}
+
+/// Parses a release date in the ISO 8601 format `YYYY-MM-DD`, ensuring that the date exists.
+fn parse_release_date(date: &str) -> Result<String, String> {
+    let pieces: Vec<&str> = date.split('-').collect();
+    let digit_counts_match = pieces.len() == 3
+        && pieces.iter().zip([4, 2, 2]).all(|(piece, digit_count)|
+            piece.len() == digit_count && piece.bytes().all(|b| b.is_ascii_digit())
+        );
+    if !digit_counts_match {
+        return Err(format!("{:?} is not in the format YYYY-MM-DD", date));
+    }
+
+    let year: u32 = pieces[0].parse().unwrap();
+    let month: u32 = pieces[1].parse().unwrap();
+    let day: u32 = pieces[2].parse().unwrap();
+    let is_leap_year = (year.is_multiple_of(4) && !year.is_multiple_of(100)) || year.is_multiple_of(400);
+    let days_in_month = match month {
+        1|3|5|7|8|10|12 => 31,
+        4|6|9|11 => 30,
+        2 => if is_leap_year { 29 } else { 28 },
+        _ => return Err(format!("{:?} has an invalid month", date)),
+    };
+    if day < 1 || day > days_in_month {
+        return Err(format!("{:?} has an invalid day", date));
+    }
+
+    Ok(date.to_owned())
+}
+
+/// Parses a build number consisting of dot-separated decimal numbers (e.g. `10.0.19045`).
+fn parse_build_number(build_number: &str) -> Result<String, String> {
+    let is_well_formed = build_number
+        .split('.')
+        .all(|piece| !piece.is_empty() && piece.bytes().all(|b| b.is_ascii_digit()));
+    if !is_well_formed {
+        return Err(format!("{:?} is not a sequence of dot-separated numbers", build_number));
+    }
+    Ok(build_number.to_owned())
+}
+
+fn do_set_os_metadata(opts: SetOsMetadataOpts) {
+    let nothing_to_set = opts.long_name.is_none()
+        && opts.release_date.is_none()
+        && opts.build_number.is_none()
+        && opts.has_icon.is_none();
+    if nothing_to_set {
+        eprintln!("nothing to set; pass at least one of --long-name, --release-date, --build-number and --has-icon");
+        std::process::exit(1);
+    }
+
+    let mut db = open_database(&opts.database_path);
+
+    // start a transaction
+    let txn = db.transaction()
+        .expect("failed to start transaction");
+
+    // values that have not been given remain unchanged
+    let updated_count = txn
+        .execute(
+            "
+                UPDATE operating_systems
+                SET
+                    long_name = COALESCE(?1, long_name),
+                    release_date = COALESCE(?2, release_date),
+                    build_number = COALESCE(?3, build_number),
+                    has_icon = COALESCE(?4, has_icon)
+                WHERE
+                    short_name = ?5
+            ",
+            (
+                opts.long_name.as_deref(),
+                opts.release_date.as_deref(),
+                opts.build_number.as_deref(),
+                opts.has_icon,
+                opts.os_short_name.as_str(),
+            ),
+        )
+        .expect("failed to set operating system metadata");
+    if updated_count == 0 {
+        eprintln!("operating system {:?} not found", opts.os_short_name);
+        std::process::exit(1);
+    }
+
+    // the release date and build number decide which operating system a symbol first appeared in
+    reindex(&txn);
+
+    // and we're done
+    txn.commit()
+        .expect("committing transaction failed");
+}
+
+fn do_reindex(opts: ReindexOpts) {
+    let mut db = open_database(&opts.database_path);
+
+    let txn = db.transaction()
+        .expect("failed to start transaction");
+    reindex(&txn);
+    txn.commit()
+        .expect("committing transaction failed");
+}
+
+fn do_prune(opts: PruneOpts) {
+    let mut db = open_database(&opts.database_path);
+
+    // start a transaction
+    let txn = db.transaction()
+        .expect("failed to start transaction");
+
+    let os_id_opt: Option<i64> = txn
+        .query_one(
+            "SELECT os_id FROM operating_systems WHERE short_name = ?1",
+            [&opts.os_short_name],
+            |row| row.get(0),
+        )
+        .optional()
+        .expect("failed to query operating system");
+    let Some(os_id) = os_id_opt else {
+        eprintln!("operating system {:?} not found", opts.os_short_name);
+        std::process::exit(1);
+    };
+
+    let has_editions: bool = txn
+        .query_one(
+            "SELECT EXISTS (SELECT 1 FROM operating_systems WHERE parent_os_id = ?1)",
+            [os_id],
+            |row| row.get(0),
+        )
+        .expect("failed to query for editions");
+    if has_editions {
+        eprintln!("{:?} has editions; use set-os-parent to detach them first", opts.os_short_name);
+        std::process::exit(1);
+    }
+
+    let relationship_count = txn
+        .execute("DELETE FROM symbol_dll_os WHERE os_id = ?1", [os_id])
+        .expect("failed to remove symbols of operating system");
+    // (recomputed by reindex below)
+    txn
+        .execute("UPDATE symbols SET first_os_id = NULL WHERE first_os_id = ?1", [os_id])
+        .expect("failed to detach symbols from operating system");
+    let os_count = txn
+        .execute("DELETE FROM operating_systems WHERE os_id = ?1", [os_id])
+        .expect("failed to remove operating system");
+
+    // remove whatever is no longer contained in any operating system
+    let symbol_count = txn
+        .execute(
+            "DELETE FROM symbols WHERE NOT EXISTS (SELECT 1 FROM symbol_dll_os sdo WHERE sdo.sym_id = symbols.sym_id)",
+            [],
+        )
+        .expect("failed to remove orphaned symbols");
+    let dll_count = txn
+        .execute(
+            "DELETE FROM dlls WHERE NOT EXISTS (SELECT 1 FROM symbol_dll_os sdo WHERE sdo.dll_id = dlls.dll_id)",
+            [],
+        )
+        .expect("failed to remove orphaned DLLs");
+
+    // the data has changed; update the index
+    reindex(&txn);
+
+    // and we're done
+    txn.commit()
+        .expect("committing transaction failed");
+
+    eprintln!("removed {} operating system(s)", os_count);
+    eprintln!("removed {} symbol-DLL-operating system relationship(s)", relationship_count);
+    eprintln!("removed {} orphaned symbol(s)", symbol_count);
+    eprintln!("removed {} orphaned DLL(s)", dll_count);
+}
+
+fn do_maintain(opts: MaintainOpts) {
+    let mut db = open_database(&opts.database_path);
+
+    let size_before = std::fs::metadata(&opts.database_path)
+        .expect("failed to obtain database file size")
+        .len();
+
+    eprintln!("recomputing precomputed data");
+    let txn = db.transaction()
+        .expect("failed to start transaction");
+    reindex(&txn);
+    txn.commit()
+        .expect("committing transaction failed");
+
+    // VACUUM cannot run within a transaction, so neither of these does
+    eprintln!("analyzing");
+    db.execute_batch("ANALYZE; PRAGMA optimize;")
+        .expect("failed to analyze database");
+    eprintln!("vacuuming");
+    db.execute_batch("VACUUM;")
+        .expect("failed to vacuum database");
+    drop(db);
+
+    let size_after = std::fs::metadata(&opts.database_path)
+        .expect("failed to obtain database file size")
+        .len();
+    eprintln!("database size: {} bytes before, {} bytes after", size_before, size_after);
+}
+
+fn do_export(opts: ExportOpts) {
+    if !export(&opts) {
+        std::process::exit(1);
+    }
+}
+
+/// Exports the relationships as specified by the options, returning whether this was successful.
+fn export(opts: &ExportOpts) -> bool {
+    let Some(db) = open_database_read_only(&opts.database_path)
+        else { return false };
+
+    if let Some(os_short_name) = opts.os_short_name.as_deref() {
+        let os_exists: bool = db
+            .query_one(
+                "SELECT EXISTS (SELECT 1 FROM operating_systems WHERE short_name = ?1)",
+                [os_short_name],
+                |row| row.get(0),
+            )
+            .expect("failed to query operating system");
+        if !os_exists {
+            eprintln!("operating system {:?} not found", os_short_name);
+            return false;
+        }
+    }
+
+    let out_file = File::create(&opts.out_path)
+        .expect("failed to create output file");
+    let mut out_writer = BufWriter::new(out_file);
+
+    let mut query_relationships = db
+        .prepare("
+            SELECT
+                os.short_name,
+                d.path,
+                d.secondary_platform,
+                sdo.ordinal,
+                s.raw_name,
+                sdo.architecture
+            FROM
+                symbol_dll_os sdo
+                INNER JOIN operating_systems os
+                    ON os.os_id = sdo.os_id
+                INNER JOIN dlls d
+                    ON d.dll_id = sdo.dll_id
+                INNER JOIN symbols s
+                    ON s.sym_id = sdo.sym_id
+            WHERE
+                ?1 IS NULL OR os.short_name = ?1
+            ORDER BY
+                os.short_name,
+                d.path,
+                sdo.ordinal,
+                s.raw_name
+        ")
+        .expect("failed to prepare query_relationships statement");
+    let mut rows = query_relationships
+        .query([opts.os_short_name.as_deref()])
+        .expect("failed to query relationships");
+
+    // write the lines in the format parsed by parse_list_line
+    let mut line_count: u64 = 0;
+    while let Some(row) = rows.next().expect("failed to obtain row") {
+        let os_short_name: String = row.get(0).expect("failed to obtain operating system");
+        let dll_path: String = row.get(1).expect("failed to obtain DLL path");
+        let secondary_platform: bool = row.get(2).expect("failed to obtain secondary platform flag");
+        let ordinal: Option<u64> = row.get(3).expect("failed to obtain ordinal");
+        let raw_name: Option<String> = row.get(4).expect("failed to obtain raw name");
+        let architecture: Option<String> = row.get(5).expect("failed to obtain architecture");
+
+        let path_json = serde_json::to_string(&[format!("{}\\{}", os_short_name, dll_path)])
+            .expect("failed to encode path as JSON");
+        let ordinal_string = ordinal
+            .map(|o| o.to_string())
+            .unwrap_or_default();
+        write!(out_writer, "{}\t{}\t{}", path_json, ordinal_string, raw_name.as_deref().unwrap_or(""))
+            .expect("failed to write line");
+        if secondary_platform || architecture.is_some() {
+            let platform = if secondary_platform { "secondary" } else { "primary" };
+            write!(out_writer, "\t{}", platform)
+                .expect("failed to write line");
+        }
+        if let Some(arch) = architecture.as_deref() {
+            write!(out_writer, "\t{}", arch)
+                .expect("failed to write line");
+        }
+        writeln!(out_writer)
+            .expect("failed to write line");
+        line_count += 1;
+    }
+    out_writer.flush()
+        .expect("failed to write output file");
+
+    eprintln!("exported {} line(s)", line_count);
+    true
+}
+
+fn do_verify(opts: VerifyOpts) {
+    if !verify(&opts) {
+        std::process::exit(1);
+    }
+}
+
+/// Checks the database for inconsistencies without changing it, outputting each of them and
+/// returning whether none were found.
+fn verify(opts: &VerifyOpts) -> bool {
+    // each query returns the rowid of each offending row and a description of the offense
+    const CHECKS: [(&str, &str); 7] = [
+        (
+            "symbols",
+            "
+                SELECT sym_id, 'neither a name nor a DLL and ordinal'
+                FROM symbols
+                WHERE raw_name IS NULL AND (dll_name IS NULL OR ordinal IS NULL)
+                UNION ALL
+                SELECT sym_id, 'both a name and a DLL or ordinal'
+                FROM symbols
+                WHERE raw_name IS NOT NULL AND (dll_name IS NOT NULL OR ordinal IS NOT NULL)
+            ",
+        ),
+        (
+            "symbol_dll_os",
+            "
+                SELECT sdo.rowid, 'nonexistent symbol ' || sdo.sym_id
+                FROM symbol_dll_os sdo
+                WHERE NOT EXISTS (SELECT 1 FROM symbols s WHERE s.sym_id = sdo.sym_id)
+            ",
+        ),
+        (
+            "symbol_dll_os",
+            "
+                SELECT sdo.rowid, 'nonexistent DLL ' || sdo.dll_id
+                FROM symbol_dll_os sdo
+                WHERE NOT EXISTS (SELECT 1 FROM dlls d WHERE d.dll_id = sdo.dll_id)
+            ",
+        ),
+        (
+            "symbol_dll_os",
+            "
+                SELECT sdo.rowid, 'nonexistent operating system ' || sdo.os_id
+                FROM symbol_dll_os sdo
+                WHERE NOT EXISTS (SELECT 1 FROM operating_systems os WHERE os.os_id = sdo.os_id)
+            ",
+        ),
+        (
+            "operating_systems",
+            "
+                SELECT os_id, 'malformed release date ' || quote(release_date)
+                FROM operating_systems
+                WHERE release_date IS NOT NULL AND date(release_date) IS NOT release_date
+            ",
+        ),
+        (
+            // keep in sync with ARCHITECTURES
+            "symbol_dll_os",
+            "
+                SELECT sdo.rowid, 'unknown architecture ' || quote(sdo.architecture)
+                FROM symbol_dll_os sdo
+                WHERE sdo.architecture NOT IN ('x86', 'x64', 'arm', 'arm64', 'ia64', 'alpha', 'mips', 'ppc')
+            ",
+        ),
+        (
+            // keep in sync with db/reindex.sql
+            "symbols",
+            "
+                SELECT
+                    s.sym_id,
+                    'earliest operating system ' || quote(s.first_os_id) || ' instead of '
+                        || quote(s.expected_first_os_id) || ' (run reindex)'
+                FROM (
+                    SELECT
+                        sym.sym_id,
+                        sym.first_os_id,
+                        (
+                            SELECT os.os_id
+                            FROM
+                                symbol_dll_os sdo
+                                INNER JOIN operating_systems os
+                                    ON os.os_id = sdo.os_id
+                            WHERE
+                                sdo.sym_id = sym.sym_id
+                                AND os.release_date IS NOT NULL
+                            ORDER BY
+                                os.sort_order ASC
+                            LIMIT 1
+                        ) expected_first_os_id
+                    FROM symbols sym
+                ) s
+                WHERE s.first_os_id IS NOT s.expected_first_os_id
+            ",
+        ),
+    ];
+
+    let Some(db) = open_database_read_only(&opts.database_path)
+        else { return false };
+
+    let mut violation_count: u64 = 0;
+    for (table, query) in CHECKS {
+        let mut statement = db.prepare(query)
+            .expect("failed to prepare verification query");
+        let violations: Vec<(i64, String)> = statement
+            .query_map((), |row| Ok((row.get(0)?, row.get(1)?)))
+            .expect("failed to run verification query")
+            .collect::<Result<_, _>>()
+            .expect("failed to obtain row");
+        for (rowid, description) in violations {
+            println!("{} row {}: {}", table, rowid, description);
+            violation_count += 1;
+        }
+    }
+
+    if violation_count > 0 {
+        eprintln!("found {} violation(s)", violation_count);
+        return false;
+    }
+    eprintln!("no inconsistencies found");
+    true
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use tempfile::TempDir;
+
+
+    /// Writes a list with the given lines and file name into the directory, returning its path.
+    fn write_list(dir: &TempDir, file_name: &str, lines: &[&str]) -> PathBuf {
+        let list_path = dir.path().join(file_name);
+        std::fs::write(&list_path, lines.join("\n"))
+            .expect("failed to write list");
+        list_path
+    }
+
+    /// Loads the given lists into the database in the directory using the given additional
+    /// options, returning the path to the database.
+    fn load_list_files(dir: &TempDir, list_paths: &[PathBuf], extra_args: &[&str]) -> PathBuf {
+        let database_path = dir.path().join("winapi.sqlite3");
+        let mut args = vec!["load", database_path.to_str().unwrap()];
+        args.extend(list_paths.iter().map(|p| p.to_str().unwrap()));
+        args.extend_from_slice(extra_args);
+        assert!(load_lists(&LoadOpts::parse_from(args)), "loading failed");
+        database_path
+    }
+
+    /// Writes a list with the given lines into the directory and loads it into the database in the
+    /// same directory using the given additional options, returning the path to the database.
+    fn load_list(dir: &TempDir, lines: &[&str], extra_args: &[&str]) -> PathBuf {
+        let list_path = write_list(dir, "list.tsv", lines);
+        load_list_files(dir, &[list_path], extra_args)
+    }
+
+    /// Runs a query returning a single text column and collects its values.
+    fn query_strings(db: &Connection, query: &str) -> Vec<String> {
+        db.prepare(query)
+            .expect("failed to prepare query")
+            .query_map([], |row| row.get(0))
+            .expect("failed to run query")
+            .collect::<Result<_, _>>()
+            .expect("failed to obtain row")
+    }
+
+
+    #[test]
+    fn ordinal_symbols_of_same_named_dlls_stay_distinct() {
+        let dir = TempDir::new().unwrap();
+        let database_path = load_list(
+            &dir,
+            &[
+                r#"["win10\\system32\\api-ms-win-core-file-l1-1-0.dll"]	1	"#,
+                r#"["win10\\system32\\downlevel\\api-ms-win-core-file-l1-1-0.dll"]	1	"#,
+            ],
+            &[],
+        );
+
+        let db = Connection::open(&database_path).unwrap();
+        assert_eq!(
+            query_strings(&db, "SELECT dll_name FROM symbols WHERE ordinal = 1 ORDER BY dll_name"),
+            [
+                r"system32\api-ms-win-core-file-l1-1-0.dll",
+                r"system32\downlevel\api-ms-win-core-file-l1-1-0.dll",
+            ],
+        );
+
+        // the index on the root page uses the file names, not the directories
+        assert_eq!(
+            query_strings(&db, "SELECT start_chars FROM index_start_chars WHERE category = 'ordinal_dll'"),
+            ["a"],
+        );
+    }
+
+    #[test]
+    fn symbols_are_counted_per_operating_system() {
+        let dir = TempDir::new().unwrap();
+        let database_path = load_list(
+            &dir,
+            &[
+                r#"["win95\\system\\kernel32.dll"]	1	CreateFileA"#,
+                r#"["win98\\system\\kernel32.dll"]	1	CreateFileA"#,
+                r#"["win98\\system\\kernel32.dll"]	2	CreateFileW"#,
+                // the same symbol in another DLL is only counted once
+                r#"["win98\\system\\kernelbase.dll"]	1	CreateFileW"#,
+            ],
+            &[],
+        );
+
+        let db = Connection::open(&database_path).unwrap();
+        assert_eq!(
+            query_strings(&db, "SELECT short_name || ' ' || symbol_count FROM operating_systems ORDER BY short_name"),
+            ["win95 1", "win98 2"],
+        );
+    }
+
+    #[test]
+    fn replacing_keeps_symbols_of_earlier_list_with_same_os() {
+        let dir = TempDir::new().unwrap();
+        load_list(&dir, &[r#"["win95\\system\\kernel32.dll"]	1	OldFunction"#], &[]);
+
+        let first = write_list(&dir, "first.tsv", &[r#"["win95\\system\\kernel32.dll"]	1	CreateFileA"#]);
+        let second = write_list(&dir, "second.tsv", &[r#"["win95\\system\\user32.dll"]	1	MessageBoxA"#]);
+        let database_path = load_list_files(&dir, &[first, second], &["--replace-os"]);
+
+        let db = Connection::open(&database_path).unwrap();
+        assert_eq!(
+            query_strings(&db, "SELECT s.raw_name FROM symbol_dll_os sdo INNER JOIN symbols s ON s.sym_id = sdo.sym_id ORDER BY 1"),
+            ["CreateFileA", "MessageBoxA"],
+        );
+    }
+
+    #[test]
+    fn failed_list_does_not_remove_symbols_of_its_os() {
+        let dir = TempDir::new().unwrap();
+        load_list(&dir, &[r#"["win95\\system\\kernel32.dll"]	1	OldFunction"#], &[]);
+
+        let broken = write_list(&dir, "broken.tsv", &[
+            r#"["win95\\system\\kernel32.dll"]	1	CreateFileA"#,
+            "malformed",
+        ]);
+        let other = write_list(&dir, "other.tsv", &[r#"["win98\\system\\kernel32.dll"]	1	CreateFileA"#]);
+        let database_path = dir.path().join("winapi.sqlite3");
+        let opts = LoadOpts::parse_from([
+            "load",
+            database_path.to_str().unwrap(),
+            broken.to_str().unwrap(),
+            other.to_str().unwrap(),
+            "--replace-os",
+            "--strict",
+            "--continue-on-error",
+        ]);
+        assert!(!load_lists(&opts));
+
+        let db = Connection::open(&database_path).unwrap();
+        assert_eq!(
+            query_strings(&db, "
+                SELECT os.short_name || ' ' || s.raw_name
+                FROM symbol_dll_os sdo
+                INNER JOIN symbols s ON s.sym_id = sdo.sym_id
+                INNER JOIN operating_systems os ON os.os_id = sdo.os_id
+                ORDER BY 1
+            "),
+            ["win95 OldFunction", "win98 CreateFileA"],
+        );
+    }
+
+    /// Describes every symbol-DLL-operating system relationship in the database.
+    fn describe_relationships(db: &Connection) -> Vec<String> {
+        query_strings(db, "
+            SELECT
+                os.short_name
+                || ' ' || d.path
+                || ' ' || d.secondary_platform
+                || ' ' || COALESCE(s.raw_name, s.dll_name || '#' || s.ordinal)
+                || ' ' || COALESCE(sdo.ordinal, '-')
+                || ' ' || COALESCE(sdo.architecture, '-')
+            FROM
+                symbol_dll_os sdo
+                INNER JOIN operating_systems os ON os.os_id = sdo.os_id
+                INNER JOIN dlls d ON d.dll_id = sdo.dll_id
+                INNER JOIN symbols s ON s.sym_id = sdo.sym_id
+            ORDER BY 1
+        ")
+    }
+
+    #[test]
+    fn exported_list_loads_into_same_relationships() {
+        let dir = TempDir::new().unwrap();
+        let original_path = load_list(
+            &dir,
+            &[
+                r#"["win95\\system\\kernel32.dll"]	1	CreateFileA"#,
+                r#"["win95\\system\\kernel32.dll"]	2	"#,
+                r#"["win11-arm64\\system32\\kernel32.dll"]		CreateFileW"#,
+                r#"["win11-arm64\\syswow64\\kernel32.dll"]		CreateFileW	secondary	x86"#,
+            ],
+            &[],
+        );
+        let export_path = dir.path().join("export.tsv");
+        assert!(export(&ExportOpts::parse_from([
+            "export",
+            original_path.to_str().unwrap(),
+            export_path.to_str().unwrap(),
+        ])));
+
+        let copy_dir = TempDir::new().unwrap();
+        let copy_path = load_list_files(&copy_dir, &[export_path], &[]);
+
+        let original = describe_relationships(&Connection::open(&original_path).unwrap());
+        let copy = describe_relationships(&Connection::open(&copy_path).unwrap());
+        assert_eq!(original.len(), 4);
+        assert_eq!(original, copy);
+    }
+
+    #[test]
+    fn export_from_missing_database_fails_without_creating_it() {
+        let dir = TempDir::new().unwrap();
+        let database_path = dir.path().join("winapi.sqlite3");
+        let export_path = dir.path().join("export.tsv");
+        assert!(!export(&ExportOpts::parse_from([
+            "export",
+            database_path.to_str().unwrap(),
+            export_path.to_str().unwrap(),
+        ])));
+        assert!(!database_path.exists());
+        assert!(!export_path.exists());
+    }
+
+    #[test]
+    fn export_from_outdated_database_fails_without_migrating_it() {
+        let dir = TempDir::new().unwrap();
+        let database_path = load_list(&dir, &[r#"["win95\\system\\kernel32.dll"]	1	CreateFileA"#], &[]);
+        Connection::open(&database_path).unwrap()
+            .execute_batch("UPDATE schema_version SET ver = 15")
+            .unwrap();
+
+        let export_path = dir.path().join("export.tsv");
+        assert!(!export(&ExportOpts::parse_from([
+            "export",
+            database_path.to_str().unwrap(),
+            export_path.to_str().unwrap(),
+        ])));
+        let db = Connection::open(&database_path).unwrap();
+        assert_eq!(query_strings(&db, "SELECT CAST(ver AS TEXT) FROM schema_version"), ["15"]);
+    }
+
+    #[test]
+    fn export_is_restricted_to_operating_system() {
+        let dir = TempDir::new().unwrap();
+        let database_path = load_list(
+            &dir,
+            &[
+                r#"["win95\\system\\kernel32.dll"]	1	CreateFileA"#,
+                r#"["win98\\system\\kernel32.dll"]	1	CreateFileA"#,
+            ],
+            &[],
+        );
+        let export_path = dir.path().join("export.tsv");
+        assert!(export(&ExportOpts::parse_from([
+            "export",
+            database_path.to_str().unwrap(),
+            export_path.to_str().unwrap(),
+            "--os-short-name",
+            "win98",
+        ])));
+
+        assert_eq!(
+            std::fs::read_to_string(&export_path).unwrap(),
+            "[\"win98\\\\system\\\\kernel32.dll\"]\t1\tCreateFileA\n",
+        );
+    }
+
+    #[test]
+    fn verifying_missing_database_fails_without_creating_it() {
+        let dir = TempDir::new().unwrap();
+        let database_path = dir.path().join("winapi.sqlite3");
+        assert!(!verify(&VerifyOpts::parse_from(["verify", database_path.to_str().unwrap()])));
+        assert!(!database_path.exists());
+    }
+
+    #[test]
+    fn wrong_earliest_operating_system_is_reported_and_kept() {
+        let dir = TempDir::new().unwrap();
+        let database_path = load_list(&dir, &[r#"["win95\\system\\kernel32.dll"]	1	CreateFileA"#], &[]);
+        let db = Connection::open(&database_path).unwrap();
+        db.execute_batch("
+            UPDATE operating_systems SET release_date = '1995-08-24';
+            UPDATE symbols SET first_os_id = NULL;
+        ").unwrap();
+        assert!(!verify(&VerifyOpts::parse_from(["verify", database_path.to_str().unwrap()])));
+        assert_eq!(query_strings(&db, "SELECT CAST(COUNT(*) AS TEXT) FROM symbols WHERE first_os_id IS NULL"), ["1"]);
+    }
+
+    #[test]
+    fn single_element_path_is_split_into_os_and_dll() {
+        let line = parse_list_line("[\"windows10\\\\system32\\\\kernel32.dll\"]\t1\tCreateFileW").unwrap();
+        assert_eq!(line.operating_system, "windows10");
+        assert_eq!(line.dll_path, "system32\\kernel32.dll");
+    }
+
+    #[test]
+    fn two_element_path_is_split_into_os_and_dll() {
+        let line = parse_list_line("[\"windows10\",\"kernel32.dll\"]\t1\tCreateFileW").unwrap();
+        assert_eq!(line.operating_system, "windows10");
+        assert_eq!(line.dll_path, "kernel32.dll");
+    }
+
+    #[test]
+    fn three_element_path_is_split_into_os_and_dll() {
+        let line = parse_list_line("[\"windows10\",\"system32\",\"kernel32.dll\"]\t1\tCreateFileW").unwrap();
+        assert_eq!(line.operating_system, "windows10");
+        assert_eq!(line.dll_path, "system32\\kernel32.dll");
+    }
+
+    #[test]
+    fn path_without_dll_is_rejected() {
+        assert!(parse_list_line("[\"windows10\"]\t1\tCreateFileW").is_err());
+        assert!(parse_list_line("[]\t1\tCreateFileW").is_err());
+    }
+
+    #[test]
+    fn names_differing_by_surrounding_whitespace_are_one_symbol() {
+        let dir = TempDir::new().unwrap();
+        let database_path = load_list(
+            &dir,
+            &[
+                "[\"win95\\\\system\\\\kernel32.dll\"]\t1\tFoo ",
+                "[\"win98\\\\system\\\\kernel32.dll\"]\t1\tFoo",
+            ],
+            &[],
+        );
+
+        let db = Connection::open(&database_path).unwrap();
+        assert_eq!(query_strings(&db, "SELECT raw_name FROM symbols"), ["Foo"]);
+        assert_eq!(
+            query_strings(&db, "SELECT CAST(COUNT(DISTINCT sym_id) AS TEXT) FROM symbol_dll_os"),
+            ["1"],
+        );
+    }
+
+    #[test]
+    fn byte_order_mark_does_not_end_up_in_os_name() {
+        let dir = TempDir::new().unwrap();
+        let database_path = load_list(
+            &dir,
+            &[
+                "\u{FEFF}[\"win95\\\\system\\\\kernel32.dll\"]\t1\tCreateFileA",
+                "[\"win95\\\\system\\\\kernel32.dll\"]\t2\tCreateFileW",
+            ],
+            &[],
+        );
+
+        let db = Connection::open(&database_path).unwrap();
+        assert_eq!(query_strings(&db, "SELECT short_name FROM operating_systems"), ["win95"]);
+    }
+
+    #[test]
+    fn build_numbers_are_ordered_numerically() {
+        let dir = TempDir::new().unwrap();
+        let database_path = load_list(
+            &dir,
+            &[
+                "[\"win10-22h2\\\\system32\\\\kernel32.dll\"]\t1\tCreateFileW",
+                "[\"win8\\\\system32\\\\kernel32.dll\"]\t1\tCreateFileW",
+                "[\"winnext\\\\system32\\\\kernel32.dll\"]\t1\tCreateFileW",
+            ],
+            &[],
+        );
+        let database_arg = database_path.to_str().unwrap();
+        for (os, build_number) in [("win10-22h2", "10.0.19045"), ("win8", "10.0.9200")] {
+            do_set_os_metadata(SetOsMetadataOpts::parse_from([
+                "set-os-metadata", database_arg, os,
+                "--release-date", "2012-10-26",
+                "--build-number", build_number,
+            ]));
+        }
+
+        let db = Connection::open(&database_path).unwrap();
+        assert_eq!(
+            query_strings(&db, "SELECT short_name FROM operating_systems ORDER BY sort_order"),
+            ["win8", "win10-22h2", "winnext"],
+        );
+        assert_eq!(
+            query_strings(&db, "SELECT os.short_name FROM symbols s INNER JOIN operating_systems os ON os.os_id = s.first_os_id"),
+            ["win8"],
+        );
+    }
+
+    #[test]
+    fn architectures_of_same_dll_are_kept_apart() {
+        let dir = TempDir::new().unwrap();
+        let database_path = load_list(
+            &dir,
+            &[
+                "[\"win11\\\\system32\\\\kernel32.dll\"]\t1\tCreateFileW\t\tx64",
+                "[\"win11\\\\system32\\\\kernel32.dll\"]\t1\tCreateFileW\t\tarm64",
+                "[\"win11\\\\system32\\\\kernel32.dll\"]\t1\tCreateFileW\t\tarm64",
+                "[\"win11\\\\system32\\\\kernel32.dll\"]\t1\tCreateFileW",
+            ],
+            &[],
+        );
+
+        let db = Connection::open(&database_path).unwrap();
+        assert_eq!(
+            query_strings(&db, "SELECT COALESCE(architecture, '-') FROM symbol_dll_os ORDER BY 1"),
+            ["-", "arm64", "x64"],
+        );
+    }
+
+    #[test]
+    fn failed_deprecated_list_marks_no_symbols() {
+        let dir = TempDir::new().unwrap();
+        let list = write_list(&dir, "list.tsv", &[
+            r#"["win95\\system\\kernel32.dll"]	1	CreateFileA"#,
+            r#"["win95\\system\\kernel32.dll"]	2	DeleteFileA"#,
+        ]);
+        // the invalid UTF-8 on the second line fails reading the list after the first symbol has
+        // been marked
+        let deprecated = dir.path().join("deprecated.txt");
+        std::fs::write(&deprecated, b"CreateFileA\n\xFF\xFE\n").unwrap();
+        let database_path = dir.path().join("winapi.sqlite3");
+        let opts = LoadOpts::parse_from([
+            "load",
+            database_path.to_str().unwrap(),
+            list.to_str().unwrap(),
+            "--deprecated",
+            deprecated.to_str().unwrap(),
+            "--continue-on-error",
+        ]);
+        assert!(!load_lists(&opts));
+
+        let db = Connection::open(&database_path).unwrap();
+        assert_eq!(
+            query_strings(&db, "SELECT raw_name FROM symbols ORDER BY 1"),
+            ["CreateFileA", "DeleteFileA"],
+        );
+        assert!(query_strings(&db, "SELECT raw_name FROM symbols WHERE deprecated = 1").is_empty());
+    }
+
+    #[test]
+    fn override_replaces_demangled_name() {
+        let lines = [
+            r#"["win95\\system\\kernel32.dll"]	1	?f@@YAXHH@Z"#,
+            r#"["win95\\system\\kernel32.dll"]	2	"#,
+        ];
+
+        #[cfg(feature = "ms_cpp_filt")]
+        {
+            let dir = TempDir::new().unwrap();
+            let database_path = load_list(&dir, &lines, &[]);
+            let db = Connection::open(&database_path).unwrap();
+            assert_eq!(
+                query_strings(&db, "SELECT friendly_name FROM symbols WHERE raw_name IS NOT NULL"),
+                ["void __cdecl f(int, int)"],
+            );
+        }
+
+        let dir = TempDir::new().unwrap();
+        let overrides = write_list(&dir, "overrides.tsv", &[
+            "?f@@YAXHH@Z\tf(int, int)",
+            "system\\kernel32.dll#2\tOrdinalTwo",
+        ]);
+        let database_path = load_list(&dir, &lines, &["--overrides", overrides.to_str().unwrap()]);
+        let db = Connection::open(&database_path).unwrap();
+        assert_eq!(
+            query_strings(&db, "SELECT friendly_name FROM symbols ORDER BY sym_id"),
+            ["f(int, int)", "OrdinalTwo"],
+        );
+    }
+
+    #[test]
+    fn failed_overrides_replace_no_names() {
+        let dir = TempDir::new().unwrap();
+        let list = write_list(&dir, "list.tsv", &[r#"["win95\\system\\kernel32.dll"]	1	CreateFileA"#]);
+        // the invalid UTF-8 on the second line fails reading the overrides after the first name has
+        // been replaced
+        let overrides = dir.path().join("overrides.tsv");
+        std::fs::write(&overrides, b"CreateFileA\tOpenFile\n\xFF\xFE\n").unwrap();
+        let database_path = dir.path().join("winapi.sqlite3");
+        let opts = LoadOpts::parse_from([
+            "load",
+            database_path.to_str().unwrap(),
+            list.to_str().unwrap(),
+            "--overrides",
+            overrides.to_str().unwrap(),
+            "--continue-on-error",
+        ]);
+        assert!(!load_lists(&opts));
+
+        let db = Connection::open(&database_path).unwrap();
+        assert!(query_strings(&db, "SELECT friendly_name FROM symbols WHERE friendly_name IS NOT NULL").is_empty());
+        assert_eq!(query_strings(&db, "SELECT raw_name FROM symbols"), ["CreateFileA"]);
+    }
 }
@@ -1,8 +1,55 @@
 use msvc_demangler::{demangle, DemangleFlags};
 
 
-pub fn demangle_cpp_name(input: &str) -> Result<String, String> {
-    let flags = DemangleFlags::llvm();
-    demangle(input, flags)
+pub fn demangle_cpp_name(input: &str, flags: DemangleFlags) -> Result<String, String> {
+    let msvc_error = match demangle(input, flags) {
+        Ok(demangled) => return Ok(demangled),
+        Err(e) => e.to_string(),
+    };
+
+    #[cfg(feature = "itanium")]
+    if input.starts_with("_Z") {
+        return demangle_itanium_name(input);
+    }
+
+    Err(msvc_error)
+}
+
+#[cfg(feature = "itanium")]
+fn demangle_itanium_name(input: &str) -> Result<String, String> {
+    let symbol = cpp_demangle::Symbol::new(input)
+        .map_err(|e| e.to_string())?;
+    symbol.demangle(&cpp_demangle::DemangleOptions::default())
         .map_err(|e| e.to_string())
 }
+
+
+#[cfg(test)]
+mod tests {
+    use msvc_demangler::DemangleFlags;
+
+    use super::demangle_cpp_name;
+
+    #[test]
+    fn demangles_msvc_name() {
+        assert_eq!(
+            demangle_cpp_name("??1exception@@UAE@XZ", DemangleFlags::llvm()).unwrap(),
+            "public: virtual __thiscall exception::~exception(void)",
+        );
+    }
+
+    #[cfg(feature = "itanium")]
+    #[test]
+    fn falls_back_to_itanium() {
+        assert_eq!(
+            demangle_cpp_name("_ZN9wikipedia7article6formatEv", DemangleFlags::llvm()).unwrap(),
+            "wikipedia::article::format()",
+        );
+    }
+
+    #[cfg(not(feature = "itanium"))]
+    #[test]
+    fn rejects_itanium_without_feature() {
+        assert!(demangle_cpp_name("_ZN9wikipedia7article6formatEv", DemangleFlags::llvm()).is_err());
+    }
+}
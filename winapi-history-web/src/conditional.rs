@@ -0,0 +1,99 @@
+//! Answering conditional requests (`If-Modified-Since`) without querying the data of the page.
+//!
+//! Only pages which are rendered in full before responding (and the Atom feed) carry a
+//! `Last-Modified` header and may be answered with `304 Not Modified`. Streamed pages, the exports
+//! (CSV, module-definition files and Markdown), the endpoints under `/api`, the sitemaps and the
+//! icons are always returned in full, without a validator.
+
+
+use std::convert::Infallible;
+use std::time::{Duration, SystemTime};
+
+use rocket::{Request, Response};
+use rocket::http::{Header, Status};
+use rocket::request::{FromRequest, Outcome};
+use rocket::response::Responder;
+
+use crate::{connect_to_database, query_reindexed_at, DbPool};
+
+
+/// The time at which the data last changed, and whether the client's copy of the page is at least
+/// as recent.
+///
+/// The time is queried anew with each request, so loading new data invalidates the clients' caches
+/// without restarting the web server. It is truncated to the whole seconds which an HTTP date can
+/// represent.
+pub struct Freshness {
+    pub last_change: Option<SystemTime>,
+    pub client_is_current: bool,
+}
+impl Freshness {
+    /// Renders the page using the given function unless the client's copy is current, in which case
+    /// the page is answered with `304 Not Modified` without rendering it.
+    ///
+    /// Only for pages whose body is produced in full before responding; a streamed body cannot be
+    /// taken back once its producer has started.
+    pub fn respond<R, F: FnOnce() -> R>(self, render: F) -> Conditional<R> {
+        match self.last_change {
+            Some(last_change) if self.client_is_current => Conditional::NotModified(last_change),
+            last_change_opt => Conditional::Modified(render(), last_change_opt),
+        }
+    }
+}
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for Freshness {
+    type Error = Infallible;
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        let last_change = last_change(request);
+        let client_is_current = match (last_change, request.headers().get_one("If-Modified-Since")) {
+            (Some(last_change), Some(if_modified_since)) => httpdate::parse_http_date(if_modified_since)
+                .is_ok_and(|since| last_change <= since),
+            _ => false,
+        };
+        Outcome::Success(Freshness {
+            last_change,
+            client_is_current,
+        })
+    }
+}
+
+/// Obtains the time at which the data last changed, truncated to whole seconds.
+fn last_change(request: &Request<'_>) -> Option<SystemTime> {
+    let db_pool = request.rocket().state::<DbPool>()?;
+    let db = connect_to_database(db_pool)?;
+    let reindexed_at = query_reindexed_at(&db)?;
+    let reindexed_at_secs = u64::try_from(reindexed_at / 1000).ok()?;
+    Some(SystemTime::UNIX_EPOCH + Duration::from_secs(reindexed_at_secs))
+}
+
+
+/// Either `304 Not Modified` or the rendered page; a successful page obtains a `Last-Modified`
+/// header with the time at which the data last changed.
+pub enum Conditional<R> {
+    NotModified(SystemTime),
+    Modified(R, Option<SystemTime>),
+}
+impl<'r, 'o : 'r, R: Responder<'r, 'o>> Responder<'r, 'o> for Conditional<R> {
+    fn respond_to(self, request: &'r Request<'_>) -> rocket::response::Result<'o> {
+        match self {
+            Self::NotModified(last_change) => {
+                Response::build()
+                    .status(Status::NotModified)
+                    .header(last_modified_header(last_change))
+                    .ok()
+            },
+            Self::Modified(page, last_change_opt) => {
+                let mut response = page.respond_to(request)?;
+                if response.status() == Status::Ok && let Some(last_change) = last_change_opt {
+                    response.set_header(last_modified_header(last_change));
+                }
+                Ok(response)
+            },
+        }
+    }
+}
+
+fn last_modified_header(last_change: SystemTime) -> Header<'static> {
+    Header::new("Last-Modified", httpdate::fmt_http_date(last_change))
+}
@@ -0,0 +1,229 @@
+//! Fairings applying to all routes.
+
+
+use std::path::PathBuf;
+
+use async_compression::Level;
+use async_compression::tokio::bufread::{BrotliEncoder, GzipEncoder};
+use rocket::{Build, Request, Response, Rocket};
+use rocket::fairing::{self, Fairing, Info, Kind};
+use rocket::http::Header;
+use rocket::tokio::io::BufReader;
+use tracing::{error, info};
+
+use crate::{check_database, DbPool};
+use crate::forwarding::client_ip;
+
+
+/// Checks at startup whether the database is usable, turning a missing or outdated database into an
+/// obvious diagnostic instead of a failure of every request.
+///
+/// If `require_database` is set, the web server refuses to launch if the database is not usable;
+/// otherwise, the problem is only logged.
+pub struct DatabaseCheck {
+    pub database_path: PathBuf,
+    pub require_database: bool,
+}
+#[rocket::async_trait]
+impl Fairing for DatabaseCheck {
+    fn info(&self) -> Info {
+        Info {
+            name: "database check",
+            kind: Kind::Ignite,
+        }
+    }
+
+    async fn on_ignite(&self, rocket: Rocket<Build>) -> fairing::Result {
+        let Some(db_pool) = rocket.state::<DbPool>()
+            else { return Ok(rocket) };
+        let Err(problem) = check_database(&self.database_path, db_pool)
+            else { return Ok(rocket) };
+
+        error!(
+            "DATABASE NOT USABLE: {} (database path: {}); pages requiring the database will fail",
+            problem, self.database_path.display(),
+        );
+        if self.require_database {
+            error!("refusing to launch since the database is required");
+            Err(rocket)
+        } else {
+            Ok(rocket)
+        }
+    }
+}
+
+
+/// Logs each request along with the status of its response.
+///
+/// The entries are logged at the `info` level with the target `access`, so they can be enabled
+/// using `RUST_LOG=access=info`.
+pub struct AccessLog;
+#[rocket::async_trait]
+impl Fairing for AccessLog {
+    fn info(&self) -> Info {
+        Info {
+            name: "access log",
+            kind: Kind::Response,
+        }
+    }
+
+    async fn on_response<'r>(&self, request: &'r Request<'_>, response: &mut Response<'r>) {
+        let client = match client_ip(request) {
+            Some(ip) => ip.to_string(),
+            None => "-".to_owned(),
+        };
+        info!(
+            target: "access",
+            "{} {} {} {}",
+            client, request.method(), request.uri(), response.status().code,
+        );
+    }
+}
+
+
+/// The media types (type and subtype) of the responses worth compressing.
+const COMPRESSIBLE_MEDIA_TYPES: [(&str, &str); 3] = [
+    ("text", "html"),
+    ("text", "csv"),
+    ("application", "json"),
+];
+
+/// The size in bytes below which a response is not compressed, since the savings would not be
+/// worth the overhead.
+///
+/// Streamed responses have no known size and are always compressed; they are only used for large
+/// pages anyway.
+const MIN_COMPRESSED_SIZE: usize = 1024;
+
+/// A content coding with which a response can be compressed.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+enum ContentCoding {
+    Brotli,
+    Gzip,
+}
+impl ContentCoding {
+    /// The name of the coding in the `Accept-Encoding` and `Content-Encoding` headers.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Brotli => "br",
+            Self::Gzip => "gzip",
+        }
+    }
+}
+
+/// The Brotli quality with which responses are compressed.
+///
+/// Responses are compressed on the fly, and some of them are several megabytes large; Brotli's
+/// default (and highest) quality of 11 costs far too much time for that. Lower qualities still
+/// compress better than gzip at its default level.
+const BROTLI_QUALITY: i32 = 4;
+
+/// Picks the content coding to use for a response from the value of an `Accept-Encoding` header,
+/// preferring Brotli to gzip.
+///
+/// Codings with a quality value of zero are considered unacceptable, even if `*` is acceptable;
+/// all other quality values are otherwise ignored.
+fn choose_content_coding(accept_encoding: &str) -> Option<ContentCoding> {
+    // for each coding: None if not mentioned, otherwise whether it is acceptable
+    let mut brotli_acceptable = None;
+    let mut gzip_acceptable = None;
+    let mut others_acceptable = None;
+    for entry in accept_encoding.split(',') {
+        let mut pieces = entry.split(';').map(|p| p.trim());
+        let coding_name = pieces.next().unwrap_or("");
+        let acceptable = !pieces
+            .filter_map(|p| p.strip_prefix("q="))
+            .any(|q| q.parse::<f64>().map(|qv| qv == 0.0).unwrap_or(false));
+
+        if coding_name.eq_ignore_ascii_case("br") {
+            brotli_acceptable = Some(acceptable);
+        } else if coding_name.eq_ignore_ascii_case("gzip") {
+            gzip_acceptable = Some(acceptable);
+        } else if coding_name == "*" {
+            others_acceptable = Some(acceptable);
+        }
+    }
+
+    let others_acceptable = others_acceptable.unwrap_or(false);
+    if brotli_acceptable.unwrap_or(others_acceptable) {
+        Some(ContentCoding::Brotli)
+    } else if gzip_acceptable.unwrap_or(others_acceptable) {
+        Some(ContentCoding::Gzip)
+    } else {
+        None
+    }
+}
+
+/// Compresses HTML, CSV and JSON responses using Brotli or gzip, depending on what the client
+/// accepts.
+///
+/// Responses which already have a `Content-Encoding` are left alone, as are responses smaller than
+/// [`MIN_COMPRESSED_SIZE`]. The compressed body is streamed, so it is sent chunked instead of with
+/// a `Content-Length`.
+pub struct Compression;
+#[rocket::async_trait]
+impl Fairing for Compression {
+    fn info(&self) -> Info {
+        Info {
+            name: "compression",
+            kind: Kind::Response,
+        }
+    }
+
+    async fn on_response<'r>(&self, request: &'r Request<'_>, response: &mut Response<'r>) {
+        let Some(content_type) = response.content_type()
+            else { return };
+        let compressible = COMPRESSIBLE_MEDIA_TYPES
+            .iter()
+            .any(|(top, sub)| content_type.top() == *top && content_type.sub() == *sub);
+        if !compressible {
+            return;
+        }
+
+        // the response differs depending on Accept-Encoding, even if it ends up uncompressed
+        response.adjoin_header(Header::new("Vary", "Accept-Encoding"));
+
+        if response.headers().contains("Content-Encoding") {
+            // already compressed
+            return;
+        }
+        if let Some(size) = response.body_mut().size().await
+            && size < MIN_COMPRESSED_SIZE
+        {
+            return;
+        }
+        // a header may be split across multiple lines
+        let accept_encoding: Vec<&str> = request.headers().get("Accept-Encoding").collect();
+        let Some(coding) = choose_content_coding(&accept_encoding.join(","))
+            else { return };
+
+        let body = BufReader::new(response.body_mut().take());
+        match coding {
+            ContentCoding::Brotli => response.set_streamed_body(BrotliEncoder::with_quality(body, Level::Precise(BROTLI_QUALITY))),
+            ContentCoding::Gzip => response.set_streamed_body(GzipEncoder::new(body)),
+        }
+        response.set_header(Header::new("Content-Encoding", coding.name()));
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::{choose_content_coding, ContentCoding};
+
+
+    #[test]
+    fn brotli_is_preferred_to_gzip() {
+        assert_eq!(choose_content_coding("gzip, deflate, br"), Some(ContentCoding::Brotli));
+        assert_eq!(choose_content_coding("gzip, deflate"), Some(ContentCoding::Gzip));
+        assert_eq!(choose_content_coding("*"), Some(ContentCoding::Brotli));
+        assert_eq!(choose_content_coding("identity"), None);
+    }
+
+    #[test]
+    fn explicitly_unacceptable_coding_is_not_chosen_through_wildcard() {
+        assert_eq!(choose_content_coding("br;q=0, *"), Some(ContentCoding::Gzip));
+        assert_eq!(choose_content_coding("br;q=0, gzip;q=0, *"), None);
+        assert_eq!(choose_content_coding("gzip, *;q=0"), Some(ContentCoding::Gzip));
+    }
+}
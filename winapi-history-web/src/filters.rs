@@ -0,0 +1,31 @@
+//! Custom filters for the Askama templates.
+
+
+use std::fmt::Display;
+
+use percent_encoding::utf8_percent_encode;
+
+use crate::URL_UNRESERVED;
+
+
+/// Percent-encodes a value so that it can be used as a single segment of a URL path.
+///
+/// Uses the same character set as the URLs built by the handlers, so that names containing
+/// characters such as `?`, `@`, `$` or `\` link to the routes that serve them.
+pub fn percent_encode<T: Display>(value: T, _values: &dyn askama::Values) -> askama::Result<String> {
+    let value_string = value.to_string();
+    Ok(utf8_percent_encode(&value_string, URL_UNRESERVED).to_string())
+}
+
+/// Formats a number with commas separating each group of three digits, e.g. `12,345`.
+pub fn thousands<T: Display>(value: T, _values: &dyn askama::Values) -> askama::Result<String> {
+    let digits = value.to_string();
+    let mut grouped = String::with_capacity(digits.len() + digits.len() / 3);
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            grouped.push(',');
+        }
+        grouped.push(c);
+    }
+    Ok(grouped)
+}
@@ -0,0 +1,122 @@
+//! Handling of requests forwarded by reverse proxies.
+
+
+use std::convert::Infallible;
+use std::net::{IpAddr, SocketAddr};
+
+use rocket::Request;
+use rocket::request::{FromRequest, Outcome};
+
+use crate::WebConfig;
+
+
+/// Parses an address from an `X-Forwarded-For` header, which may or may not contain a port.
+fn parse_forwarded_address(address: &str) -> Option<IpAddr> {
+    if let Ok(ip) = address.parse::<IpAddr>() {
+        return Some(ip);
+    }
+    if let Ok(socket_address) = address.parse::<SocketAddr>() {
+        return Some(socket_address.ip());
+    }
+    None
+}
+
+/// Returns whether the request was made by one of the trusted proxies in the configuration.
+fn is_from_trusted_proxy(request: &Request<'_>) -> bool {
+    let Some(peer_ip) = request.remote().map(|r| r.ip())
+        else { return false };
+    let Some(config) = request.rocket().state::<WebConfig>()
+        else { return false };
+    config.trusted_proxies.contains(&peer_ip)
+}
+
+/// Obtains the first value of a forwarding header, which is the one added by the proxy closest to
+/// the client.
+fn first_forwarded_value<'r>(request: &'r Request<'_>, header_name: &str) -> Option<&'r str> {
+    let value = request.headers()
+        .get_one(header_name)?
+        .split(',')
+        .next()?
+        .trim();
+    if value.is_empty() {
+        None
+    } else {
+        Some(value)
+    }
+}
+
+/// Checks whether a value from an `X-Forwarded-Host` header looks like a host name or address with
+/// an optional port, and not like something that would change the meaning of the URL around it.
+fn is_plausible_host(host: &str) -> bool {
+    host.chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '.' || c == '-' || c == ':' || c == '[' || c == ']')
+}
+
+/// Obtains the IP address of the client that made the request.
+///
+/// If the request was made by one of the trusted proxies in the configuration, the client address
+/// is taken from the `X-Forwarded-For` header: the chain of addresses is walked from the most
+/// recent hop backwards and the first address that is not a trusted proxy is returned. Otherwise,
+/// the address of the peer is returned and `X-Forwarded-For` is ignored, since any client can set
+/// it to any value.
+pub fn client_ip(request: &Request<'_>) -> Option<IpAddr> {
+    let peer_ip = request.remote()?.ip();
+    let Some(config) = request.rocket().state::<WebConfig>()
+        else { return Some(peer_ip) };
+    if !config.trusted_proxies.contains(&peer_ip) {
+        return Some(peer_ip);
+    }
+
+    let forwarded_addresses: Vec<&str> = request.headers()
+        .get("X-Forwarded-For")
+        .flat_map(|value| value.split(','))
+        .map(|address| address.trim())
+        .collect();
+    let mut client_ip = peer_ip;
+    for address in forwarded_addresses.into_iter().rev() {
+        let Some(ip) = parse_forwarded_address(address)
+            else { break };
+        client_ip = ip;
+        if !config.trusted_proxies.contains(&ip) {
+            break;
+        }
+    }
+    Some(client_ip)
+}
+
+/// The base URL of the site as seen by the client, ending in a slash.
+///
+/// Used where absolute links must be emitted. If the request was made by one of the trusted proxies
+/// in the configuration, the scheme and host are taken from the `X-Forwarded-Proto` and
+/// `X-Forwarded-Host` headers. Otherwise (or if those headers are missing or invalid), the scheme
+/// is `http` and the host is taken from the `Host` header, falling back to the address Rocket is
+/// configured to listen on.
+pub struct BaseUrl(pub String);
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for BaseUrl {
+    type Error = Infallible;
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        let mut scheme = "http";
+        let mut forwarded_host = None;
+        if is_from_trusted_proxy(request) {
+            match first_forwarded_value(request, "X-Forwarded-Proto") {
+                Some(p) if p.eq_ignore_ascii_case("https") => scheme = "https",
+                Some(p) if p.eq_ignore_ascii_case("http") => scheme = "http",
+                _ => {},
+            }
+            forwarded_host = first_forwarded_value(request, "X-Forwarded-Host")
+                .filter(|h| is_plausible_host(h));
+        }
+
+        let host = match (forwarded_host, request.host()) {
+            (Some(h), _) => h.to_owned(),
+            (None, Some(h)) => h.to_string(),
+            (None, None) => {
+                let config = request.rocket().config();
+                SocketAddr::new(config.address, config.port).to_string()
+            },
+        };
+        Outcome::Success(BaseUrl(format!("{}://{}/", scheme, host)))
+    }
+}
@@ -0,0 +1,58 @@
+//! Continuing a sorted list after a given entry (keyset pagination).
+//!
+//! Instead of skipping a number of entries (as with SQL `OFFSET`), a page requested by keyset
+//! starts directly after the entry with the given sort key. This remains fast deep into huge
+//! lists and does not skip or repeat entries if the database is reloaded between pages.
+//!
+//! The sort key of an entry is the list of the values of the expressions by which the list is
+//! ordered. It is passed around as a token in the `after` query parameter.
+//!
+//! # Token format
+//!
+//! A token is a JSON array containing, in order, the value of each sort key expression for the last
+//! entry of the previous page; each value is a string or an integer. Which expressions make up the
+//! sort key depends on the list and its order, so a token is only meaningful for the list and
+//! order that produced it. Tokens are generated by the database (using `json_array`) and should
+//! be treated as opaque by clients.
+
+
+/// The expressions by which a list is ordered, from most to least significant.
+///
+/// The expressions must never be `NULL` (wrap nullable columns in `COALESCE` and, where `NULL`s
+/// must be sorted last, precede them with an `... IS NULL` expression) and the last expressions
+/// must identify an entry uniquely; otherwise entries are skipped when continuing after them.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct SortKey(pub &'static [&'static str]);
+impl SortKey {
+    /// The SQL `ORDER BY` clause (without the keywords) sorting by this key.
+    pub fn order_by(&self) -> String {
+        self.0.join(", ")
+    }
+
+    /// An SQL expression assembling the token of the current row.
+    pub fn token_expression(&self) -> String {
+        format!("json_array({})", self.0.join(", "))
+    }
+
+    /// An SQL condition true for all rows sorted after the token passed as the parameter with the
+    /// given (1-based) index; true for all rows if that parameter is `NULL`.
+    pub fn after_condition(&self, param_index: usize) -> String {
+        let token_values: Vec<String> = (0..self.0.len())
+            .map(|i| format!("json_extract(?{}, '$[{}]')", param_index, i))
+            .collect();
+        format!(
+            "(?{} IS NULL OR ({}) > ({}))",
+            param_index,
+            self.0.join(", "),
+            token_values.join(", "),
+        )
+    }
+
+    /// Whether the given token could have been generated for this key.
+    pub fn is_valid_token(&self, token: &str) -> bool {
+        let Ok(values) = serde_json::from_str::<Vec<serde_json::Value>>(token)
+            else { return false };
+        values.len() == self.0.len()
+            && values.iter().all(|v| v.is_string() || v.is_i64())
+    }
+}
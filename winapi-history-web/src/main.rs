@@ -1,14 +1,64 @@
-use std::collections::BTreeMap;
-use std::fmt::Debug;
+mod conditional;
+mod fairings;
+mod filters;
+mod forwarding;
+mod keyset;
+mod negotiation;
+mod openapi;
+mod pagination;
+mod paths;
+mod spans;
+
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::{self, Debug};
 use std::io::Cursor;
+use std::net::IpAddr;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
 
 use askama::Template;
 use percent_encoding::{utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
-use rocket::{Request, Response};
+use rocket::{Request, Response, State};
+use rocket::fairing::AdHoc;
+use rocket::form::FromFormField;
+use rocket::request::FromParam;
 use rocket::response::{Redirect, Responder};
-use rocket::http::{ContentType, Status};
-use rusqlite::{Connection, OpenFlags, Params, Row, Statement};
-use tracing::error;
+use rocket::response::stream::ReaderStream;
+use rocket::http::{ContentType, Header, Status};
+use rocket::http::uri::Origin;
+use rocket::tokio::sync::mpsc;
+use r2d2::PooledConnection;
+use r2d2_sqlite::SqliteConnectionManager;
+use rusqlite::{CachedStatement, Connection, OpenFlags, Params, Row, Statement};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, debug_span, error, info};
+
+use crate::conditional::{Conditional, Freshness};
+use crate::forwarding::BaseUrl;
+use crate::keyset::SortKey;
+use crate::negotiation::ResponseFormat;
+use crate::pagination::Pagination;
+use crate::paths::{path_to_root_from, PathToRoot};
+use crate::spans::RouteSpan;
+
+
+/// The default path to the SQLite database containing the API information.
+const DEFAULT_DATABASE_PATH: &str = "winapi.sqlite3";
+
+/// The path to the directory containing the static assets (operating system icons and favicon) if
+/// none is configured.
+const DEFAULT_STATIC_PATH: &str = "static";
+
+/// The version of the database schema (`schema_version` table) which the queries are written for.
+///
+/// Must be kept in sync with `db/schema.sql` and the migrations applied by `whload`.
+const EXPECTED_SCHEMA_VERSION: i64 = 16;
+
+/// The URL of a Microsoft Learn search, to which the search terms are appended.
+const LEARN_SEARCH_URL_PREFIX: &str = "https://learn.microsoft.com/en-us/search/?terms=";
 
 
 /// Characters not reserved for any special use in URLs.
@@ -20,11 +70,268 @@ use tracing::error;
 const URL_UNRESERVED: &AsciiSet = &NON_ALPHANUMERIC
     .remove(b'-').remove(b'.').remove(b'_').remove(b'~');
 
+/// The default number of symbols per page in symbol lists.
+const DEFAULT_SYMBOLS_PER_PAGE: u64 = 500;
+
+/// The number of results of a symbol search if no limit is given.
+const DEFAULT_SEARCH_RESULTS: u64 = 500;
+
+/// The maximum number of rows of a symbol list if none is configured.
+const DEFAULT_MAX_ROWS: u64 = 10_000;
+
+/// Shown in place of search results if the search query is empty.
+const EMPTY_SEARCH_HINT: &str = "Please enter a part of the name of the symbol to search for.";
+
+/// The maximum number of characters of the SQL text of a query that are logged along with its
+/// duration.
+const MAX_LOGGED_QUERY_CHARS: usize = 200;
+
+/// The number of autocompletion suggestions returned if no limit is given.
+const DEFAULT_AUTOCOMPLETE_LIMIT: u64 = 10;
+
+/// The maximum number of autocompletion suggestions that may be requested.
+const MAX_AUTOCOMPLETE_LIMIT: u64 = 50;
+
+/// The number of prepared statements cached by each database connection.
+///
+/// Each cached statement keeps its compiled bytecode in memory (generally a few kilobytes), and
+/// every pooled connection has its own cache, so the memory cost is this capacity times the number
+/// of connections. The capacity is chosen to hold all the distinct queries issued by the handlers,
+/// so that a query is only parsed once per connection instead of once per request.
+const STATEMENT_CACHE_CAPACITY: usize = 64;
+
+/// How long to wait for a database connection before giving up.
+///
+/// Opening the SQLite database takes next to no time, so a connection that cannot be obtained
+/// quickly generally means that the database is missing or broken. Giving up soon means that pages
+/// fail promptly in that case instead of keeping the client waiting.
+const DATABASE_CONNECTION_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// How long to wait for a database connection when checking whether the database is usable.
+///
+/// The check is run by health checks, which should report a broken database promptly, so it waits
+/// even less than pages do.
+const DATABASE_CHECK_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// The name of the group of DLLs on an operating system's page whose paths have no directory.
+const ROOT_DLL_DIRECTORY: &str = "(root)";
+
+/// The number of bytes of a streamed page that are rendered before they are sent to the client.
+const STREAMED_CHUNK_SIZE: usize = 64 * 1024;
+
+/// The number of rendered chunks of a streamed page that may wait to be sent to the client.
+///
+/// Once this many chunks are waiting, rendering pauses until the client catches up, which bounds the
+/// memory required by a streamed page to roughly this number times [`STREAMED_CHUNK_SIZE`].
+const STREAMED_CHUNK_BACKLOG: usize = 4;
+
+/// The pool of connections to the database.
+type DbPool = r2d2::Pool<SqliteConnectionManager>;
+
+/// An operating system along with its ID in the database.
+type IdentifiedOs = (i64, OperatingSystemPart);
+
+/// Operating systems by their short names.
+type OsesByShortName = BTreeMap<String, IdentifiedOs>;
+
+/// The minimum number of characters of a prefix starting with `?` for which symbols are listed.
+///
+/// Mangled C++ names start with `?` and vastly outnumber all other names. The root page buckets such
+/// names by their first two characters, so the alphabetical list must accept prefixes of that
+/// length.
+const MIN_MANGLED_PREFIX_CHARS: usize = 2;
+
+/// The minimum number of characters of a search or autocomplete query starting with `?`.
+///
+/// Even the two-character buckets of mangled names are huge, and a search scans them with `LIKE`,
+/// so these queries must contain a few more characters than the root page's buckets.
+const MIN_MANGLED_SEARCH_CHARS: usize = 4;
+
+/// Shown in place of the presence matrix if no operating systems have been chosen.
+const EMPTY_MATRIX_HINT: &str = "Please choose at least one operating system to compare.";
+
+/// Shown in place of the differences if an operating system is compared to itself.
+const SAME_OS_COMPARISON_HINT: &str = "An operating system cannot be compared to itself. Please choose two different operating systems.";
+
+/// Shown instead of a symbol list if the token after which the list should continue is malformed or
+/// belongs to a different order.
+const INVALID_AFTER_HINT: &str = "The position after which the list should continue is invalid. Please start again from the first page.";
+
+/// The hint displayed instead of results if a query for mangled names is too short.
+const MANGLED_QUERY_TOO_SHORT_HINT: &str = "Type more characters to search mangled names.";
+
+/// Shown instead of an alphabetical list of symbols if no symbols start with the requested prefix.
+const NO_SYMBOLS_FOR_PREFIX_HINT: &str = "There are no symbols for this prefix.";
+
+/// Shown above the list of symbols whose names only differ in case from a symbol that was not found.
+const CASE_VARIANTS_HINT: &str = "No symbol with exactly this name exists, but there are symbols whose names only differ in case:";
+
+/// The maximum number of ordinals spanned by a range of ordinals whose symbols are listed.
+const MAX_ORDINAL_RANGE_SPAN: u64 = 4096;
+
+/// Shown instead of the symbols if the lower end of an ordinal range is above its upper end.
+const INVERTED_ORDINAL_RANGE_HINT: &str = "The first ordinal of the range must not be greater than the last.";
+
+/// Shown instead of the symbols if an ordinal range spans more than [`MAX_ORDINAL_RANGE_SPAN`]
+/// ordinals.
+fn ordinal_range_too_large_hint() -> String {
+    format!("At most {} ordinals can be listed at once.", MAX_ORDINAL_RANGE_SPAN)
+}
+
+/// The number of leading characters of a symbol name that was not found which must match for
+/// another symbol to be suggested in its place.
+const SYMBOL_SUGGESTION_PREFIX_CHARS: usize = 4;
+
+/// The maximum number of symbols suggested in place of a symbol that was not found.
+const MAX_SYMBOL_SUGGESTIONS: u64 = 10;
+
+/// The number of leading characters of a symbol name that was not found which must match for
+/// another symbol to be considered when looking for the nearest names by edit distance.
+const NEAREST_SYMBOL_PREFIX_CHARS: usize = 2;
+
+/// The maximum number of symbols whose edit distance to a name that was not found is calculated.
+const MAX_NEAREST_SYMBOL_CANDIDATES: u64 = 4096;
+
+/// The maximum number of symbols with the nearest names by edit distance that are suggested.
+const MAX_NEAREST_SYMBOLS: usize = 5;
+
+/// Finds the DLLs (path and whether they belong to a secondary platform) present in one operating
+/// system (?1) but not in another (?2).
+///
+/// If ?3 is true, the editions of each operating system are considered part of it.
+const OS_DLL_DIFF_QUERY: &str = "
+    SELECT
+        dll.path,
+        dll.secondary_platform
+    FROM
+        dlls dll
+    WHERE
+        EXISTS (
+            SELECT 1
+            FROM symbol_dll_os y_sdo
+            WHERE y_sdo.os_id IN (
+                SELECT os_id
+                FROM operating_systems
+                WHERE os_id = ?1
+                OR (?3 AND parent_os_id = ?1)
+            )
+            AND y_sdo.dll_id = dll.dll_id
+        )
+        AND NOT EXISTS (
+            SELECT 1
+            FROM symbol_dll_os n_sdo
+            WHERE n_sdo.os_id IN (
+                SELECT os_id
+                FROM operating_systems
+                WHERE os_id = ?2
+                OR (?3 AND parent_os_id = ?2)
+            )
+            AND n_sdo.dll_id = dll.dll_id
+        )
+    ORDER BY
+        1
+";
+
+/// Finds the symbols present in one operating system (?1) but not in another (?2), named as well as
+/// ordinal but no meta-functions.
+///
+/// Along with each symbol, the DLLs exporting it in the first operating system are returned as a
+/// JSON array of objects (see [`DllPart::try_vec_from_json_row`]).
+///
+/// If ?3 is true, the editions of each operating system are considered part of it.
+const OS_SYMBOL_DIFF_QUERY: &str = "
+    SELECT
+        sym.raw_name,
+        sym.friendly_name,
+        sym.dll_name,
+        sym.ordinal,
+        sym.is_meta_func,
+        sym.deprecated,
+        (
+            SELECT
+                json_group_array(json_object(
+                    'path', y_dll.path,
+                    'secondary_platform', json(CASE WHEN y_dll.secondary_platform THEN 'true' ELSE 'false' END)
+                ))
+            FROM (
+                SELECT DISTINCT
+                    dll.path,
+                    dll.secondary_platform
+                FROM
+                    symbol_dll_os d_sdo
+                    INNER JOIN dlls dll
+                        ON dll.dll_id = d_sdo.dll_id
+                WHERE
+                    d_sdo.os_id IN (
+                        SELECT os_id
+                        FROM operating_systems
+                        WHERE os_id = ?1
+                        OR (?3 AND parent_os_id = ?1)
+                    )
+                    AND d_sdo.sym_id = sym.sym_id
+                ORDER BY
+                    dll.path
+            ) y_dll
+        )
+    FROM
+        symbols sym
+    WHERE
+        sym.is_meta_func = 0
+        AND EXISTS (
+            SELECT 1
+            FROM symbol_dll_os y_sdo
+            WHERE y_sdo.os_id IN (
+                SELECT os_id
+                FROM operating_systems
+                WHERE os_id = ?1
+                OR (?3 AND parent_os_id = ?1)
+            )
+            AND y_sdo.sym_id = sym.sym_id
+        )
+        AND NOT EXISTS (
+            SELECT 1
+            FROM symbol_dll_os n_sdo
+            WHERE n_sdo.os_id IN (
+                SELECT os_id
+                FROM operating_systems
+                WHERE os_id = ?2
+                OR (?3 AND parent_os_id = ?2)
+            )
+            AND n_sdo.sym_id = sym.sym_id
+        )
+    ORDER BY
+        1 ASC NULLS LAST,
+        2 ASC NULLS LAST,
+        3,
+        4
+";
+
+/// The maximum number of entries in the feed of new symbols.
+const MAX_FEED_ENTRIES: usize = 200;
+
+/// The maximum number of URLs in a single sitemap file, as specified by the sitemap protocol.
+const SITEMAP_CHUNK_SIZE: u64 = 50_000;
+
+/// The rules for web crawlers, keeping them away from the pages that are expensive to generate.
+///
+/// The location of the sitemap is appended when serving, since it must be an absolute URL.
+const ROBOTS_TXT: &str = "User-agent: *
+Disallow: /os/*/all-symbols
+Disallow: /os/*/compare/
+Disallow: /compare-os
+Disallow: /compare-matrix
+Disallow: /dll/*/compare-os
+Allow: /
+";
+
+/// The XML namespace of sitemaps and sitemap indexes.
+const SITEMAP_NAMESPACE: &str = "http://www.sitemaps.org/schemas/sitemap/0.9";
+
 
 #[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd, Template)]
 #[template(path = "root.html")]
 struct RootTemplate {
-    pub operating_systems: Vec<OperatingSystemPart>,
+    pub operating_systems: Vec<OsSymbolCountPart>,
     pub dll_start_chars: Vec<String>,
     pub func_start_chars: Vec<String>,
     pub ordinal_dll_start_chars: Vec<String>,
@@ -33,68 +340,173 @@ struct RootTemplate {
 #[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd, Template)]
 #[template(path = "os.html")]
 struct OsTemplate {
+    pub path_to_root: String,
     pub os: OperatingSystemPart,
-    pub dlls: Vec<DllPart>,
+    pub parent_os: Option<OperatingSystemPart>,
+    pub editions: Vec<OperatingSystemPart>,
+    pub dll_directories: BTreeMap<String, Vec<DllSymbolCountPart>>,
+    pub secondary: Option<bool>,
+    pub architecture: Option<Architecture>,
+    pub architectures: Vec<Architecture>, // those occurring in this operating system
+}
+impl OsTemplate {
+    /// The query string selecting the given platform and architecture filters.
+    pub fn filter_query(&self, secondary: Option<bool>, architecture: Option<Architecture>) -> String {
+        let mut params = Vec::with_capacity(2);
+        if let Some(sec) = secondary {
+            params.push(format!("secondary={}", sec));
+        }
+        if let Some(arch) = architecture {
+            params.push(format!("arch={}", arch.as_str()));
+        }
+        if params.is_empty() {
+            String::new()
+        } else {
+            format!("?{}", params.join("&"))
+        }
+    }
 }
 
 #[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd, Template)]
 #[template(path = "dll.html")]
 struct DllTemplate {
+    pub path_to_root: String,
     pub dll: DllPart,
     pub dll_operating_systems: Vec<OperatingSystemPart>,
-    pub symbols_oses: Vec<(SymbolPart, Vec<OperatingSystemPart>)>,
+    pub symbols: Vec<DllSymbolPart>,
+    pub sort: SymbolSort,
+    pub index: ListIndex,
+    pub truncated: bool, // whether symbols were omitted due to the row limit
 }
 
 #[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd, Template)]
 #[template(path = "os-sym-list.html")]
 struct OsSymbolListTemplate {
+    pub path_to_root: String,
     pub os: OperatingSystemPart,
     pub symbols: Vec<OsSymbolPart>,
+    pub pagination: Pagination,
+    pub sort: SymbolSort,
+    pub architecture: Option<Architecture>,
+    pub architectures: Vec<Architecture>, // those occurring in this operating system
+    pub truncated: bool, // whether symbols were omitted due to the row limit
+    pub hint: Option<&'static str>,
+}
+impl OsSymbolListTemplate {
+    /// The query string selecting the current order and the given architecture.
+    pub fn filter_query(&self, architecture: Option<Architecture>) -> String {
+        let mut query = format!("?sort={}", self.sort.as_str());
+        if let Some(arch) = architecture {
+            query.push_str(&format!("&arch={}", arch.as_str()));
+        }
+        query
+    }
+
+    /// The query parameters selecting the current architecture, to be appended to other links.
+    pub fn architecture_query_suffix(&self) -> String {
+        match self.architecture {
+            Some(arch) => format!("&arch={}", arch.as_str()),
+            None => String::new(),
+        }
+    }
 }
 
 #[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd, Template)]
 #[template(path = "os-dll-sym-list.html")]
 struct OsDllSymbolListTemplate {
+    pub path_to_root: String,
     pub os: OperatingSystemPart,
     pub dll: DllPart,
     pub symbols: Vec<SymbolPart>,
+    pub ordinal_range: Option<(u64, u64)>,
+    pub hint: Option<String>,
+    pub sort: SymbolSort,
 }
 
 #[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd, Template)]
 #[template(path = "symbol.html")]
 struct SymbolTemplate {
-    pub path_to_root: &'static str,
+    pub path_to_root: String,
     pub symbol: SymbolPart,
-    pub os_dlls: Vec<(OperatingSystemPart, Vec<DllPart>)>,
+    pub os_dlls: Vec<(OperatingSystemPart, Vec<SymbolDllPart>)>,
+    pub presence: Vec<PresenceInterval>,
+    pub absent_since: Option<OperatingSystemPart>,
+    pub seen_range: SeenRange,
+    pub related: Vec<SymbolPart>,
+    pub all_oses_with_presence: Vec<(OperatingSystemPart, bool)>, // ordered by release date
 }
 
 #[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd, Template)]
 #[template(path = "alpha-dll-list.html")]
 struct AlphabeticalDllListTemplate {
+    pub path_to_root: String,
     pub dll_parts: Vec<DllOsesPart>,
 }
 
 #[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd, Template)]
 #[template(path = "alpha-sym-list.html")]
 struct AlphabeticalSymbolListTemplate {
-    pub path_to_root: &'static str,
+    pub path_to_root: String,
+    pub symbols: Vec<SymbolPart>,
+    pub index: ListIndex,
+    pub hint: Option<&'static str>,
+    pub pagination: Option<Pagination>,
+    pub hide_deprecated: Option<bool>, // None if deprecated symbols cannot be hidden on this page
+    pub truncated: bool, // whether symbols were omitted due to the row limit
+}
+
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd, Template)]
+#[template(path = "search.html")]
+struct SearchTemplate {
+    pub path_to_root: String,
+    pub query: String,
+    pub dll: Option<DllPart>,
+    pub os: Option<OperatingSystemPart>,
     pub symbols: Vec<SymbolPart>,
+    pub nearest_symbols: Vec<SymbolPart>,
+    pub hint: Option<&'static str>,
+    pub truncated: bool, // whether symbols were omitted due to the row limit
 }
 
 #[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd, Template)]
 #[template(path = "compare-os.html")]
 struct CompareOsTemplate {
+    pub path_to_root: String,
     pub old_os: OperatingSystemPart,
     pub new_os: OperatingSystemPart,
+    pub merge_editions: bool,
     pub removed_dlls: Vec<String>,
     pub added_dlls: Vec<String>,
-    pub removed_symbols: Vec<SymbolPart>,
-    pub added_symbols: Vec<SymbolPart>,
+    pub removed_symbols: Vec<(SymbolPart, Vec<DllPart>)>, // each with its DLLs in the old OS
+    pub added_symbols: Vec<(SymbolPart, Vec<DllPart>)>, // each with its DLLs in the new OS
+    pub common_symbols: Option<Vec<SymbolPart>>,
+    pub hint: Option<&'static str>,
+}
+
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd, Template)]
+#[template(path = "compare-dlls.html")]
+struct CompareDllsTemplate {
+    pub path_to_root: String,
+    pub old_os: OperatingSystemPart,
+    pub new_os: OperatingSystemPart,
+    pub merge_editions: bool,
+    pub added_dlls: Vec<DllPart>,
+    pub removed_dlls: Vec<DllPart>,
+}
+
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd, Template)]
+#[template(path = "compare-matrix.html")]
+struct CompareMatrixTemplate {
+    pub path_to_root: String,
+    pub hint: Option<&'static str>,
+    pub operating_systems: Vec<OperatingSystemPart>,
+    pub symbols: Vec<(SymbolPart, Vec<bool>)>, // one entry per operating system
 }
 
 #[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd, Template)]
 #[template(path = "compare-os-dll.html")]
 struct CompareOsDllTemplate {
+    pub path_to_root: String,
     pub old_os: OperatingSystemPart,
     pub new_os: OperatingSystemPart,
     pub dll_path: String,
@@ -102,17 +514,41 @@ struct CompareOsDllTemplate {
     pub added_symbols: Vec<SymbolPart>,
 }
 
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd, Template)]
+#[template(path = "not-found.html")]
+struct NotFoundTemplate {
+    pub path_to_root: String,
+    pub path: String,
+    pub database_is_empty: bool,
+}
+
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd, Template)]
+#[template(path = "not-found-suggestions.html")]
+struct NotFoundSuggestionsTemplate {
+    pub path_to_root: String,
+    pub sym_raw_name: String,
+    pub suggestions: Vec<SymbolPart>,
+}
+
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd, Template)]
+#[template(path = "error.html")]
+struct ErrorTemplate {
+    pub path_to_root: String,
+}
+
 #[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
 struct OperatingSystemPart {
     pub short_name: String,
     pub long_name: String,
     pub has_icon: bool,
+    pub build_number: Option<String>,
 }
 impl OperatingSystemPart {
     pub fn try_from_row(field_offset: usize, row: &Row<'_>) -> Result<Self, rusqlite::Error> {
         let short_name: String = row.get(field_offset + 0)?;
         let long_name_opt: Option<String> = row.get(field_offset + 1)?;
         let has_icon: bool = row.get(field_offset + 2)?;
+        let build_number: Option<String> = row.get(field_offset + 3)?;
 
         let long_name = if let Some(ln) = long_name_opt {
             ln
@@ -124,12 +560,20 @@ impl OperatingSystemPart {
             short_name,
             long_name,
             has_icon,
+            build_number,
         };
         Ok(os_part)
     }
 }
 
+/// A contiguous range of operating systems, ordered by release date, in which a symbol is present.
 #[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+struct PresenceInterval {
+    pub first: OperatingSystemPart,
+    pub last: Option<OperatingSystemPart>, // None if the interval only contains `first`
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, JsonSchema, Serialize)]
 struct DllPart {
     pub path: String,
     pub secondary_platform: bool,
@@ -144,20 +588,191 @@ impl DllPart {
         };
         Ok(dll_part)
     }
+
+    /// Reads a list of DLLs which the query has assembled into a JSON array of objects, each with
+    /// the members `path` and `secondary_platform`.
+    pub fn try_vec_from_json_row(field_offset: usize, row: &Row<'_>) -> Result<Vec<Self>, rusqlite::Error> {
+        let json: String = row.get(field_offset)?;
+        serde_json::from_str(&json)
+            .map_err(|e| rusqlite::Error::FromSqlConversionFailure(
+                field_offset,
+                rusqlite::types::Type::Text,
+                Box::new(e),
+            ))
+    }
+}
+
+/// A symbol exported by a DLL, as listed on the page of the DLL.
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+struct DllSymbolPart {
+    pub symbol: SymbolPart,
+
+    /// The operating systems containing the symbol, in this DLL or in any other.
+    pub oses: Vec<OperatingSystemPart>,
+
+    /// Whether the DLL exports the symbol with different ordinals in different operating systems.
+    pub ordinal_varies: bool,
 }
 
+/// A DLL exporting a symbol in an operating system.
 #[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+struct SymbolDllPart {
+    pub dll: DllPart,
+
+    /// The architecture under which the DLL exports the symbol, if known.
+    pub architecture: Option<Architecture>,
+
+    /// The ordinal with which the DLL exports the symbol, if known.
+    pub ordinal: Option<u64>,
+}
+
+/// The order in which a list of symbols is sorted, as chosen by the `sort` query parameter.
+#[derive(Clone, Copy, Debug, Default, Eq, FromFormField, Hash, Ord, PartialEq, PartialOrd)]
+enum SymbolSort {
+    /// By friendly name, falling back to the raw name.
+    #[default]
+    Friendly,
+
+    /// By raw (possibly mangled) name.
+    Raw,
+
+    /// By DLL, then by ordinal within the DLL.
+    Dll,
+}
+impl SymbolSort {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Friendly => "friendly",
+            Self::Raw => "raw",
+            Self::Dll => "dll",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Self::Friendly => "name",
+            Self::Raw => "raw name",
+            Self::Dll => "DLL and ordinal",
+        }
+    }
+
+    pub fn choices(&self) -> [Self; 3] {
+        [Self::Friendly, Self::Raw, Self::Dll]
+    }
+
+    /// The query parameters selecting this order, to be preserved across pages.
+    pub fn query_params(&self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            Self::Friendly => &[],
+            Self::Raw => &[("sort", "raw")],
+            Self::Dll => &[("sort", "dll")],
+        }
+    }
+
+    /// The key sorting the symbols of an operating system (along with their DLLs) in this order.
+    ///
+    /// `NULL`s are sorted first unless preceded by an `IS NULL` expression; symbols with the same
+    /// name in the same DLL are told apart by their ID.
+    pub fn os_symbol_key(&self) -> SortKey {
+        match self {
+            Self::Friendly => SortKey(&[
+                "COALESCE(sym.friendly_name, sym.raw_name) IS NULL",
+                "COALESCE(sym.friendly_name, sym.raw_name, '')",
+                "COALESCE(sym.dll_name, '')",
+                "COALESCE(sym.ordinal, -1)",
+                "dll.path",
+                "sym.sym_id",
+            ]),
+            Self::Raw => SortKey(&[
+                "sym.raw_name IS NULL",
+                "COALESCE(sym.raw_name, '')",
+                "sym.friendly_name IS NULL",
+                "COALESCE(sym.friendly_name, '')",
+                "COALESCE(sym.dll_name, '')",
+                "COALESCE(sym.ordinal, -1)",
+                "dll.path",
+                "sym.sym_id",
+            ]),
+            Self::Dll => SortKey(&[
+                "dll.path",
+                "sdo.ordinal IS NULL",
+                "COALESCE(sdo.ordinal, -1)",
+                "sym.raw_name IS NULL",
+                "COALESCE(sym.raw_name, '')",
+                "COALESCE(sym.dll_name, '')",
+                "COALESCE(sym.ordinal, -1)",
+                "sym.sym_id",
+            ]),
+        }
+    }
+}
+
+/// The key sorting the named symbols on the alphabetical list pages.
+const NAMED_SYMBOL_KEY: SortKey = SortKey(&["raw_name"]);
+
+/// A processor architecture under which a symbol may be exported, as chosen by the `arch` query
+/// parameter.
+///
+/// These are the architectures accepted by whload.
+#[derive(Clone, Copy, Debug, Eq, FromFormField, Hash, Ord, PartialEq, PartialOrd)]
+enum Architecture {
+    X86,
+    X64,
+    Arm,
+    Arm64,
+    Ia64,
+    Alpha,
+    Mips,
+    Ppc,
+}
+impl Architecture {
+    pub const ALL: [Self; 8] = [
+        Self::X86, Self::X64, Self::Arm, Self::Arm64, Self::Ia64, Self::Alpha, Self::Mips, Self::Ppc,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::X86 => "x86",
+            Self::X64 => "x64",
+            Self::Arm => "arm",
+            Self::Arm64 => "arm64",
+            Self::Ia64 => "ia64",
+            Self::Alpha => "alpha",
+            Self::Mips => "mips",
+            Self::Ppc => "ppc",
+        }
+    }
+
+    /// Finds the architecture with the given name as stored in the database.
+    pub fn from_db_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|arch| arch.as_str() == name)
+    }
+
+    /// The query parameters selecting this architecture, to be preserved across pages.
+    pub fn query_params(&self) -> [(&'static str, &'static str); 1] {
+        [("arch", self.as_str())]
+    }
+}
+
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd, JsonSchema, Serialize)]
+#[serde(tag = "kind")]
 enum SymbolPart {
+    #[serde(rename = "named")]
     Named {
         raw_name: String,
         friendly_name: Option<String>,
         is_meta_func: bool,
+        deprecated: bool,
     },
+    #[serde(rename = "ordinal")]
     DllOrdinal {
         dll_name: String,
         ordinal: u64,
         friendly_name: Option<String>, // `"<dll_name>#<ordinal>"` if not overridden
         is_meta_func: bool,
+        deprecated: bool,
     },
 }
 impl SymbolPart {
@@ -192,6 +807,47 @@ impl SymbolPart {
         }
     }
 
+    /// The name identifying this symbol to a linker: the raw (possibly mangled) name of a named
+    /// symbol or `"<dll_name>#<ordinal>"` for an ordinal-only symbol.
+    pub fn raw_identity(&self) -> String {
+        match self {
+            Self::Named { raw_name, .. }
+                => raw_name.clone(),
+            Self::DllOrdinal { dll_name, ordinal, .. }
+                => format!("{}#{}", dll_name, ordinal),
+        }
+    }
+
+    /// The URL of a Microsoft Learn search for this symbol, if its name looks like a plain
+    /// (unmangled) identifier.
+    ///
+    /// Ordinal-only symbols and symbols with mangled or demangled C++ names have no such URL.
+    pub fn learn_search_url(&self) -> Option<String> {
+        let Self::Named { raw_name, friendly_name, .. } = self
+            else { return None };
+        let name = friendly_name.as_deref().unwrap_or(raw_name);
+        if !is_plain_identifier(name) {
+            return None;
+        }
+        Some(format!("{}{}", LEARN_SEARCH_URL_PREFIX, name))
+    }
+
+    /// The path of this symbol's page, relative to the root of the site.
+    pub fn url_path(&self) -> String {
+        match self {
+            Self::Named { raw_name, .. }
+                => format!(
+                    "symbol/{}",
+                    utf8_percent_encode(raw_name, URL_UNRESERVED),
+                ),
+            Self::DllOrdinal { dll_name, ordinal, .. }
+                => format!(
+                    "symbol/dll/{}/ordinal/{}",
+                    utf8_percent_encode(dll_name, URL_UNRESERVED), ordinal,
+                ),
+        }
+    }
+
     pub fn is_meta_func(&self) -> bool {
         match self {
             Self::Named { is_meta_func, .. }
@@ -201,6 +857,15 @@ impl SymbolPart {
         }
     }
 
+    pub fn is_deprecated(&self) -> bool {
+        match self {
+            Self::Named { deprecated, .. }
+                => *deprecated,
+            Self::DllOrdinal { deprecated, .. }
+                => *deprecated,
+        }
+    }
+
     pub fn dll_pair(&self) -> Option<(&str, u64)> {
         match self {
             Self::Named { .. }
@@ -211,39 +876,54 @@ impl SymbolPart {
     }
 
     pub fn try_from_row(field_offset: usize, row: &Row<'_>) -> Result<Self, rusqlite::Error> {
+        Self::try_opt_from_row(field_offset, row)?
+            .ok_or_else(|| rusqlite::Error::InvalidColumnType(
+                field_offset,
+                "raw_name".to_owned(),
+                rusqlite::types::Type::Null,
+            ))
+    }
+
+    /// Reads a symbol from the row, returning `None` if it is neither named nor has an ordinal.
+    pub fn try_opt_from_row(field_offset: usize, row: &Row<'_>) -> Result<Option<Self>, rusqlite::Error> {
         let raw_name: Option<String> = row.get(field_offset + 0)?;
         let friendly_name: Option<String> = row.get(field_offset + 1)?;
         let dll_name: Option<String> = row.get(field_offset + 2)?;
         let ordinal: Option<u64> = row.get(field_offset + 3)?;
         let is_meta_func: bool = row.get(field_offset + 4)?;
+        let deprecated: bool = row.get(field_offset + 5)?;
 
         let sym_part = if let Some(rn) = raw_name {
             SymbolPart::Named {
                 raw_name: rn,
                 friendly_name,
                 is_meta_func,
+                deprecated,
             }
-        } else if let Some(dn) = dll_name {
+        } else if let (Some(dn), Some(ord)) = (dll_name, ordinal) {
             SymbolPart::DllOrdinal {
                 dll_name: dn,
-                ordinal: ordinal.unwrap(),
+                ordinal: ord,
                 friendly_name,
                 is_meta_func,
+                deprecated,
             }
         } else {
-            panic!("symbol that is neither named nor ordinal");
+            return Ok(None);
         };
-        Ok(sym_part)
+        Ok(Some(sym_part))
     }
 
     pub fn try_named_from_row(field_offset: usize, row: &Row<'_>) -> Result<Self, rusqlite::Error> {
         let raw_name: String = row.get(field_offset + 0)?;
         let friendly_name: Option<String> = row.get(field_offset + 1)?;
         let is_meta_func: bool = row.get(field_offset + 2)?;
+        let deprecated: bool = row.get(field_offset + 3)?;
         Ok(SymbolPart::Named {
             raw_name,
             friendly_name,
             is_meta_func,
+            deprecated,
         })
     }
 
@@ -252,497 +932,2457 @@ impl SymbolPart {
         let ordinal: u64 = row.get(field_offset + 1)?;
         let friendly_name: Option<String> = row.get(field_offset + 2)?;
         let is_meta_func: bool = row.get(field_offset + 3)?;
+        let deprecated: bool = row.get(field_offset + 4)?;
         Ok(SymbolPart::DllOrdinal {
             dll_name,
             ordinal,
             friendly_name,
             is_meta_func,
+            deprecated,
         })
     }
 }
 
-#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
-struct OsSymbolPart {
-    pub symbol: SymbolPart,
-    pub dll: DllPart,
+/// An index of the sections of a symbol list, each containing the symbols whose names start with the
+/// same character, so that they can be jumped to from a bar at the top of the list.
+///
+/// Ordinal-only symbols are collected in a section labeled `#`; mangled names start with `?`
+/// anyway.
+#[derive(Clone, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+struct ListIndex {
+    pub sections: Vec<ListSection>,
 }
 
+/// A section of a symbol list, as listed in its [`ListIndex`].
 #[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
-struct DllOsesPart {
-    pub dll: DllPart,
-    pub oses: Vec<OperatingSystemPart>,
+struct ListSection {
+    pub label: char,
+
+    /// The position in the list of the first symbol in this section.
+    pub start: usize,
+}
+impl ListSection {
+    /// The ID of the list entry at which this section starts.
+    pub fn anchor(&self) -> String {
+        format!("section-{}", self.start)
+    }
 }
+impl ListIndex {
+    /// Divides a list of symbols sorted in the given order into sections.
+    ///
+    /// A list sorted by DLL and ordinal is not divided. If a list is not sorted by the labels
+    /// (e.g. because uppercase letters sort before lowercase ones), only the first symbol with each
+    /// label starts a section.
+    pub fn from_symbols<'a, I: Iterator<Item = &'a SymbolPart>>(symbols: I, sort: SymbolSort) -> Self {
+        let mut sections = Vec::new();
+        if sort == SymbolSort::Dll {
+            return Self { sections };
+        }
 
+        let mut seen_labels = BTreeSet::new();
+        for (position, symbol) in symbols.enumerate() {
+            let name_opt = match sort {
+                SymbolSort::Friendly => symbol.friendly_name().or(symbol.raw_name()),
+                SymbolSort::Raw|SymbolSort::Dll => symbol.raw_name(),
+            };
+            let label = match name_opt {
+                Some(name) => name.chars().next().unwrap_or('#'),
+                None => '#',
+            };
+            if seen_labels.insert(label) {
+                sections.push(ListSection { label, start: position });
+            }
+        }
+        Self { sections }
+    }
 
-fn connect_to_database() -> Option<Connection> {
-    let conn_res = Connection::open_with_flags(
-        "winapi.sqlite3",
-        OpenFlags::SQLITE_OPEN_READ_ONLY
-            | OpenFlags::SQLITE_OPEN_EXRESCODE
-            | OpenFlags::SQLITE_OPEN_NO_MUTEX,
-    );
-    match conn_res {
-        Ok(c) => {
-            // we don't really care as long as the connection is read-only,
-            // but should we ever implement writing features...
-            c.pragma_update(None, "foreign_keys", true)
-                .expect("failed to enable foreign-key enforcement");
-            Some(c)
-        },
-        Err(e) => {
-            error!("failed to connect to database: {}", e);
-            None
-        },
+    /// Whether the list is divided into enough sections to warrant showing the index.
+    pub fn is_worth_showing(&self) -> bool {
+        self.sections.len() > 1
     }
-}
 
-fn prepare<'c>(db: &'c Connection, query: &str) -> Option<Statement<'c>> {
-    match db.prepare(query) {
-        Ok(s) => Some(s),
-        Err(e) => {
-            error!("failed to prepare statement for query {:?}: {}", query, e);
-            None
-        },
+    /// The anchor of the section starting at the given position in the list, if any.
+    pub fn anchor_at(&self, position: usize) -> Option<String> {
+        self.sections
+            .binary_search_by_key(&position, |section| section.start)
+            .ok()
+            .map(|i| self.sections[i].anchor())
     }
 }
 
-fn query_database<
-    T,
-    P: Params,
-    F: FnMut(&Row<'_>) -> Result<T, rusqlite::Error>,
->(statement: &mut Statement<'_>, params: P, transform_row: F) -> Option<Vec<T>> {
-    let query_debug = format!("{:?}", statement);
-    let rows = match statement.query_map(params, transform_row) {
-        Ok(r) => r,
-        Err(e) => {
-            error!("failed to run query {}: {}", query_debug, e);
-            return None;
-        },
-    };
-    let mut finished_rows: Vec<T> = Vec::new();
-    for row_res in rows {
-        let row = match row_res {
-            Ok(r) => r,
-            Err(e) => {
-                error!("failed to obtain row at index {}: {}", finished_rows.len(), e);
-                return None;
-            },
-        };
-        finished_rows.push(row);
-    }
-    Some(finished_rows)
+/// A symbol and the operating systems containing it, as returned by the JSON API.
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd, JsonSchema, Serialize)]
+struct SymbolApiPart {
+    pub raw_name: Option<String>,
+    pub friendly_name: Option<String>,
+    pub is_meta_func: bool,
+    pub deprecated: bool,
+    pub operating_systems: Vec<OsDllsApiPart>,
 }
 
-fn prepare_and_query_database<
-    T,
-    P: Params,
-    F: FnMut(&Row<'_>) -> Result<T, rusqlite::Error>,
->(db: &Connection, query: &str, params: P, transform_row: F) -> Option<Vec<T>> {
-    let mut statement = prepare(db, query)?;
-    query_database(&mut statement, params, transform_row)
+/// A symbol in an operating system and the DLL containing it, as returned by the JSON API.
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd, JsonSchema, Serialize)]
+struct OsSymbolApiPart {
+    #[serde(flatten)]
+    pub symbol: SymbolPart,
+    pub dll_path: String,
+    pub secondary_platform: bool,
 }
 
-fn check_database_existence<P: Params>(db: &Connection, query: &str, params: P) -> Option<bool> {
-    let mut statement = match db.prepare(query) {
-        Ok(s) => s,
-        Err(e) => {
-            error!("failed to prepare query {:?}: {}", query, e);
-            return None;
-        },
-    };
-    let rows = match statement.query_map(params, |_| Ok(())) {
-        Ok(r) => r,
-        Err(e) => {
-            error!("failed to run query {:?}: {}", query, e);
-            return None;
-        },
-    };
-    let mut has_row = false;
-    for row_res in rows {
-        match row_res {
-            Ok(_) => {
-                has_row = true;
-                break;
-            },
-            Err(e) => {
-                error!("failed to obtain row: {}", e);
-                return None;
-            },
-        }
-    }
-    Some(has_row)
+/// An operating system and the DLLs in which it contains a symbol, as returned by the JSON API.
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd, JsonSchema, Serialize)]
+struct OsDllsApiPart {
+    pub os_short_name: String,
+    pub os_long_name: String,
+    pub dlls: Vec<DllPart>,
 }
 
-fn response_500() -> Response<'static> {
-    const BODY: &str = "internal server error";
-    Response::build()
-        .status(Status::InternalServerError)
-        .header(ContentType::Text)
-        .sized_body(BODY.len(), Cursor::new(BODY))
-        .finalize()
+/// A symbol removed or added between two operating systems and the DLLs containing it in the
+/// operating system which has it, as returned by the JSON API.
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd, JsonSchema, Serialize)]
+struct CompareOsSymbolApiPart {
+    #[serde(flatten)]
+    pub symbol: SymbolPart,
+    pub dlls: Vec<DllPart>,
+}
+
+/// The numbers of symbols removed and added between two operating systems, as returned by the JSON
+/// API.
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd, JsonSchema, Serialize)]
+struct CompareOsCountApiPart {
+    pub removed: u64,
+    pub added: u64,
+}
+
+/// The names of the symbols starting with a prefix, as returned by the JSON API.
+///
+/// Each symbol is named by its friendly name or, if it has none, by its raw name.
+#[derive(Clone, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd, JsonSchema, Serialize)]
+#[serde(transparent)]
+struct AutocompleteApiPart(pub Vec<String>);
+
+/// The differences between the symbols of two operating systems, as returned by the JSON API.
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd, JsonSchema, Serialize)]
+struct CompareOsApiPart {
+    pub old_os_short_name: String,
+    pub new_os_short_name: String,
+    pub merge_editions: bool,
+    pub removed_dlls: Vec<String>,
+    pub added_dlls: Vec<String>,
+    pub removed_symbols: Vec<CompareOsSymbolApiPart>,
+    pub added_symbols: Vec<CompareOsSymbolApiPart>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub common_symbols: Option<Vec<SymbolPart>>,
+}
+
+/// A page that can also be represented as JSON, as returned by the JSON API.
+trait ApiPage {
+    type ApiPart: Serialize;
+
+    /// Converts the page into its JSON representation.
+    fn into_api_part(self) -> Self::ApiPart;
+
+    /// Explains what is wrong with the request if the page is rendered as a bad request.
+    fn bad_request_message(&self) -> &'static str {
+        "bad request"
+    }
+}
+impl ApiPage for SymbolTemplate {
+    type ApiPart = SymbolApiPart;
+
+    fn into_api_part(self) -> Self::ApiPart {
+        symbol_api_part(&self.symbol, self.os_dlls)
+    }
+}
+impl ApiPage for CompareOsTemplate {
+    type ApiPart = CompareOsApiPart;
+
+    fn into_api_part(self) -> Self::ApiPart {
+        CompareOsApiPart {
+            old_os_short_name: self.old_os.short_name,
+            new_os_short_name: self.new_os.short_name,
+            merge_editions: self.merge_editions,
+            removed_dlls: self.removed_dlls,
+            added_dlls: self.added_dlls,
+            removed_symbols: self.removed_symbols
+                .into_iter()
+                .map(|(symbol, dlls)| CompareOsSymbolApiPart { symbol, dlls })
+                .collect(),
+            added_symbols: self.added_symbols
+                .into_iter()
+                .map(|(symbol, dlls)| CompareOsSymbolApiPart { symbol, dlls })
+                .collect(),
+            common_symbols: self.common_symbols,
+        }
+    }
+
+    fn bad_request_message(&self) -> &'static str {
+        self.hint.unwrap_or("bad request")
+    }
+}
+
+/// Assembles the JSON representation of a symbol from the operating systems and DLLs containing
+/// it.
+fn symbol_api_part(symbol: &SymbolPart, os_dlls: Vec<(OperatingSystemPart, Vec<SymbolDllPart>)>) -> SymbolApiPart {
+    let operating_systems = os_dlls
+        .into_iter()
+        .map(|(os, dlls)| OsDllsApiPart {
+            os_short_name: os.short_name,
+            os_long_name: os.long_name,
+            dlls: dlls
+                .into_iter()
+                .map(|symbol_dll| symbol_dll.dll)
+                .collect(),
+        })
+        .collect();
+    SymbolApiPart {
+        raw_name: symbol.raw_name().map(|rn| rn.to_owned()),
+        friendly_name: symbol.friendly_name().map(|f| f.to_owned()),
+        is_meta_func: symbol.is_meta_func(),
+        deprecated: symbol.is_deprecated(),
+        operating_systems,
+    }
+}
+
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd, JsonSchema, Serialize)]
+struct StatsPart {
+    pub schema_version: i64,
+    pub operating_systems: u64,
+    pub dlls: u64,
+    pub named_symbols: u64,
+    pub ordinal_symbols: u64,
+    pub relationships: u64,
+    pub earliest_release_date: Option<String>,
+    pub latest_release_date: Option<String>,
+}
+
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+struct OsSymbolPart {
+    pub symbol: SymbolPart,
+    pub dll: DllPart,
+}
+
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+struct OsSymbolCountPart {
+    pub os: OperatingSystemPart,
+    pub symbol_count: u64,
+}
+
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+struct DllSymbolCountPart {
+    pub dll: DllPart,
+    pub symbol_count: u64,
+}
+
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+struct DllOsesPart {
+    pub dll: DllPart,
+    pub oses: Vec<OperatingSystemPart>,
+}
+
+
+/// Configuration specific to this application, read from `Rocket.toml` and `ROCKET_` environment
+/// variables along with Rocket's own configuration.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+struct WebConfig {
+    /// The addresses of reverse proxies whose `X-Forwarded-For`, `X-Forwarded-Proto` and
+    /// `X-Forwarded-Host` headers are trusted.
+    ///
+    /// If empty (the default), the address of the peer is always taken as the client address and
+    /// absolute links are built from the `Host` header.
+    #[serde(default)]
+    pub trusted_proxies: Vec<IpAddr>,
+
+    /// The path to the SQLite database containing the API information.
+    ///
+    /// Defaults to `winapi.sqlite3` in the working directory.
+    #[serde(default = "default_database_path")]
+    pub database_path: PathBuf,
+
+    /// The path to the directory containing the static assets: the operating system icons in the
+    /// subdirectory `os-icons` and the file `favicon.ico`.
+    ///
+    /// Defaults to `static` in the working directory.
+    #[serde(default = "default_static_path")]
+    pub static_path: PathBuf,
+
+    /// The maximum number of rows of the symbol lists of an operating system or DLL, the
+    /// alphabetical symbol lists and the search results.
+    ///
+    /// A request may lower the limit using the `limit` query parameter but never raise it beyond
+    /// this value. Defaults to 10000.
+    #[serde(default = "default_max_rows")]
+    pub max_rows: u64,
+
+    /// Whether the web server refuses to launch if the database is missing or unusable.
+    ///
+    /// If false (the default), the problem is only logged at startup, and pages requiring the
+    /// database fail until it becomes available.
+    #[serde(default)]
+    pub require_database: bool,
+}
+
+fn default_database_path() -> PathBuf {
+    PathBuf::from(DEFAULT_DATABASE_PATH)
+}
+
+fn default_static_path() -> PathBuf {
+    PathBuf::from(DEFAULT_STATIC_PATH)
+}
+
+fn default_max_rows() -> u64 {
+    DEFAULT_MAX_ROWS
+}
+
+/// Caches the database statistics, since they are expensive to compute.
+///
+/// The statistics are keyed by the time at which whload last changed the data (see
+/// [`query_reindexed_at`]); they are recomputed once the database changes.
+#[derive(Debug, Default)]
+struct StatsCache {
+    pub reindexed_at_and_stats: Mutex<Option<(i64, StatsPart)>>,
+}
+
+/// Caches the operating systems by short name, since nearly every page needs to look one up.
+///
+/// Like [`StatsCache`], the operating systems are keyed by the time at which whload last changed
+/// the data (see [`query_reindexed_at`]); they are reloaded once the database changes. Operating
+/// systems not found in the cache are still looked up in the database.
+///
+/// Data which has never been reindexed is cached too, with `None` as its key.
+#[derive(Debug, Default)]
+struct OsCache {
+    pub reindexed_at_and_oses: Mutex<Option<(Option<i64>, OsesByShortName)>>,
+}
+impl OsCache {
+    /// Finds the operating system with the given short name in the cache, reloading all operating
+    /// systems from the database first if the data has changed since they were loaded.
+    ///
+    /// If the cache cannot be loaded, nothing is found, and the caller looks up the operating
+    /// system in the database.
+    pub fn get(&self, db: &Connection, short_name: &str) -> Option<IdentifiedOs> {
+        let reindexed_at = query_reindexed_at(db);
+        let mut cache_guard = self.reindexed_at_and_oses
+            .lock().expect("operating system cache lock poisoned");
+        let is_current = cache_guard.as_ref()
+            .is_some_and(|(cached_reindexed_at, _oses)| *cached_reindexed_at == reindexed_at);
+        if !is_current {
+            *cache_guard = query_all_oses_by_short_name(db)
+                .map(|oses| (reindexed_at, oses));
+        }
+        let (_reindexed_at, oses) = cache_guard.as_ref()?;
+        oses.get(short_name).cloned()
+    }
+
+    /// The number of cached operating systems, or `None` if the cache has not been loaded.
+    pub fn len(&self) -> Option<usize> {
+        let cache_guard = self.reindexed_at_and_oses
+            .lock().expect("operating system cache lock poisoned");
+        cache_guard.as_ref()
+            .map(|(_reindexed_at, oses)| oses.len())
+    }
+}
+
+/// Queries all operating systems, keyed by short name.
+fn query_all_oses_by_short_name(db: &Connection) -> Option<OsesByShortName> {
+    let os_rows = prepare_and_query_database(
+        db,
+        "
+            SELECT
+                os_id,
+                short_name,
+                COALESCE(long_name, short_name),
+                has_icon,
+                build_number
+            FROM
+                operating_systems
+        ",
+        [],
+        |row| {
+            let os_id: i64 = row.get(0)?;
+            let os_part = OperatingSystemPart::try_from_row(1, row)?;
+            Ok((os_id, os_part))
+        },
+    )?;
+    let by_short_name = os_rows
+        .into_iter()
+        .map(|(os_id, os_part)| (os_part.short_name.clone(), (os_id, os_part)))
+        .collect();
+    Some(by_short_name)
+}
+
+
+/// Creates the pool of database connections.
+///
+/// Connections are only opened once they are needed, so the web server can start even if the
+/// database is not (yet) available. Obtaining a connection fails after
+/// [`DATABASE_CONNECTION_TIMEOUT`].
+fn create_database_pool(database_path: &Path) -> DbPool {
+    let manager = SqliteConnectionManager::file(database_path)
+        .with_flags(
+            OpenFlags::SQLITE_OPEN_READ_ONLY
+                | OpenFlags::SQLITE_OPEN_EXRESCODE
+                | OpenFlags::SQLITE_OPEN_NO_MUTEX
+        )
+        .with_init(|c| {
+            c.set_prepared_statement_cache_capacity(STATEMENT_CACHE_CAPACITY);
+
+            // we don't really care as long as the connection is read-only,
+            // but should we ever implement writing features...
+            c.pragma_update(None, "foreign_keys", true)
+        });
+    r2d2::Pool::builder()
+        .min_idle(Some(0))
+        .connection_timeout(DATABASE_CONNECTION_TIMEOUT)
+        .build_unchecked(manager)
+}
+
+/// A reason why the database cannot be used.
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+enum DatabaseProblem {
+    /// The database file does not exist.
+    Missing,
+
+    /// The database file exists but no connection could be established.
+    ConnectionFailed,
+
+    /// The schema version could not be queried.
+    QueryFailed,
+
+    /// The database has a schema version other than [`EXPECTED_SCHEMA_VERSION`].
+    UnexpectedSchemaVersion(i64),
+}
+impl DatabaseProblem {
+    /// A terse description of the problem, suitable as the body of a health check response.
+    pub fn short_description(&self) -> &'static str {
+        match self {
+            Self::Missing => "database missing",
+            Self::ConnectionFailed => "database unavailable",
+            Self::QueryFailed => "database query failed",
+            Self::UnexpectedSchemaVersion(_) => "database schema version unexpected",
+        }
+    }
+}
+impl fmt::Display for DatabaseProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Missing => write!(f, "the database file does not exist"),
+            Self::ConnectionFailed => write!(f, "failed to connect to the database"),
+            Self::QueryFailed => write!(f, "failed to query the schema version of the database"),
+            Self::UnexpectedSchemaVersion(version) => write!(
+                f, "the database has schema version {} but version {} is expected",
+                version, EXPECTED_SCHEMA_VERSION,
+            ),
+        }
+    }
+}
+
+/// Checks whether the database exists, can be connected to and has the expected schema version.
+fn check_database(database_path: &Path, db_pool: &DbPool) -> Result<(), DatabaseProblem> {
+    if !database_path.exists() {
+        return Err(DatabaseProblem::Missing);
+    }
+    let db = match db_pool.get_timeout(DATABASE_CHECK_TIMEOUT) {
+        Ok(c) => c,
+        Err(e) => {
+            error!("failed to connect to database: {}", e);
+            return Err(DatabaseProblem::ConnectionFailed);
+        },
+    };
+    let version: i64 = match db.query_row("SELECT ver FROM schema_version", [], |row| row.get(0)) {
+        Ok(v) => v,
+        Err(e) => {
+            error!("failed to query schema version: {}", e);
+            return Err(DatabaseProblem::QueryFailed);
+        },
+    };
+    if version != EXPECTED_SCHEMA_VERSION {
+        return Err(DatabaseProblem::UnexpectedSchemaVersion(version));
+    }
+    Ok(())
+}
+
+fn connect_to_database(db_pool: &DbPool) -> Option<PooledConnection<SqliteConnectionManager>> {
+    match db_pool.get() {
+        Ok(c) => Some(c),
+        Err(e) => {
+            error!("failed to connect to database: {}", e);
+            None
+        },
+    }
+}
+
+/// Obtains the time at which whload last changed the data, in milliseconds since the Unix epoch.
+///
+/// Unlike the modification time of the database file, this also reflects changes which are still
+/// in the write-ahead log. Returns `None` if the data has never been changed by a version of whload
+/// which records this time.
+fn query_reindexed_at(db: &Connection) -> Option<i64> {
+    match db.query_row("SELECT reindexed_at FROM schema_version", [], |row| row.get(0)) {
+        Ok(reindexed_at) => reindexed_at,
+        Err(e) => {
+            error!("failed to query the time of the last change: {}", e);
+            None
+        },
+    }
+}
+
+fn prepare<'c>(db: &'c Connection, query: &str) -> Option<CachedStatement<'c>> {
+    match db.prepare_cached(query) {
+        Ok(s) => Some(s),
+        Err(e) => {
+            error!("failed to prepare statement for query {:?}: {}", query, e);
+            None
+        },
+    }
+}
+
+/// Abbreviates the SQL text of a query for logging, collapsing all whitespace and truncating it to
+/// [`MAX_LOGGED_QUERY_CHARS`] characters.
+fn abbreviate_query(query: &str) -> String {
+    let mut abbreviated = query.split_whitespace().collect::<Vec<_>>().join(" ");
+    if let Some((cut_index, _)) = abbreviated.char_indices().nth(MAX_LOGGED_QUERY_CHARS) {
+        abbreviated.truncate(cut_index);
+        abbreviated.push('…');
+    }
+    abbreviated
+}
+
+fn query_database<
+    T,
+    P: Params,
+    F: FnMut(&Row<'_>) -> Result<T, rusqlite::Error>,
+>(statement: &mut Statement<'_>, params: P, transform_row: F) -> Option<Vec<T>> {
+    let span = debug_span!("query");
+    let _entered = span.enter();
+    let start_time = Instant::now();
+
+    let query_debug = format!("{:?}", statement);
+    let rows = match statement.query_map(params, transform_row) {
+        Ok(r) => r,
+        Err(e) => {
+            error!("failed to run query {}: {}", query_debug, e);
+            return None;
+        },
+    };
+    let mut finished_rows: Vec<T> = Vec::new();
+    for row_res in rows {
+        let row = match row_res {
+            Ok(r) => r,
+            Err(e) => {
+                error!("failed to obtain row at index {}: {}", finished_rows.len(), e);
+                return None;
+            },
+        };
+        finished_rows.push(row);
+    }
+
+    // the parameters remain bound after the query has finished, so they can be shown too
+    debug!(
+        "{} row(s) in {:?}: {}",
+        finished_rows.len(),
+        start_time.elapsed(),
+        abbreviate_query(&statement.expanded_sql().unwrap_or_default()),
+    );
+    Some(finished_rows)
+}
+
+fn prepare_and_query_database<
+    T,
+    P: Params,
+    F: FnMut(&Row<'_>) -> Result<T, rusqlite::Error>,
+>(db: &Connection, query: &str, params: P, transform_row: F) -> Option<Vec<T>> {
+    let mut statement = prepare(db, query)?;
+    query_database(&mut statement, params, transform_row)
+}
+
+/// Escapes characters with a special meaning in Markdown.
+fn escape_markdown(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if "\\`*_[]<>|~&".contains(c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Escapes characters with a special meaning in XML.
+fn escape_xml(text: &str) -> String {
+    text
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+/// Escapes the wildcard characters of SQL `LIKE` patterns, with a backslash as the escape character.
+fn escape_like(text: &str) -> String {
+    text
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
+}
+
+/// The number of rows a list may contain: the limit requested by the client (or `default` if none
+/// was requested), but never more than the configured maximum.
+fn row_limit(requested: Option<u64>, default: u64, max_rows: u64) -> u64 {
+    requested
+        .unwrap_or(default)
+        .min(max_rows)
+}
+
+/// Truncates rows fetched with a `LIMIT` of one more than the row limit to the row limit, returning
+/// whether any rows were dropped.
+fn exceeds_row_limit<T>(rows: &mut Vec<T>, row_count: u64) -> bool {
+    let row_count = usize::try_from(row_count).unwrap_or(usize::MAX);
+    if rows.len() > row_count {
+        rows.truncate(row_count);
+        true
+    } else {
+        false
+    }
+}
+
+/// Returns whether the query is for mangled names but too short to be run.
+fn is_mangled_query_too_short(query: &str, min_chars: usize) -> bool {
+    query.starts_with('?') && query.chars().count() < min_chars
+}
+
+/// Renders an error page, falling back to plain text if even that fails.
+fn render_error_page<T: Template + Debug>(template: T, status: Status, fallback_body: &'static str) -> Response<'static> {
+    let (content_type, body) = match template.render() {
+        Ok(r) => (ContentType::HTML, r),
+        Err(e) => {
+            // don't try to render the error template; it might fail again
+            error!("failed to render error template {:?}: {}", template, e);
+            (ContentType::Text, fallback_body.to_owned())
+        },
+    };
+    Response::build()
+        .status(status)
+        .header(content_type)
+        .sized_body(body.len(), Cursor::new(body))
+        .finalize()
+}
+
+fn response_500(request: &Request<'_>) -> Response<'static> {
+    let template = ErrorTemplate {
+        path_to_root: path_to_root_from(request.uri().path().as_str()),
+    };
+    render_error_page(template, Status::InternalServerError, "internal server error")
+}
+
+fn response_404(request: &Request<'_>) -> Response<'static> {
+    const BODY: &str = "not found";
+    const EMPTY_DATABASE_BODY: &str = "not found (no data has been loaded into the database yet)";
+
+    // if nothing has been loaded, tell the user why they can't find anything
+    // (as of the last time the operating systems were cached; a 404 never waits for the database)
+    let database_is_empty = request.rocket().state::<OsCache>()
+        .is_some_and(|os_cache| os_cache.len() == Some(0));
+    let fallback_body = if database_is_empty { EMPTY_DATABASE_BODY } else { BODY };
+
+    let path = request.uri().path();
+    let template = NotFoundTemplate {
+        path_to_root: path_to_root_from(path.as_str()),
+        path: path.percent_decode_lossy().into_owned(),
+        database_is_empty,
+    };
+    render_error_page(template, Status::NotFound, fallback_body)
+}
+
+#[rocket::catch(404)]
+fn not_found_catcher() -> TemplateResponder<NotFoundTemplate> {
+    TemplateResponder::NotFound
+}
+
+#[rocket::catch(500)]
+fn internal_error_catcher() -> TemplateResponder<ErrorTemplate> {
+    TemplateResponder::Failure
 }
 
 enum TemplateResponder<T: Template + Debug> {
     Template(T),
+    BadRequest(T), // the template explains what is wrong with the request
+    NotFoundExplained(T), // the template explains what was not found
     NotFound,
     Failure,
 }
+impl<T: Template + Debug> TemplateResponder<T> {
+    fn render_response(template: T, status: Status, request: &Request<'_>) -> Response<'static> {
+        let rendered = match template.render() {
+            Ok(r) => r,
+            Err(e) => {
+                error!("failed to render template {:?}: {}", template, e);
+                return response_500(request);
+            },
+        };
+        Response::build()
+            .status(status)
+            .header(ContentType::HTML)
+            .sized_body(rendered.len(), Cursor::new(rendered))
+            .finalize()
+    }
+}
 impl<'r, 'o : 'r, T: Template + Debug> Responder<'r, 'o> for TemplateResponder<T> {
-    fn respond_to(self, _request: &'r Request<'_>) -> rocket::response::Result<'o> {
+    fn respond_to(self, request: &'r Request<'_>) -> rocket::response::Result<'o> {
+        match self {
+            Self::Failure => Ok(response_500(request)),
+            Self::NotFound => Ok(response_404(request)),
+            Self::Template(template) => Ok(Self::render_response(template, Status::Ok, request)),
+            Self::BadRequest(template) => Ok(Self::render_response(template, Status::BadRequest, request)),
+            Self::NotFoundExplained(template) => Ok(Self::render_response(template, Status::NotFound, request)),
+        }
+    }
+}
+
+/// Sends the bytes written to it to a channel in chunks of [`STREAMED_CHUNK_SIZE`] bytes.
+struct ChunkSender {
+    sender: mpsc::Sender<Vec<u8>>,
+    buffer: Vec<u8>,
+}
+impl ChunkSender {
+    fn send_buffer(&mut self) -> std::io::Result<()> {
+        if self.buffer.is_empty() {
+            return Ok(());
+        }
+        let chunk = std::mem::replace(&mut self.buffer, Vec::with_capacity(STREAMED_CHUNK_SIZE));
+        self.sender.blocking_send(chunk)
+            .map_err(|_| std::io::Error::new(std::io::ErrorKind::BrokenPipe, "response stream closed"))
+    }
+}
+impl std::io::Write for ChunkSender {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.buffer.extend_from_slice(buf);
+        if self.buffer.len() >= STREAMED_CHUNK_SIZE {
+            self.send_buffer()?;
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.send_buffer()
+    }
+}
+
+/// Logs an error that cut a streamed response short.
+///
+/// The response stream is closed if the client has gone away, which is expected to happen now and
+/// then and is therefore only logged at debug level.
+fn log_streaming_error(what: &str, error: &std::io::Error) {
+    if error.kind() == std::io::ErrorKind::BrokenPipe {
+        debug!("stopped streaming {}: {}", what, error);
+    } else {
+        error!("failed to stream {}: {}", what, error);
+    }
+}
+
+/// Builds a response whose body is sent to the client in chunks while the given function writes it.
+///
+/// The function runs on a thread on which blocking is allowed; it must send the remainder of the
+/// buffer once it has finished writing.
+fn streamed_response<F: FnOnce(ChunkSender) + Send + 'static>(status: Status, content_type: ContentType, write: F) -> Response<'static> {
+    let (sender, receiver) = mpsc::channel(STREAMED_CHUNK_BACKLOG);
+    rocket::tokio::task::spawn_blocking(move || {
+        write(ChunkSender {
+            sender,
+            buffer: Vec::with_capacity(STREAMED_CHUNK_SIZE),
+        })
+    });
+
+    let chunks = rocket::futures::stream::unfold(receiver, |mut receiver| async move {
+        receiver.recv().await
+            .map(|chunk| (Cursor::new(chunk), receiver))
+    });
+    Response::build()
+        .status(status)
+        .header(content_type)
+        .streamed_body(ReaderStream::from(chunks))
+        .finalize()
+}
+
+/// Responds with a template that is sent to the client in chunks while it is being rendered.
+///
+/// Unlike [`TemplateResponder`], which renders the whole page into memory before sending it, this
+/// keeps the memory required by a response bounded, which matters for pages listing huge numbers of
+/// symbols. The drawback is that the status has already been sent by the time a rendering error
+/// occurs; such a response is cut short instead of being replaced by an error page.
+struct StreamedTemplateResponder<T: Template + Debug>(TemplateResponder<T>);
+impl<T: Template + Debug + Send + 'static> StreamedTemplateResponder<T> {
+    fn stream_response(template: T, status: Status) -> Response<'static> {
+        streamed_response(status, ContentType::HTML, move |mut chunk_sender| {
+            let result = Template::write_into(&template, &mut chunk_sender)
+                .and_then(|()| chunk_sender.send_buffer());
+            if let Err(e) = result {
+                log_streaming_error(std::any::type_name::<T>(), &e);
+            }
+        })
+    }
+}
+impl<'r, 'o : 'r, T: Template + Debug + Send + 'static> Responder<'r, 'o> for StreamedTemplateResponder<T> {
+    fn respond_to(self, request: &'r Request<'_>) -> rocket::response::Result<'o> {
+        match self.0 {
+            TemplateResponder::Template(template) => Ok(Self::stream_response(template, Status::Ok)),
+            TemplateResponder::BadRequest(template) => Ok(Self::stream_response(template, Status::BadRequest)),
+            TemplateResponder::NotFoundExplained(template) => Ok(Self::stream_response(template, Status::NotFound)),
+            other => other.respond_to(request),
+        }
+    }
+}
+
+/// Responds with a plain document in a format other than HTML or JSON.
+#[allow(clippy::large_enum_variant)] // each responder is built once and handed straight to Rocket
+enum TextResponder {
+    Text {
+        content_type: ContentType,
+        body: String,
+        attachment_file_name: Option<String>,
+    },
+    BadRequest(&'static str), // explains what is wrong with the request
+    NotFound,
+    Failure,
+}
+impl<'r, 'o : 'r> Responder<'r, 'o> for TextResponder {
+    fn respond_to(self, request: &'r Request<'_>) -> rocket::response::Result<'o> {
+        match self {
+            Self::Failure => Ok(response_500(request)),
+            Self::NotFound => Ok(response_404(request)),
+            Self::BadRequest(message) => {
+                let response = Response::build()
+                    .status(Status::BadRequest)
+                    .header(ContentType::Plain)
+                    .sized_body(message.len(), Cursor::new(message))
+                    .finalize();
+                Ok(response)
+            },
+            Self::Text { content_type, body, attachment_file_name } => {
+                let mut builder = Response::build();
+                builder
+                    .status(Status::Ok)
+                    .header(content_type);
+                if let Some(file_name) = attachment_file_name {
+                    builder.header(attachment_header(&file_name));
+                }
+                let response = builder
+                    .sized_body(body.len(), Cursor::new(body))
+                    .finalize();
+                Ok(response)
+            },
+        }
+    }
+}
+
+/// Responds with the symbols of an operating system as CSV, which are sent to the client while they
+/// are being read from the database.
+#[allow(clippy::large_enum_variant)] // each responder is built once and handed straight to Rocket
+enum OsSymbolsCsvResponder {
+    Csv {
+        db: PooledConnection<SqliteConnectionManager>,
+        os_id: i64,
+        file_name: String,
+    },
+    NotFound,
+    Failure,
+}
+impl<'r, 'o : 'r> Responder<'r, 'o> for OsSymbolsCsvResponder {
+    fn respond_to(self, request: &'r Request<'_>) -> rocket::response::Result<'o> {
         match self {
-            Self::Failure => Ok(response_500()),
-            Self::NotFound => {
-                const BODY: &str = "not found";
+            Self::Failure => Ok(response_500(request)),
+            Self::NotFound => Ok(response_404(request)),
+            Self::Csv { db, os_id, file_name } => {
+                let mut response = streamed_response(Status::Ok, ContentType::CSV, move |mut chunk_sender| {
+                    write_os_symbols_csv(&db, os_id, &mut chunk_sender);
+                });
+                response.set_header(attachment_header(&file_name));
+                Ok(response)
+            },
+        }
+    }
+}
+
+/// Builds the `Content-Disposition` header offering the response as a download with the given file
+/// name.
+///
+/// As recommended by RFC 6266, the file name is given twice: percent-encoded as `filename*` and, for
+/// clients that do not understand that parameter, as `filename` with every character that cannot
+/// safely appear in a quoted string replaced by an underscore.
+fn attachment_header(file_name: &str) -> Header<'static> {
+    let fallback_file_name: String = file_name.chars()
+        .map(|c| if c == ' ' || (c.is_ascii_graphic() && c != '"' && c != '\\') { c } else { '_' })
+        .collect();
+    Header::new(
+        "Content-Disposition",
+        format!(
+            "attachment; filename=\"{}\"; filename*=UTF-8''{}",
+            fallback_file_name,
+            utf8_percent_encode(file_name, URL_UNRESERVED),
+        ),
+    )
+}
+
+/// Responds with the contents of a binary file, such as an image.
+enum BinaryResponder {
+    Binary {
+        content_type: &'static str,
+        body: Vec<u8>,
+    },
+    NotFound,
+    Failure,
+}
+impl BinaryResponder {
+    /// Reads the file at the given path into a response; a file that does not exist is not found.
+    pub fn from_file(path: &Path, content_type: &'static str) -> Self {
+        match std::fs::read(path) {
+            Ok(body) => Self::Binary { content_type, body },
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Self::NotFound,
+            Err(e) => {
+                error!("failed to read {}: {}", path.display(), e);
+                Self::Failure
+            },
+        }
+    }
+}
+impl<'r, 'o : 'r> Responder<'r, 'o> for BinaryResponder {
+    fn respond_to(self, request: &'r Request<'_>) -> rocket::response::Result<'o> {
+        match self {
+            Self::Failure => Ok(response_500(request)),
+            Self::NotFound => Ok(response_404(request)),
+            Self::Binary { content_type, body } => {
                 let response = Response::build()
-                    .status(Status::NotFound)
-                    .header(ContentType::Text)
-                    .sized_body(BODY.len(), Cursor::new(BODY))
+                    .status(Status::Ok)
+                    .raw_header("Content-Type", content_type)
+                    .sized_body(body.len(), Cursor::new(body))
                     .finalize();
                 Ok(response)
             },
-            Self::Template(template) => {
-                let rendered = match template.render() {
-                    Ok(r) => r,
+        }
+    }
+}
+
+enum JsonResponder<T: Serialize> {
+    Json(T),
+    BadRequest(&'static str), // explains what is wrong with the request
+    NotFound,
+    Failure,
+}
+impl<T: Serialize> JsonResponder<T> {
+    /// Responds with the JSON representation of a page instead of rendering it.
+    fn from_template<P: Template + Debug + ApiPage<ApiPart = T>>(template: TemplateResponder<P>) -> Self {
+        match template {
+            TemplateResponder::Template(page) => Self::Json(page.into_api_part()),
+            TemplateResponder::BadRequest(page) => Self::BadRequest(page.bad_request_message()),
+            TemplateResponder::NotFound => Self::NotFound,
+            TemplateResponder::NotFoundExplained(_) => Self::NotFound,
+            TemplateResponder::Failure => Self::Failure,
+        }
+    }
+
+    fn error_response(status: Status, message: &str) -> Response<'static> {
+        let body = serde_json::json!({"error": message}).to_string();
+        Response::build()
+            .status(status)
+            .header(ContentType::JSON)
+            .sized_body(body.len(), Cursor::new(body))
+            .finalize()
+    }
+}
+impl<'r, 'o : 'r, T: Serialize> Responder<'r, 'o> for JsonResponder<T> {
+    fn respond_to(self, _request: &'r Request<'_>) -> rocket::response::Result<'o> {
+        match self {
+            Self::Failure => Ok(Self::error_response(Status::InternalServerError, "internal server error")),
+            Self::NotFound => Ok(Self::error_response(Status::NotFound, "not found")),
+            Self::BadRequest(message) => Ok(Self::error_response(Status::BadRequest, message)),
+            Self::Json(value) => {
+                let serialized = match serde_json::to_string(&value) {
+                    Ok(s) => s,
                     Err(e) => {
-                        error!("failed to render template {:?}: {}", template, e);
-                        return Ok(response_500());
+                        error!("failed to serialize JSON response: {}", e);
+                        return Ok(Self::error_response(Status::InternalServerError, "internal server error"));
                     },
                 };
-                let response = Response::build()
-                    .status(Status::Ok)
-                    .header(ContentType::HTML)
-                    .sized_body(rendered.len(), Cursor::new(rendered))
-                    .finalize();
-                Ok(response)
+                let response = Response::build()
+                    .status(Status::Ok)
+                    .header(ContentType::JSON)
+                    .sized_body(serialized.len(), Cursor::new(serialized))
+                    .finalize();
+                Ok(response)
+            },
+        }
+    }
+}
+
+
+/// Responds with either the HTML or the JSON representation of a page, whichever the client
+/// prefers.
+///
+/// Both representations share the same URL, so the response varies by the `Accept` header.
+enum NegotiatedResponder<R, J: Serialize> {
+    Html(R),
+    Json(JsonResponder<J>),
+}
+impl<T: Template + Debug + ApiPage> NegotiatedResponder<TemplateResponder<T>, T::ApiPart> {
+    fn from_template(format: ResponseFormat, template: TemplateResponder<T>) -> Self {
+        match format {
+            ResponseFormat::Html => Self::Html(template),
+            ResponseFormat::Json => Self::Json(JsonResponder::from_template(template)),
+        }
+    }
+}
+impl<R, J: Serialize> NegotiatedResponder<R, J> {
+    pub fn map_html<S, F: FnOnce(R) -> S>(self, f: F) -> NegotiatedResponder<S, J> {
+        match self {
+            Self::Html(r) => NegotiatedResponder::Html(f(r)),
+            Self::Json(j) => NegotiatedResponder::Json(j),
+        }
+    }
+}
+impl<'r, 'o : 'r, R: Responder<'r, 'o>, J: Serialize> Responder<'r, 'o> for NegotiatedResponder<R, J> {
+    fn respond_to(self, request: &'r Request<'_>) -> rocket::response::Result<'o> {
+        let mut response = match self {
+            Self::Html(html) => html.respond_to(request)?,
+            Self::Json(json) => json.respond_to(request)?,
+        };
+        response.adjoin_header(Header::new("Vary", "Accept"));
+        Ok(response)
+    }
+}
+
+#[rocket::get("/os/<os_name>?<secondary>&<arch>")]
+#[allow(clippy::too_many_arguments)] // Rocket passes each parameter and guard separately
+fn os_page(os_name: &str, secondary: Option<bool>, arch: Option<Architecture>, uri: &Origin<'_>, path_to_root: PathToRoot, freshness: Freshness, route_span: RouteSpan, db_pool: &State<DbPool>, os_cache: &State<OsCache>) -> Conditional<OsPageResponder<TemplateResponder<OsTemplate>>> {
+    let _entered = route_span.enter();
+    freshness.respond(|| {
+        let Some(db) = connect_to_database(db_pool)
+            else { return OsPageResponder::Page(TemplateResponder::Failure) };
+        let os = match resolve_page_os(&db, os_cache, uri, 1, os_name) {
+            Ok(os) => os,
+            Err(responder) => return responder,
+        };
+        OsPageResponder::Page(os_template(&db, os, secondary, arch, path_to_root))
+    })
+}
+
+/// Describes an operating system: its DLLs, its editions and the architectures of its symbols.
+fn os_template(db: &Connection, os: IdentifiedOs, secondary: Option<bool>, arch: Option<Architecture>, path_to_root: PathToRoot) -> TemplateResponder<OsTemplate> {
+    let (os_id, os_part) = os;
+
+    // is it an edition of another operating system?
+    let parent_oses_opt = prepare_and_query_database(
+        db,
+        "
+            SELECT
+                parent.short_name,
+                COALESCE(parent.long_name, parent.short_name),
+                parent.has_icon,
+                parent.build_number
+            FROM
+                operating_systems os
+                INNER JOIN operating_systems parent
+                    ON parent.os_id = os.parent_os_id
+            WHERE
+                os.os_id = ?1
+        ",
+        [os_id],
+        |row| OperatingSystemPart::try_from_row(0, row),
+    );
+    let Some(mut parent_oses) = parent_oses_opt
+        else { return TemplateResponder::Failure };
+    let parent_os = parent_oses.pop();
+
+    // does it have editions?
+    let editions_opt = prepare_and_query_database(
+        db,
+        "
+            SELECT
+                short_name,
+                COALESCE(long_name, short_name),
+                has_icon,
+                build_number
+            FROM
+                operating_systems
+            WHERE
+                parent_os_id = ?1
+            ORDER BY
+                sort_order ASC,
+                2
+        ",
+        [os_id],
+        |row| OperatingSystemPart::try_from_row(0, row),
+    );
+    let Some(editions) = editions_opt
+        else { return TemplateResponder::Failure };
+
+    let Some(architectures) = query_os_architectures(db, os_id)
+        else { return TemplateResponder::Failure };
+
+    // find its DLLs (optionally only those of the primary or of secondary platforms, or of one
+    // architecture) and the number of symbols each of them exports
+    let dlls_opt = prepare_and_query_database(
+        db,
+        "
+            SELECT
+                d.path,
+                d.secondary_platform,
+                COUNT(DISTINCT sdo.sym_id)
+            FROM
+                dlls d
+                INNER JOIN symbol_dll_os sdo
+                    ON sdo.dll_id = d.dll_id
+            WHERE
+                sdo.os_id = ?1
+                AND (?2 IS NULL OR d.secondary_platform = ?2)
+                AND (?3 IS NULL OR sdo.architecture = ?3)
+            GROUP BY
+                d.dll_id
+            ORDER BY
+                1
+        ",
+        (os_id, secondary, arch.map(|a| a.as_str())),
+        |row| {
+            let dll = DllPart::try_from_row(0, row)?;
+            let symbol_count: u64 = row.get(2)?;
+            Ok(DllSymbolCountPart {
+                dll,
+                symbol_count,
+            })
+        },
+    );
+    let Some(dlls) = dlls_opt
+        else { return TemplateResponder::Failure };
+
+    // group them by their leading directory (the query has already sorted them within each group)
+    let mut dll_directories: BTreeMap<String, Vec<DllSymbolCountPart>> = BTreeMap::new();
+    for dll_count in dlls {
+        let directory = match dll_count.dll.path.split_once('\\') {
+            Some((directory, _rest)) => directory.to_owned(),
+            None => ROOT_DLL_DIRECTORY.to_owned(),
+        };
+        dll_directories
+            .entry(directory)
+            .or_default()
+            .push(dll_count);
+    }
+
+    let template = OsTemplate {
+        path_to_root: path_to_root.0,
+        dll_directories,
+        secondary,
+        architecture: arch,
+        architectures,
+        os: os_part,
+        parent_os,
+        editions,
+    };
+    TemplateResponder::Template(template)
+}
+
+#[rocket::get("/os/<os_name>/dll/<dll_name>?<sort>")]
+#[allow(clippy::too_many_arguments)] // Rocket passes each parameter and guard separately
+fn os_dll_page(os_name: &str, dll_name: &str, sort: Option<SymbolSort>, uri: &Origin<'_>, path_to_root: PathToRoot, route_span: RouteSpan, db_pool: &State<DbPool>, os_cache: &State<OsCache>) -> OsPageResponder<StreamedTemplateResponder<OsDllSymbolListTemplate>> {
+    let _entered = route_span.enter();
+    let Some(db) = connect_to_database(db_pool)
+        else { return OsPageResponder::Page(StreamedTemplateResponder(TemplateResponder::Failure)) };
+    let os = match resolve_page_os(&db, os_cache, uri, 1, os_name) {
+        Ok(os) => os,
+        Err(responder) => return responder.map(StreamedTemplateResponder),
+    };
+    OsPageResponder::Page(StreamedTemplateResponder(os_dll_template(&db, os, dll_name, None, sort.unwrap_or_default(), path_to_root)))
+}
+
+#[rocket::get("/os/<os_name>/dll/<dll_name>/ordinals/<lo>/<hi>?<sort>")]
+#[allow(clippy::too_many_arguments)] // Rocket passes each parameter and guard separately
+fn os_dll_ordinal_range_page(os_name: &str, dll_name: &str, lo: u64, hi: u64, sort: Option<SymbolSort>, uri: &Origin<'_>, path_to_root: PathToRoot, route_span: RouteSpan, db_pool: &State<DbPool>, os_cache: &State<OsCache>) -> OsPageResponder<StreamedTemplateResponder<OsDllSymbolListTemplate>> {
+    let _entered = route_span.enter();
+    let Some(db) = connect_to_database(db_pool)
+        else { return OsPageResponder::Page(StreamedTemplateResponder(TemplateResponder::Failure)) };
+    let os = match resolve_page_os(&db, os_cache, uri, 1, os_name) {
+        Ok(os) => os,
+        Err(responder) => return responder.map(StreamedTemplateResponder),
+    };
+    OsPageResponder::Page(StreamedTemplateResponder(os_dll_template(&db, os, dll_name, Some((lo, hi)), sort.unwrap_or_default(), path_to_root)))
+}
+
+/// Lists the symbols of a DLL in an operating system, optionally limited to those whose ordinals
+/// are within the given inclusive range.
+fn os_dll_template(db: &Connection, os: IdentifiedOs, dll_name: &str, ordinal_range: Option<(u64, u64)>, sort: SymbolSort, path_to_root: PathToRoot) -> TemplateResponder<OsDllSymbolListTemplate> {
+    let (os_id, os_part) = os;
+
+    // does this DLL exist? what ID does it have?
+    let dll_info_rows_opt = query_dll_by_path(db, dll_name);
+    let (dll_id, dll_part) = match dll_info_rows_opt {
+        None => return TemplateResponder::Failure,
+        Some(v) if v.len() == 0 => return TemplateResponder::NotFound,
+        Some(mut v) => v.swap_remove(0),
+    };
+
+    if let Some((lo, hi)) = ordinal_range {
+        // ordinals are stored as SQLite integers, i.e. they fit into an i64
+        if i64::try_from(hi).is_err() {
+            return TemplateResponder::NotFound;
+        }
+
+        let range_hint = if lo > hi {
+            Some(INVERTED_ORDINAL_RANGE_HINT.to_owned())
+        } else if hi - lo >= MAX_ORDINAL_RANGE_SPAN {
+            Some(ordinal_range_too_large_hint())
+        } else {
+            None
+        };
+        if let Some(hint) = range_hint {
+            let template = OsDllSymbolListTemplate {
+                path_to_root: path_to_root.0,
+                symbols: Vec::new(),
+                os: os_part,
+                dll: dll_part,
+                ordinal_range,
+                hint: Some(hint),
+                sort,
+            };
+            return TemplateResponder::BadRequest(template);
+        }
+    }
+    let (ordinal_lo, ordinal_hi) = ordinal_range.unzip();
+
+    let order = match sort {
+        SymbolSort::Friendly => "COALESCE(sym.friendly_name, sym.raw_name) ASC NULLS LAST, sym.dll_name, sym.ordinal",
+        SymbolSort::Raw => "sym.raw_name ASC NULLS LAST, sym.friendly_name ASC NULLS LAST, sym.dll_name, sym.ordinal",
+        SymbolSort::Dll => "sdo.ordinal ASC NULLS LAST, sym.raw_name ASC NULLS LAST, sym.dll_name, sym.ordinal",
+    };
+
+    // find the DLL's symbols in this OS, named and ordinal
+    // including meta-functions
+    // (a symbol appears once per architecture under which the DLL exports it; list it once)
+    let syms_opt = prepare_and_query_database(
+        db,
+        &format!(
+            "
+                SELECT
+                    sym.raw_name,
+                    sym.friendly_name,
+                    sym.dll_name,
+                    sym.ordinal,
+                    sym.is_meta_func,
+                    sym.deprecated
+                FROM
+                    dlls d
+                    INNER JOIN symbol_dll_os sdo
+                        ON sdo.dll_id = d.dll_id
+                    INNER JOIN symbols sym
+                        ON sym.sym_id = sdo.sym_id
+                WHERE
+                    sdo.os_id = ?1
+                    AND d.dll_id = ?2
+                    AND (?3 IS NULL OR sdo.ordinal BETWEEN ?3 AND ?4)
+                GROUP BY
+                    sym.sym_id
+                ORDER BY
+                    {}
+            ",
+            order,
+        ),
+        (os_id, dll_id, ordinal_lo, ordinal_hi),
+        |row| SymbolPart::try_from_row(0, row),
+    );
+    let Some(symbols) = syms_opt
+        else { return TemplateResponder::Failure };
+
+    let template = OsDllSymbolListTemplate {
+        path_to_root: path_to_root.0,
+        symbols,
+        os: os_part,
+        dll: dll_part,
+        ordinal_range,
+        hint: None,
+        sort,
+    };
+    TemplateResponder::Template(template)
+}
+
+#[rocket::get("/os/<os_name>/dll/<dll_name>/exports.def")]
+fn os_dll_exports_def(os_name: &str, dll_name: &str, route_span: RouteSpan, db_pool: &State<DbPool>, os_cache: &State<OsCache>) -> TextResponder {
+    let _entered = route_span.enter();
+    let Some(db) = connect_to_database(db_pool)
+        else { return TextResponder::Failure };
+
+    // does this operating system exist? what ID does it have?
+    let os_id_rows_opt = query_os_by_short_name(&db, os_cache, os_name);
+    let (os_id, _os_part) = match os_id_rows_opt {
+        None => return TextResponder::Failure,
+        Some(v) if v.is_empty() => return TextResponder::NotFound,
+        Some(mut v) => v.swap_remove(0),
+    };
+
+    // does this DLL exist? what ID does it have?
+    let dll_info_rows_opt = prepare_and_query_database(
+        &db,
+        "
+            SELECT
+                dll_id, path
+            FROM
+                dlls
+            WHERE
+                path = ?1
+        ",
+        [dll_name],
+        |row| {
+            let dll_id: i64 = row.get(0)?;
+            let dll_path: String = row.get(1)?;
+            Ok((dll_id, dll_path))
+        },
+    );
+    let (dll_id, dll_path) = match dll_info_rows_opt {
+        None => return TextResponder::Failure,
+        Some(v) if v.is_empty() => return TextResponder::NotFound,
+        Some(mut v) => v.swap_remove(0),
+    };
+
+    // find the DLL's exports in this OS, including meta-functions,
+    // along with the ordinal they have in this OS
+    let exports_opt = prepare_and_query_database(
+        &db,
+        "
+            SELECT DISTINCT
+                sym.raw_name,
+                sym.friendly_name,
+                sym.dll_name,
+                sym.ordinal,
+                sym.is_meta_func,
+                sym.deprecated,
+                sdo.ordinal
+            FROM
+                symbol_dll_os sdo
+                INNER JOIN symbols sym
+                    ON sym.sym_id = sdo.sym_id
+            WHERE
+                sdo.os_id = ?1
+                AND sdo.dll_id = ?2
+            ORDER BY
+                7 ASC NULLS LAST,
+                1 ASC NULLS LAST,
+                3,
+                4
+        ",
+        [os_id, dll_id],
+        |row| {
+            let symbol = SymbolPart::try_from_row(0, row)?;
+            let os_ordinal: Option<u64> = row.get(6)?;
+            Ok((symbol, os_ordinal))
+        },
+    );
+    let Some(exports) = exports_opt
+        else { return TextResponder::Failure };
+    if exports.is_empty() {
+        // the DLL isn't part of this OS
+        return TextResponder::NotFound;
+    }
+
+    let dll_file_name = dll_path.rsplit('\\').next().unwrap();
+    let mut def = format!("LIBRARY {}\r\nEXPORTS\r\n", dll_file_name);
+    for (symbol, os_ordinal) in exports {
+        match (&symbol, os_ordinal) {
+            (SymbolPart::Named { raw_name, .. }, Some(ordinal)) => {
+                def.push_str(&format!("    {} @{}\r\n", raw_name, ordinal));
+            },
+            (SymbolPart::Named { raw_name, .. }, None) => {
+                def.push_str(&format!("    {}\r\n", raw_name));
+            },
+            (SymbolPart::DllOrdinal { ordinal, friendly_name, .. }, os_ordinal) => {
+                // exported without a name; the import library still needs one for the linker
+                let ordinal = os_ordinal.unwrap_or(*ordinal);
+                let name = match friendly_name {
+                    Some(f) => f.clone(),
+                    None => format!("Ordinal{}", ordinal),
+                };
+                def.push_str(&format!("    {} @{} NONAME\r\n", name, ordinal));
             },
         }
     }
+
+    let def_file_name = match dll_file_name.rsplit_once('.') {
+        Some((stem, _extension)) => format!("{}.def", stem),
+        None => format!("{}.def", dll_file_name),
+    };
+    TextResponder::Text {
+        content_type: ContentType::Plain,
+        body: def,
+        attachment_file_name: Some(def_file_name),
+    }
 }
 
+/// Renders the list of all symbols of an operating system, split into pages.
+///
+/// A page is requested either by number (`page`) or by keyset (`after`, see [`keyset`]).
+#[rocket::get("/os/<os_name>/all-symbols?<page>&<per_page>&<sort>&<arch>&<limit>&<after>")]
+#[allow(clippy::too_many_arguments)] // Rocket passes each parameter and guard separately
+fn all_os_symbols(os_name: &str, page: Option<u64>, per_page: Option<u64>, sort: Option<SymbolSort>, arch: Option<Architecture>, limit: Option<u64>, after: Option<&str>, uri: &Origin<'_>, path_to_root: PathToRoot, route_span: RouteSpan, config: &State<WebConfig>, db_pool: &State<DbPool>, os_cache: &State<OsCache>) -> OsPageResponder<StreamedTemplateResponder<OsSymbolListTemplate>> {
+    let _entered = route_span.enter();
+    let Some(db) = connect_to_database(db_pool)
+        else { return OsPageResponder::Page(StreamedTemplateResponder(TemplateResponder::Failure)) };
+    let os = match resolve_page_os(&db, os_cache, uri, 1, os_name) {
+        Ok(os) => os,
+        Err(responder) => return responder.map(StreamedTemplateResponder),
+    };
+    OsPageResponder::Page(StreamedTemplateResponder(all_os_symbols_template(&db, os, page, per_page, after, sort.unwrap_or_default(), arch, limit, config.max_rows, path_to_root)))
+}
 
-#[rocket::get("/os/<os_name>")]
-fn os_page(os_name: &str) -> TemplateResponder<OsTemplate> {
-    let Some(db) = connect_to_database()
-        else { return TemplateResponder::Failure };
+#[allow(clippy::too_many_arguments)]
+fn all_os_symbols_template(db: &Connection, os: IdentifiedOs, page: Option<u64>, per_page: Option<u64>, after: Option<&str>, sort: SymbolSort, arch: Option<Architecture>, limit: Option<u64>, max_rows: u64, path_to_root: PathToRoot) -> TemplateResponder<OsSymbolListTemplate> {
+    let (os_id, os) = os;
 
-    // does this operating system exist? what ID does it have?
-    let os_info_rows_opt = prepare_and_query_database(
-        &db,
+    // count the symbols once to know the number of pages
+    // (a symbol is listed once per DLL, even if the DLL exports it under multiple architectures)
+    let count_rows_opt = prepare_and_query_database(
+        db,
         "
             SELECT
-                os_id, short_name, COALESCE(long_name, short_name), has_icon
+                COUNT(*)
             FROM
-                operating_systems
-            WHERE
-                short_name = ?1
+                (
+                    SELECT DISTINCT
+                        sdo.sym_id,
+                        sdo.dll_id
+                    FROM
+                        symbol_dll_os sdo
+                        INNER JOIN symbols sym
+                            ON sym.sym_id = sdo.sym_id
+                    WHERE
+                        sdo.os_id = ?1
+                        AND sym.is_meta_func = 0
+                        AND (?2 IS NULL OR sdo.architecture = ?2)
+                )
         ",
+        (os_id, arch.map(|a| a.as_str())),
+        |row| row.get(0),
+    );
+    let total_items: u64 = match count_rows_opt {
+        Some(v) if !v.is_empty() => v[0],
+        _ => return TemplateResponder::Failure,
+    };
+
+    // only the symbols within the row limit are reachable through the pages
+    let row_count = row_limit(limit, max_rows, max_rows);
+    let truncated = total_items > row_count;
+    let mut pagination = Pagination::new(page, per_page, DEFAULT_SYMBOLS_PER_PAGE, total_items.min(row_count))
+        .with_extra_params(sort.query_params());
+    if let Some(a) = arch {
+        pagination = pagination.with_extra_params(&a.query_params());
+    }
+    if limit.is_some() {
+        pagination = pagination.with_extra_param("limit", row_count.to_string());
+    }
+    pagination.after = after.map(|a| a.to_owned());
+
+    let Some(architectures) = query_os_architectures(db, os_id)
+        else { return TemplateResponder::Failure };
+
+    if let Some(a) = after && !sort.os_symbol_key().is_valid_token(a) {
+        let template = OsSymbolListTemplate {
+            path_to_root: path_to_root.0,
+            os,
+            symbols: Vec::new(),
+            pagination,
+            sort,
+            architecture: arch,
+            architectures,
+            truncated: false,
+            hint: Some(INVALID_AFTER_HINT),
+        };
+        return TemplateResponder::BadRequest(template);
+    }
+
+    let symbol_rows = match query_os_symbols(db, os_id, arch, Some(&mut pagination), sort) {
+        None => return TemplateResponder::Failure,
+        Some(sr) => sr,
+    };
+
+    let template = OsSymbolListTemplate {
+        path_to_root: path_to_root.0,
+        os,
+        symbols: symbol_rows,
+        pagination,
+        sort,
+        architecture: arch,
+        architectures,
+        truncated,
+        hint: None,
+    };
+    TemplateResponder::Template(template)
+}
+
+#[rocket::get("/os/<os_name>/all-symbols.csv")]
+fn all_os_symbols_csv(os_name: &str, route_span: RouteSpan, db_pool: &State<DbPool>, os_cache: &State<OsCache>) -> OsSymbolsCsvResponder {
+    let _entered = route_span.enter();
+    let Some(db) = connect_to_database(db_pool)
+        else { return OsSymbolsCsvResponder::Failure };
+
+    let os_info_rows_opt = query_os_by_short_name(&db, os_cache, os_name);
+    let (os_id, os) = match os_info_rows_opt {
+        None => return OsSymbolsCsvResponder::Failure,
+        Some(v) if v.is_empty() => return OsSymbolsCsvResponder::NotFound,
+        Some(mut v) => v.swap_remove(0),
+    };
+
+    OsSymbolsCsvResponder::Csv {
+        db,
+        os_id,
+        file_name: format!("{}-symbols.csv", os.short_name),
+    }
+}
+
+/// Writes all symbols of the given operating system as CSV, each as soon as it has been read from
+/// the database.
+///
+/// Since the response is already underway, errors are only logged; the output is cut short.
+fn write_os_symbols_csv<W: std::io::Write>(db: &Connection, os_id: i64, writer: W) {
+    let Some(mut stmt) = prepare(db, &os_symbols_query(SymbolSort::Friendly))
+        else { return };
+    let mut rows = match stmt.query((os_id, -1, 0, None::<&str>, None::<&str>)) {
+        Ok(r) => r,
+        Err(e) => {
+            error!("failed to query symbols of OS {} for CSV: {}", os_id, e);
+            return;
+        },
+    };
+
+    // RFC 4180 terminates records with CRLF
+    let mut csv_writer = csv::WriterBuilder::new()
+        .terminator(csv::Terminator::CRLF)
+        .from_writer(writer);
+    let header = ["friendly_name", "raw_name", "dll_name", "ordinal", "dll_path", "secondary_platform"];
+    if let Err(e) = csv_writer.write_record(header) {
+        log_csv_error(e);
+        return;
+    }
+    loop {
+        let row = match rows.next() {
+            Ok(Some(r)) => r,
+            Ok(None) => break,
+            Err(e) => {
+                error!("failed to obtain symbol of OS {} for CSV: {}", os_id, e);
+                return;
+            },
+        };
+        let os_sym = match os_symbol_from_row(row) {
+            Ok((os_sym, _token)) => os_sym,
+            Err(e) => {
+                error!("failed to read symbol of OS {} for CSV: {}", os_id, e);
+                return;
+            },
+        };
+        let (dll_name, ordinal) = match os_sym.symbol.dll_pair() {
+            Some((dll_name, ordinal)) => (dll_name, ordinal.to_string()),
+            None => ("", String::new()),
+        };
+        let record = [
+            os_sym.symbol.friendly_name().unwrap_or(""),
+            os_sym.symbol.raw_name().unwrap_or(""),
+            dll_name,
+            &ordinal,
+            &os_sym.dll.path,
+            if os_sym.dll.secondary_platform { "true" } else { "false" },
+        ];
+        if let Err(e) = csv_writer.write_record(record) {
+            log_csv_error(e);
+            return;
+        }
+    }
+    if let Err(e) = csv_writer.flush() {
+        log_streaming_error("CSV", &e);
+    }
+}
+
+/// Logs an error that occurred while writing CSV.
+fn log_csv_error(error: csv::Error) {
+    match error.into_kind() {
+        csv::ErrorKind::Io(e) => log_streaming_error("CSV", &e),
+        other => error!("failed to write CSV: {:?}", other),
+    }
+}
+
+#[rocket::get("/api/os/<os_name>/symbols")]
+fn api_os_symbols(os_name: &str, route_span: RouteSpan, db_pool: &State<DbPool>, os_cache: &State<OsCache>) -> JsonResponder<Vec<OsSymbolApiPart>> {
+    let _entered = route_span.enter();
+    let Some(db) = connect_to_database(db_pool)
+        else { return JsonResponder::Failure };
+
+    let os_info_rows_opt = query_os_by_short_name(&db, os_cache, os_name);
+    let (os_id, _os) = match os_info_rows_opt {
+        None => return JsonResponder::Failure,
+        Some(v) if v.is_empty() => return JsonResponder::NotFound,
+        Some(mut v) => v.swap_remove(0),
+    };
+
+    let Some(symbol_rows) = query_os_symbols(&db, os_id, None, None, SymbolSort::Friendly)
+        else { return JsonResponder::Failure };
+
+    let symbols = symbol_rows
+        .into_iter()
+        .map(|os_sym| OsSymbolApiPart {
+            symbol: os_sym.symbol,
+            dll_path: os_sym.dll.path,
+            secondary_platform: os_sym.dll.secondary_platform,
+        })
+        .collect();
+    JsonResponder::Json(symbols)
+}
+
+/// Finds the operating system with the given short name, consulting the cache before the database.
+///
+/// If no operating system has exactly that short name, finds the one whose short name matches once
+/// surrounding whitespace is trimmed and case is ignored. If the short names of multiple operating
+/// systems only differ in case, none of them is found.
+fn query_os_by_short_name(db: &Connection, os_cache: &OsCache, os_name: &str) -> Option<Vec<IdentifiedOs>> {
+    if let Some(os) = os_cache.get(db, os_name) {
+        return Some(vec![os]);
+    }
+    let exact_oses = query_os_by_short_name_uncached(db, os_name, false)?;
+    if !exact_oses.is_empty() {
+        return Some(exact_oses);
+    }
+
+    let mut similar_oses = query_os_by_short_name_uncached(db, os_name.trim(), true)?;
+    if similar_oses.len() != 1 {
+        // do not guess
+        similar_oses.clear();
+    }
+    Some(similar_oses)
+}
+
+fn query_os_by_short_name_uncached(db: &Connection, os_name: &str, ignore_case: bool) -> Option<Vec<IdentifiedOs>> {
+    prepare_and_query_database(
+        db,
+        &format!(
+            "
+                SELECT
+                    os_id,
+                    short_name,
+                    COALESCE(long_name, short_name),
+                    has_icon,
+                    build_number
+                FROM
+                    operating_systems
+                WHERE
+                    short_name = ?1 {}
+            ",
+            if ignore_case { "COLLATE NOCASE" } else { "" },
+        ),
         [os_name],
         |row| {
             let os_id: i64 = row.get(0)?;
             let os_part = OperatingSystemPart::try_from_row(1, row)?;
             Ok((os_id, os_part))
         },
+    )
+}
+
+/// The outcome of looking up an operating system by a short name taken from a URL.
+enum OsLookup {
+    Found(IdentifiedOs),
+
+    /// The short name only matches once surrounding whitespace is trimmed and case is ignored;
+    /// contains the canonical short name.
+    NotCanonical(String),
+
+    NotFound,
+    Failure,
+}
+
+/// Looks up the operating system with the given short name, noting whether the short name is
+/// canonical so that the caller can redirect to the canonical short name.
+fn look_up_os(db: &Connection, os_cache: &OsCache, os_name: &str) -> OsLookup {
+    match query_os_by_short_name(db, os_cache, os_name) {
+        None => OsLookup::Failure,
+        Some(mut v) if !v.is_empty() => {
+            let os = v.swap_remove(0);
+            if os.1.short_name == os_name {
+                OsLookup::Found(os)
+            } else {
+                OsLookup::NotCanonical(os.1.short_name)
+            }
+        },
+        Some(_) => OsLookup::NotFound,
+    }
+}
+
+/// Responds with a page about one or more operating systems, or redirects to the same page under
+/// the canonical short name of an operating system.
+#[derive(Responder)]
+enum OsPageResponder<R> {
+    Page(R),
+    CanonicalRedirect(Box<Redirect>),
+}
+impl<R> OsPageResponder<R> {
+    pub fn map<S, F: FnOnce(R) -> S>(self, f: F) -> OsPageResponder<S> {
+        match self {
+            Self::Page(r) => OsPageResponder::Page(f(r)),
+            Self::CanonicalRedirect(redirect) => OsPageResponder::CanonicalRedirect(redirect),
+        }
+    }
+}
+
+/// Looks up the operating system whose short name is the segment at the given index of the
+/// requested path.
+///
+/// Fails with a redirect if the short name is not canonical and with the appropriate error
+/// response if the operating system cannot be found.
+fn resolve_page_os<T: Template + Debug>(db: &Connection, os_cache: &OsCache, uri: &Origin<'_>, segment_index: usize, os_name: &str) -> Result<IdentifiedOs, OsPageResponder<TemplateResponder<T>>> {
+    match look_up_os(db, os_cache, os_name) {
+        OsLookup::Found(os) => Ok(os),
+        OsLookup::NotCanonical(short_name) => Err(OsPageResponder::CanonicalRedirect(Box::new(redirect_to_canonical_os(uri, segment_index, &short_name)))),
+        OsLookup::NotFound => Err(OsPageResponder::Page(TemplateResponder::NotFound)),
+        OsLookup::Failure => Err(OsPageResponder::Page(TemplateResponder::Failure)),
+    }
+}
+
+/// Redirects permanently to the requested URL with the segment at the given index of its path
+/// replaced by the canonical short name of an operating system.
+fn redirect_to_canonical_os(uri: &Origin<'_>, segment_index: usize, canonical_short_name: &str) -> Redirect {
+    let mut path = String::new();
+    for (i, segment) in uri.path().segments().enumerate() {
+        if i > 0 {
+            path.push('/');
+        }
+        let segment = if i == segment_index { canonical_short_name } else { segment };
+        path.extend(utf8_percent_encode(segment, URL_UNRESERVED));
+    }
+
+    // keep the redirect relative, like all the links between pages
+    let mut target = format!("{}{}", path_to_root_from(uri.path().as_str()), path);
+    if let Some(query) = uri.query() {
+        target.push('?');
+        target.push_str(query.as_str());
+    }
+    Redirect::moved(target)
+}
+
+/// Finds the architectures under which symbols are available in the given operating system.
+fn query_os_architectures(db: &Connection, os_id: i64) -> Option<Vec<Architecture>> {
+    let names: Vec<String> = prepare_and_query_database(
+        db,
+        "
+            SELECT DISTINCT
+                architecture
+            FROM
+                symbol_dll_os
+            WHERE
+                os_id = ?1
+                AND architecture IS NOT NULL
+        ",
+        [os_id],
+        |row| row.get(0),
+    )?;
+    let mut architectures: Vec<Architecture> = names
+        .iter()
+        .filter_map(|name| Architecture::from_db_name(name))
+        .collect();
+    architectures.sort_unstable();
+    Some(architectures)
+}
+
+/// Finds the DLL with the given path, returning its ID and information.
+fn query_dll_by_path(db: &Connection, dll_path: &str) -> Option<Vec<(i64, DllPart)>> {
+    prepare_and_query_database(
+        db,
+        "
+            SELECT
+                dll_id, path, secondary_platform
+            FROM
+                dlls
+            WHERE
+                path = ?1
+        ",
+        [dll_path],
+        |row| {
+            let dll_id: i64 = row.get(0)?;
+            let dll_part = DllPart::try_from_row(1, row)?;
+            Ok((dll_id, dll_part))
+        },
+    )
+}
+
+/// Finds all symbols available in the given operating system, named and ordinal, along with the
+/// DLLs containing them; no meta-functions though.
+///
+/// If an architecture is given, only the symbols exported under that architecture are returned. If
+/// pagination is given, only the symbols on the current page are returned, and the token of the
+/// page following it is set.
+fn query_os_symbols(db: &Connection, os_id: i64, architecture: Option<Architecture>, pagination: Option<&mut Pagination>, sort: SymbolSort) -> Option<Vec<OsSymbolPart>> {
+    // a negative LIMIT means no limit in SQLite
+    let (limit, offset, after) = match &pagination {
+        Some(p) => {
+            let (limit, offset) = p.query_limit_offset();
+            (i64::try_from(limit).unwrap(), offset, p.after.clone())
+        },
+        None => (-1, 0, None),
+    };
+    let rows = prepare_and_query_database(
+        db,
+        &os_symbols_query(sort),
+        (os_id, limit, offset, architecture.map(|a| a.as_str()), after),
+        os_symbol_from_row,
     );
-    let (os_id, os_part) = match os_info_rows_opt {
-        None => return TemplateResponder::Failure,
-        Some(v) if v.len() == 0 => return TemplateResponder::NotFound,
-        Some(mut v) => v.swap_remove(0),
+    let rows = rows?;
+
+    let os_symbols = match pagination {
+        Some(p) => p.finish_page(rows),
+        None => rows.into_iter().map(|(os_symbol, _token)| os_symbol).collect(),
+    };
+    Some(os_symbols)
+}
+
+/// The query for the symbols of an operating system in the given order, along with the DLLs
+/// containing them and the sort token of each row; no meta-functions though.
+///
+/// The parameters are the ID of the operating system, the limit and offset, the architecture (or
+/// `NULL` for all of them) and the token after which to continue (or `NULL` to start at the
+/// beginning).
+fn os_symbols_query(sort: SymbolSort) -> String {
+    let key = sort.os_symbol_key();
+    format!(
+        "
+            SELECT
+                sym.raw_name,
+                sym.friendly_name,
+                sym.dll_name,
+                sym.ordinal,
+                sym.is_meta_func,
+                sym.deprecated,
+                dll.path,
+                dll.secondary_platform,
+                {}
+            FROM
+                symbols sym
+                INNER JOIN symbol_dll_os sdo
+                    ON sdo.sym_id = sym.sym_id
+                INNER JOIN dlls dll
+                    ON dll.dll_id = sdo.dll_id
+            WHERE
+                sdo.os_id = ?1
+                AND sym.is_meta_func = 0
+                AND (?4 IS NULL OR sdo.architecture = ?4)
+                AND {}
+            GROUP BY
+                sym.sym_id,
+                dll.dll_id
+            ORDER BY
+                {}
+            LIMIT ?2 OFFSET ?3
+        ",
+        key.token_expression(),
+        key.after_condition(5),
+        key.order_by(),
+    )
+}
+
+/// Reads a row returned by [`os_symbols_query`].
+fn os_symbol_from_row(row: &Row<'_>) -> Result<(OsSymbolPart, String), rusqlite::Error> {
+    let symbol_part = SymbolPart::try_from_row(0, row)?;
+    let dll_part = DllPart::try_from_row(6, row)?;
+    let token: String = row.get(8)?;
+    let os_symbol = OsSymbolPart {
+        dll: dll_part,
+        symbol: symbol_part,
     };
+    Ok((os_symbol, token))
+}
+
+/// Collapses the presence of a symbol in each operating system, ordered by release date, into the
+/// intervals of contiguous presence.
+///
+/// Also returns the operating system from which on the symbol is no longer present, if any.
+fn presence_intervals(catalog: Vec<(OperatingSystemPart, bool)>) -> (Vec<PresenceInterval>, Option<OperatingSystemPart>) {
+    let mut intervals: Vec<PresenceInterval> = Vec::new();
+    let mut absent_since = None;
+    let mut in_interval = false;
+    for (os, present) in catalog {
+        if present {
+            if in_interval {
+                intervals.last_mut().unwrap().last = Some(os);
+            } else {
+                intervals.push(PresenceInterval {
+                    first: os,
+                    last: None,
+                });
+                in_interval = true;
+                absent_since = None;
+            }
+        } else {
+            if in_interval {
+                absent_since = Some(os);
+            }
+            in_interval = false;
+        }
+    }
+    (intervals, absent_since)
+}
+
+/// Obtains the operating systems containing the given symbol, ordered by release date, along with
+/// the DLLs in which each of them contains the symbol.
+fn query_os_dlls(db: &Connection, sym_id: i64) -> Option<Vec<(OperatingSystemPart, Vec<SymbolDllPart>)>> {
+    let dll_rows_opt = prepare_and_query_database(
+        db,
+        "
+            SELECT
+                os.os_id,
+                os.short_name,
+                COALESCE(os.long_name, os.short_name),
+                os.has_icon,
+                os.build_number,
+                dll.path,
+                dll.secondary_platform,
+                sdo.architecture,
+                sdo.ordinal
+            FROM
+                operating_systems os
+                INNER JOIN symbol_dll_os sdo
+                    ON sdo.os_id = os.os_id
+                INNER JOIN dlls dll
+                    ON dll.dll_id = sdo.dll_id
+            WHERE
+                sdo.sym_id = ?1
+            ORDER BY
+                os.sort_order ASC,
+                3, 4
+        ",
+        [sym_id],
+        |row| {
+            let os_id: i64 = row.get(0)?;
+            let os_part = OperatingSystemPart::try_from_row(1, row)?;
+            let dll_part = DllPart::try_from_row(5, row)?;
+            let architecture_name: Option<String> = row.get(7)?;
+            let architecture = architecture_name
+                .and_then(|name| Architecture::from_db_name(&name));
+            let ordinal: Option<u64> = row.get(8)?;
+            let symbol_dll = SymbolDllPart {
+                dll: dll_part,
+                architecture,
+                ordinal,
+            };
+            Ok((os_id, os_part, symbol_dll))
+        },
+    );
+    let dll_rows = dll_rows_opt?;
+
+    let mut id_to_os: BTreeMap<i64, OperatingSystemPart> = BTreeMap::new();
+    let mut os_id_to_dlls: BTreeMap<i64, Vec<SymbolDllPart>> = BTreeMap::new();
+    let mut os_id_ordered = Vec::new();
+    for (os_id, os, dll) in dll_rows {
+        id_to_os
+            .entry(os_id)
+            .or_insert_with(|| {
+                // also append ID to ordering vec
+                os_id_ordered.push(os_id);
+
+                os
+            });
+        os_id_to_dlls
+            .entry(os_id)
+            .or_insert_with(|| Vec::with_capacity(1))
+            .push(dll);
+    }
+
+    let mut os_dlls = Vec::with_capacity(id_to_os.len());
+    for id in os_id_ordered {
+        let os = id_to_os
+            .remove(&id)
+            .unwrap();
+        let dlls = os_id_to_dlls
+            .remove(&id)
+            .unwrap();
+        os_dlls.push((os, dlls));
+    }
+    Some(os_dlls)
+}
+
+/// The operating systems with the earliest and latest release dates that contain a symbol.
+#[derive(Clone, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+struct SeenRange {
+    pub first_seen: Option<OperatingSystemPart>,
+    pub last_seen: Option<OperatingSystemPart>,
+
+    /// Whether operating systems have been released after `last_seen`.
+    pub last_seen_is_superseded: bool,
+}
+
+/// Finds the operating systems with the earliest and latest release dates that contain the given
+/// symbol.
+///
+/// Operating systems without a release date are not considered; if none of the operating systems
+/// containing the symbol has one, both ends of the range are `None`. The earliest operating system
+/// is precomputed by `whload` (see `db/reindex.sql`), whose order the latest operating system
+/// mirrors.
+fn query_seen_range(db: &Connection, sym_id: i64) -> Option<SeenRange> {
+    let first_rows = prepare_and_query_database(
+        db,
+        "
+            SELECT
+                os.short_name,
+                COALESCE(os.long_name, os.short_name),
+                os.has_icon,
+                os.build_number
+            FROM
+                symbols sym
+                INNER JOIN operating_systems os
+                    ON os.os_id = sym.first_os_id
+            WHERE
+                sym.sym_id = ?1
+        ",
+        [sym_id],
+        |row| OperatingSystemPart::try_from_row(0, row),
+    )?;
+    let Some(first_seen) = first_rows.into_iter().next()
+        else { return Some(SeenRange::default()) };
 
-    // find its DLLs
-    let dlls_opt = prepare_and_query_database(
-        &db,
+    let last_rows = prepare_and_query_database(
+        db,
         "
-            SELECT DISTINCT
-                d.path,
-                d.secondary_platform
+            SELECT
+                os.short_name,
+                COALESCE(os.long_name, os.short_name),
+                os.has_icon,
+                os.build_number,
+                EXISTS (
+                    SELECT 1
+                    FROM operating_systems newer_os
+                    WHERE newer_os.release_date > os.release_date
+                )
             FROM
-                dlls d
+                operating_systems os
             WHERE
-                EXISTS (
+                os.release_date IS NOT NULL
+                AND EXISTS (
                     SELECT 1
                     FROM symbol_dll_os sdo
-                    WHERE sdo.dll_id = d.dll_id
-                    AND sdo.os_id = ?1
+                    WHERE sdo.sym_id = ?1
+                    AND sdo.os_id = os.os_id
                 )
             ORDER BY
-                1
+                os.sort_order DESC,
+                2 DESC
+            LIMIT 1
         ",
-        [os_id],
-        |row| DllPart::try_from_row(0, row),
-    );
-    let Some(dlls) = dlls_opt
-        else { return TemplateResponder::Failure };
+        [sym_id],
+        |row| {
+            let os_part = OperatingSystemPart::try_from_row(0, row)?;
+            let is_superseded: bool = row.get(4)?;
+            Ok((os_part, is_superseded))
+        },
+    )?;
+    let (last_seen, last_seen_is_superseded) = last_rows.into_iter().next()
+        .unwrap_or_else(|| (first_seen.clone(), false));
+    Some(SeenRange {
+        first_seen: Some(first_seen),
+        last_seen: Some(last_seen),
+        last_seen_is_superseded,
+    })
+}
 
-    let template = OsTemplate {
-        dlls,
-        os: os_part,
-    };
-    TemplateResponder::Template(template)
+/// Returns whether the name looks like a plain C identifier, i.e. not like a (mangled or demangled)
+/// C++ name.
+fn is_plain_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    let Some(first_char) = chars.next()
+        else { return false };
+    (first_char.is_ascii_alphabetic() || first_char == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
 }
 
-#[rocket::get("/os/<os_name>/dll/<dll_name>")]
-fn os_dll_page(os_name: &str, dll_name: &str) -> TemplateResponder<OsDllSymbolListTemplate> {
-    let Some(db) = connect_to_database()
-        else { return TemplateResponder::Failure };
+/// Finds the other members of the family of the given symbol, such as `CreateFileA` and
+/// `CreateFileW` for `CreateFile`.
+///
+/// A family consists of a base name along with its variants suffixed by `A` (ANSI), `W` (wide) and
+/// `Ex` (extended), as well as `ExA` and `ExW`. To avoid mistaking e.g. `GetDataW` for a variant of
+/// `GetDat`, a family is only recognized if its base name exists as a symbol or both its `A` and
+/// `W` variants (with or without `Ex`) exist.
+fn query_related_symbols(db: &Connection, sym_part: &SymbolPart) -> Option<Vec<SymbolPart>> {
+    const FAMILY_SUFFIXES: [&str; 6] = ["", "A", "W", "Ex", "ExA", "ExW"];
+
+    let Some(raw_name) = sym_part.raw_name()
+        else { return Some(Vec::new()) };
+    if !is_plain_identifier(raw_name) {
+        return Some(Vec::new());
+    }
 
-    // does this operating system exist? what ID does it have?
-    let os_info_rows_opt = prepare_and_query_database(
-        &db,
+    let without_charset = raw_name
+        .strip_suffix(['A', 'W'])
+        .unwrap_or(raw_name);
+    let base_name = without_charset
+        .strip_suffix("Ex")
+        .unwrap_or(without_charset);
+    if base_name.is_empty() {
+        return Some(Vec::new());
+    }
+    let family_names = FAMILY_SUFFIXES.map(|suffix| format!("{}{}", base_name, suffix));
+
+    let family = prepare_and_query_database(
+        db,
         "
             SELECT
-                os_id, short_name, COALESCE(long_name, short_name), has_icon
+                raw_name,
+                friendly_name,
+                is_meta_func,
+                deprecated
             FROM
-                operating_systems
+                symbols
             WHERE
-                short_name = ?1
+                raw_name IN (?1, ?2, ?3, ?4, ?5, ?6)
+            ORDER BY
+                raw_name
         ",
-        [os_name],
-        |row| {
-            let os_id: i64 = row.get(0)?;
-            let os_part = OperatingSystemPart::try_from_row(1, row)?;
-            Ok((os_id, os_part))
-        },
-    );
-    let (os_id, os_part) = match os_info_rows_opt {
-        None => return TemplateResponder::Failure,
-        Some(v) if v.len() == 0 => return TemplateResponder::NotFound,
-        Some(mut v) => v.swap_remove(0),
-    };
+        rusqlite::params_from_iter(&family_names),
+        |row| SymbolPart::try_named_from_row(0, row),
+    )?;
+
+    let exists = |suffix: &str| family
+        .iter()
+        .any(|member| member.raw_name() == Some(&format!("{}{}", base_name, suffix)));
+    let is_family =
+        exists("")
+        || (exists("A") && exists("W"))
+        || (exists("ExA") && exists("ExW"));
+    if !is_family {
+        return Some(Vec::new());
+    }
 
-    // does this DLL exist? what ID does it have?
-    let dll_info_rows_opt = prepare_and_query_database(
-        &db,
+    Some(
+        family
+            .into_iter()
+            .filter(|member| member.raw_name() != Some(raw_name))
+            .collect()
+    )
+}
+
+fn finish_dlls(db: &Connection, sym_id: i64, sym_part: SymbolPart, path_to_root: String) -> TemplateResponder<SymbolTemplate> {
+    let Some(os_dlls) = query_os_dlls(db, sym_id)
+        else { return TemplateResponder::Failure };
+
+    // summarize the presence over all operating systems
+    // (editions are counted towards their base operating system)
+    let catalog_opt = prepare_and_query_database(
+        db,
         "
             SELECT
-                dll_id, path, secondary_platform
+                os.short_name,
+                COALESCE(os.long_name, os.short_name),
+                os.has_icon,
+                os.build_number,
+                EXISTS (
+                    SELECT 1
+                    FROM
+                        symbol_dll_os sdo
+                        INNER JOIN operating_systems sdo_os
+                            ON sdo_os.os_id = sdo.os_id
+                    WHERE
+                        sdo.sym_id = ?1
+                        AND (sdo_os.os_id = os.os_id OR sdo_os.parent_os_id = os.os_id)
+                )
             FROM
-                dlls
+                operating_systems os
             WHERE
-                path = ?1
+                os.parent_os_id IS NULL
+            ORDER BY
+                os.sort_order ASC,
+                2
         ",
-        [dll_name],
+        [sym_id],
         |row| {
-            let dll_id: i64 = row.get(0)?;
-            let dll_part = DllPart::try_from_row(1, row)?;
-            Ok((dll_id, dll_part))
+            let os_part = OperatingSystemPart::try_from_row(0, row)?;
+            let present: bool = row.get(4)?;
+            Ok((os_part, present))
         },
     );
-    let (dll_id, dll_part) = match dll_info_rows_opt {
-        None => return TemplateResponder::Failure,
-        Some(v) if v.len() == 0 => return TemplateResponder::NotFound,
-        Some(mut v) => v.swap_remove(0),
-    };
+    let Some(catalog) = catalog_opt
+        else { return TemplateResponder::Failure };
+    let all_oses_with_presence = catalog.clone();
+    let (presence, absent_since) = presence_intervals(catalog);
 
-    // find the DLL's symbols in this OS, named and ordinal
-    // including meta-functions
-    let syms_opt = prepare_and_query_database(
-        &db,
-        "
-            SELECT DISTINCT
-                sym.raw_name,
-                sym.friendly_name,
-                sym.dll_name,
-                sym.ordinal,
-                sym.is_meta_func
-            FROM
-                dlls d
-                INNER JOIN symbol_dll_os sdo
-                    ON sdo.dll_id = d.dll_id
-                INNER JOIN symbols sym
-                    ON sym.sym_id = sdo.sym_id
-            WHERE
-                sdo.os_id = ?1
-                AND d.dll_id = ?2
-            ORDER BY
-                1 ASC NULLS LAST,
-                2 ASC NULLS LAST,
-                3,
-                4
-        ",
-        [os_id, dll_id],
-        |row| SymbolPart::try_from_row(0, row),
-    );
-    let Some(symbols) = syms_opt
+    let Some(seen_range) = query_seen_range(db, sym_id)
         else { return TemplateResponder::Failure };
 
-    let template = OsDllSymbolListTemplate {
-        symbols,
-        os: os_part,
-        dll: dll_part,
+    let Some(related) = query_related_symbols(db, &sym_part)
+        else { return TemplateResponder::Failure };
+
+    let template = SymbolTemplate {
+        path_to_root,
+        symbol: sym_part,
+        os_dlls,
+        presence,
+        absent_since,
+        seen_range,
+        related,
+        all_oses_with_presence,
     };
     TemplateResponder::Template(template)
 }
 
-#[rocket::get("/os/<os_name>/all-symbols")]
-fn all_os_symbols(os_name: &str) -> TemplateResponder<OsSymbolListTemplate> {
-    let Some(db) = connect_to_database()
-        else { return TemplateResponder::Failure };
+/// Responds to a request for a symbol page.
+#[derive(Responder)]
+enum SymbolPageResponder {
+    Symbol(TemplateResponder<SymbolTemplate>),
+    CaseVariants(TemplateResponder<AlphabeticalSymbolListTemplate>),
+    CaseRedirect(Redirect),
+    Suggestions(TemplateResponder<NotFoundSuggestionsTemplate>),
+}
 
-    // does this operating system exist? what ID does it have?
-    let os_info_rows_opt = prepare_and_query_database(
-        &db,
+/// Renders the page of a named symbol, or returns its JSON representation if the client prefers
+/// JSON.
+///
+/// Case variants and suggestions for a symbol that does not exist are only offered as HTML; a JSON
+/// client receives a 404 instead.
+#[rocket::get("/symbol/<sym_raw_name>")]
+fn symbol_page(sym_raw_name: &str, format: ResponseFormat, path_to_root: PathToRoot, freshness: Freshness, route_span: RouteSpan, db_pool: &State<DbPool>) -> Conditional<NegotiatedResponder<SymbolPageResponder, SymbolApiPart>> {
+    let _entered = route_span.enter();
+    freshness.respond(|| symbol_template(sym_raw_name, format, path_to_root, db_pool))
+}
+
+fn symbol_template(sym_raw_name: &str, format: ResponseFormat, path_to_root: PathToRoot, db_pool: &State<DbPool>) -> NegotiatedResponder<SymbolPageResponder, SymbolApiPart> {
+    let negotiate = |template| NegotiatedResponder::from_template(format, template)
+        .map_html(SymbolPageResponder::Symbol);
+    let Some(db) = connect_to_database(db_pool)
+        else { return negotiate(TemplateResponder::Failure) };
+
+    // does this symbol exist? what ID does it have?
+    let sym_info_rows_opt = query_named_symbol(&db, sym_raw_name);
+    let (sym_id, sym_part) = match sym_info_rows_opt {
+        None => return negotiate(TemplateResponder::Failure),
+        Some(v) if v.is_empty() => return match format {
+            ResponseFormat::Html => NegotiatedResponder::Html(find_symbol_case_variants(&db, sym_raw_name, path_to_root.0)),
+            ResponseFormat::Json => NegotiatedResponder::Json(JsonResponder::NotFound),
+        },
+        Some(mut v) => v.swap_remove(0),
+    };
+
+    negotiate(finish_dlls(&db, sym_id, sym_part, path_to_root.0))
+}
+
+/// Handles a request for a symbol name that does not exist exactly as written.
+///
+/// If exactly one symbol has the same name apart from its case, redirects to that symbol's page; if
+/// multiple do, lists them. If none do, suggests symbols with a similar name.
+fn find_symbol_case_variants(db: &Connection, sym_raw_name: &str, path_to_root: String) -> SymbolPageResponder {
+    let case_variants_opt = prepare_and_query_database(
+        db,
         "
             SELECT
-                os_id,
-                short_name,
-                COALESCE(long_name, short_name),
-                has_icon
+                raw_name,
+                friendly_name,
+                is_meta_func,
+                deprecated
             FROM
-                operating_systems
+                symbols
             WHERE
-                short_name = ?1
+                raw_name = ?1 COLLATE NOCASE
+            ORDER BY
+                raw_name
         ",
-        [os_name],
-        |row| {
-            let os_id: i64 = row.get(0)?;
-            let os_part = OperatingSystemPart::try_from_row(1, row)?;
-            Ok((os_id, os_part))
-        },
+        [sym_raw_name],
+        |row| SymbolPart::try_named_from_row(0, row),
     );
-    let (os_id, os) = match os_info_rows_opt {
-        None => return TemplateResponder::Failure,
-        Some(v) if v.len() == 0 => return TemplateResponder::NotFound,
-        Some(mut v) => v.swap_remove(0),
+    let mut symbols = match case_variants_opt {
+        None => return SymbolPageResponder::Symbol(TemplateResponder::Failure),
+        Some(v) => v,
     };
 
-    // find all symbols available in this OS, named and ordinal;
-    // no meta-functions though
-    let symbol_rows_opt = prepare_and_query_database(
-        &db,
+    if symbols.len() == 1 {
+        let symbol = symbols.swap_remove(0);
+        let raw_name = symbol.raw_name()
+            .expect("named symbol has no raw name");
+
+        // we are at symbol/<sym_raw_name>; the canonical page is a sibling
+        let raw_name_percent: String = utf8_percent_encode(raw_name, URL_UNRESERVED).collect();
+        return SymbolPageResponder::CaseRedirect(Redirect::moved(raw_name_percent));
+    }
+    if symbols.is_empty() {
+        return SymbolPageResponder::Suggestions(suggest_similar_symbols(db, sym_raw_name, path_to_root));
+    }
+
+    let template = AlphabeticalSymbolListTemplate {
+        path_to_root,
+        index: ListIndex::from_symbols(symbols.iter(), SymbolSort::Raw),
+        symbols,
+        hint: Some(CASE_VARIANTS_HINT),
+        pagination: None,
+        hide_deprecated: None,
+        truncated: false,
+    };
+    SymbolPageResponder::CaseVariants(TemplateResponder::Template(template))
+}
+
+/// Renders a not-found page suggesting the symbols whose names share a prefix with the name of a
+/// symbol that was not found.
+fn suggest_similar_symbols(db: &Connection, sym_raw_name: &str, path_to_root: String) -> TemplateResponder<NotFoundSuggestionsTemplate> {
+    let prefix: String = sym_raw_name
+        .chars()
+        .take(SYMBOL_SUGGESTION_PREFIX_CHARS)
+        .collect();
+
+    // every string with the prefix sorts at or after the prefix and before the prefix followed by
+    // the greatest code point, which allows the raw-name index to be used
+    let upper_bound = format!("{}{}", prefix, char::MAX);
+    let suggestions_opt = prepare_and_query_database(
+        db,
         "
             SELECT
-                sym.raw_name,
-                sym.friendly_name,
-                sym.dll_name,
-                sym.ordinal,
-                sym.is_meta_func,
-                dll.path,
-                dll.secondary_platform
+                raw_name,
+                friendly_name,
+                is_meta_func,
+                deprecated
             FROM
-                symbols sym
-                INNER JOIN symbol_dll_os sdo
-                    ON sdo.sym_id = sym.sym_id
-                INNER JOIN dlls dll
-                    ON dll.dll_id = sdo.dll_id
+                symbols
             WHERE
-                sdo.os_id = ?1
-                AND sym.is_meta_func = 0
+                raw_name >= ?1
+                AND raw_name < ?2
             ORDER BY
-                1 ASC NULLS LAST,
-                2 ASC NULLS LAST,
-                3,
-                4
+                raw_name
+            LIMIT ?3
         ",
-        [os_id],
-        |row| {
-            let symbol_part = SymbolPart::try_from_row(0, row)?;
-            let dll_part = DllPart::try_from_row(5, row)?;
-            Ok(OsSymbolPart {
-                dll: dll_part,
-                symbol: symbol_part,
-            })
-        },
+        (prefix, upper_bound, MAX_SYMBOL_SUGGESTIONS),
+        |row| SymbolPart::try_named_from_row(0, row),
     );
-    let symbol_rows = match symbol_rows_opt {
+    let mut suggestions = match suggestions_opt {
         None => return TemplateResponder::Failure,
-        Some(sr) => sr,
+        Some(v) => v,
     };
+    if suggestions.is_empty() {
+        // nothing shares the prefix; fall back to the names nearest by edit distance
+        suggestions = match find_nearest_symbols(db, sym_raw_name, None, None) {
+            None => return TemplateResponder::Failure,
+            Some(v) => v,
+        };
+    }
+    if suggestions.is_empty() {
+        return TemplateResponder::NotFound;
+    }
 
-    let template = OsSymbolListTemplate {
-        os,
-        symbols: symbol_rows,
+    let template = NotFoundSuggestionsTemplate {
+        path_to_root,
+        sym_raw_name: sym_raw_name.to_owned(),
+        suggestions,
     };
-    TemplateResponder::Template(template)
+    TemplateResponder::NotFoundExplained(template)
 }
 
-fn finish_dlls(db: &Connection, sym_id: i64, sym_part: SymbolPart, path_to_root: &'static str) -> TemplateResponder<SymbolTemplate> {
-    let dll_rows_opt = prepare_and_query_database(
-        &db,
+/// Finds the named symbols whose raw or friendly names are nearest to the given name by edit
+/// distance, ignoring case; no meta-functions though.
+///
+/// Only symbols whose raw names share the first [`NEAREST_SYMBOL_PREFIX_CHARS`] characters with
+/// the given name (ignoring case) are considered, and at most [`MAX_NEAREST_SYMBOL_CANDIDATES`] of
+/// them. If a DLL or operating system is given, only symbols which it contains are considered.
+fn find_nearest_symbols(db: &Connection, name: &str, dll_id: Option<i64>, os_id: Option<i64>) -> Option<Vec<SymbolPart>> {
+    let prefix: String = name
+        .chars()
+        .take(NEAREST_SYMBOL_PREFIX_CHARS)
+        .collect();
+    let upper_bound = format!("{}{}", prefix, char::MAX);
+    let candidates = prepare_and_query_database(
+        db,
         "
             SELECT
-                os.os_id,
-                os.short_name,
-                COALESCE(os.long_name, os.short_name),
-                os.has_icon,
-                dll.path,
-                dll.secondary_platform
+                raw_name,
+                friendly_name,
+                is_meta_func,
+                deprecated
             FROM
-                operating_systems os
-                INNER JOIN symbol_dll_os sdo
-                    ON sdo.os_id = os.os_id
-                INNER JOIN dlls dll
-                    ON dll.dll_id = sdo.dll_id
+                symbols
             WHERE
-                sdo.sym_id = ?1
+                raw_name COLLATE NOCASE >= ?1
+                AND raw_name COLLATE NOCASE < ?2
+                AND is_meta_func = 0
+                AND (
+                    (?4 IS NULL AND ?5 IS NULL)
+                    OR EXISTS (
+                        SELECT 1
+                        FROM symbol_dll_os sdo
+                        WHERE sdo.sym_id = symbols.sym_id
+                        AND (?4 IS NULL OR sdo.dll_id = ?4)
+                        AND (?5 IS NULL OR sdo.os_id = ?5)
+                    )
+                )
             ORDER BY
-                os.release_date ASC NULLS LAST,
-                3, 4
+                raw_name COLLATE NOCASE
+            LIMIT ?3
         ",
-        [sym_id],
-        |row| {
-            let os_id: i64 = row.get(0)?;
-            let os_part = OperatingSystemPart::try_from_row(1, row)?;
-            let dll_part = DllPart::try_from_row(4, row)?;
-            Ok((os_id, os_part, dll_part))
-        },
-    );
-    let dll_rows = match dll_rows_opt {
-        None => return TemplateResponder::Failure,
-        Some(sr) => sr,
-    };
+        (prefix, upper_bound, MAX_NEAREST_SYMBOL_CANDIDATES, dll_id, os_id),
+        |row| SymbolPart::try_named_from_row(0, row),
+    )?;
 
-    let mut id_to_os: BTreeMap<i64, OperatingSystemPart> = BTreeMap::new();
-    let mut os_id_to_dlls: BTreeMap<i64, Vec<DllPart>> = BTreeMap::new();
-    let mut os_id_ordered = Vec::new();
-    for (os_id, os, dll) in dll_rows {
-        id_to_os
-            .entry(os_id)
-            .or_insert_with(|| {
-                // also append ID to ordering vec
-                os_id_ordered.push(os_id);
+    let name_lower = name.to_lowercase();
+    let mut distances_and_symbols: Vec<(usize, SymbolPart)> = candidates
+        .into_iter()
+        .map(|symbol| {
+            let raw_distance = symbol.raw_name()
+                .map(|raw_name| levenshtein_distance(&name_lower, &raw_name.to_lowercase()))
+                .unwrap_or(usize::MAX);
+            let friendly_distance = symbol.friendly_name()
+                .map(|friendly_name| levenshtein_distance(&name_lower, &friendly_name.to_lowercase()))
+                .unwrap_or(usize::MAX);
+            (raw_distance.min(friendly_distance), symbol)
+        })
+        .collect();
+    distances_and_symbols.sort_unstable();
+    Some(
+        distances_and_symbols
+            .into_iter()
+            .take(MAX_NEAREST_SYMBOLS)
+            .map(|(_distance, symbol)| symbol)
+            .collect()
+    )
+}
 
-                os
-            });
-        os_id_to_dlls
-            .entry(os_id)
-            .or_insert_with(|| Vec::with_capacity(1))
-            .push(dll);
+/// Calculates the Levenshtein distance between two strings, i.e. the minimum number of characters
+/// that must be inserted, deleted or substituted to turn one into the other.
+fn levenshtein_distance(one: &str, other: &str) -> usize {
+    let other_chars: Vec<char> = other.chars().collect();
+
+    // the distances between the prefixes of one processed so far and each prefix of other
+    let mut previous_row: Vec<usize> = (0..=other_chars.len()).collect();
+    let mut current_row: Vec<usize> = vec![0; other_chars.len() + 1];
+    for (i, one_char) in one.chars().enumerate() {
+        current_row[0] = i + 1;
+        for (j, other_char) in other_chars.iter().enumerate() {
+            let substitution_cost = if one_char == *other_char { 0 } else { 1 };
+            current_row[j + 1] = (previous_row[j] + substitution_cost)
+                .min(previous_row[j + 1] + 1)
+                .min(current_row[j] + 1);
+        }
+        std::mem::swap(&mut previous_row, &mut current_row);
     }
+    previous_row[other_chars.len()]
+}
 
-    let mut os_dlls = Vec::with_capacity(id_to_os.len());
-    for id in os_id_ordered {
-        let os = id_to_os
-            .remove(&id)
-            .unwrap();
-        let dlls = os_id_to_dlls
-            .remove(&id)
-            .unwrap();
-        os_dlls.push((os, dlls));
-    }
+#[rocket::get("/api/symbol/<sym_raw_name>")]
+fn api_symbol(sym_raw_name: &str, route_span: RouteSpan, db_pool: &State<DbPool>) -> JsonResponder<SymbolApiPart> {
+    let _entered = route_span.enter();
+    let Some(db) = connect_to_database(db_pool)
+        else { return JsonResponder::Failure };
 
-    let template = SymbolTemplate {
-        path_to_root,
-        symbol: sym_part,
-        os_dlls,
+    let sym_info_rows_opt = query_named_symbol(&db, sym_raw_name);
+    let (sym_id, sym_part) = match sym_info_rows_opt {
+        None => return JsonResponder::Failure,
+        Some(v) if v.is_empty() => return JsonResponder::NotFound,
+        Some(mut v) => v.swap_remove(0),
     };
-    TemplateResponder::Template(template)
-}
 
-#[rocket::get("/symbol/<sym_raw_name>")]
-fn symbol_page(sym_raw_name: &str) -> TemplateResponder<SymbolTemplate> {
-    let Some(db) = connect_to_database()
-        else { return TemplateResponder::Failure };
+    let Some(os_dlls) = query_os_dlls(&db, sym_id)
+        else { return JsonResponder::Failure };
 
-    // does this symbol exist? what ID does it have?
-    let sym_info_rows_opt = prepare_and_query_database(
-        &db,
+    JsonResponder::Json(symbol_api_part(&sym_part, os_dlls))
+}
+
+/// Finds the named symbol with the given raw name, returning its ID and information.
+fn query_named_symbol(db: &Connection, sym_raw_name: &str) -> Option<Vec<(i64, SymbolPart)>> {
+    prepare_and_query_database(
+        db,
         "
             SELECT
                 sym_id,
                 raw_name,
                 friendly_name,
-                is_meta_func
+                is_meta_func,
+                deprecated
             FROM
                 symbols
             WHERE
@@ -754,19 +3394,25 @@ fn symbol_page(sym_raw_name: &str) -> TemplateResponder<SymbolTemplate> {
             let sym_part = SymbolPart::try_named_from_row(1, row)?;
             Ok((sym_id, sym_part))
         },
-    );
-    let (sym_id, sym_part) = match sym_info_rows_opt {
-        None => return TemplateResponder::Failure,
-        Some(v) if v.len() == 0 => return TemplateResponder::NotFound,
-        Some(mut v) => v.swap_remove(0),
-    };
-
-    finish_dlls(&db, sym_id, sym_part, "../")
+    )
 }
 
 #[rocket::get("/symbol/dll/<dll_name>/ordinal/<ordinal>")]
-fn dll_ordinal_symbol_page(dll_name: &str, ordinal: usize) -> TemplateResponder<SymbolTemplate> {
-    let Some(db) = connect_to_database()
+fn dll_ordinal_symbol_page(dll_name: &str, ordinal: &str, format: ResponseFormat, path_to_root: PathToRoot, freshness: Freshness, route_span: RouteSpan, db_pool: &State<DbPool>) -> Conditional<NegotiatedResponder<TemplateResponder<SymbolTemplate>, SymbolApiPart>> {
+    let _entered = route_span.enter();
+    freshness.respond(|| NegotiatedResponder::from_template(format, dll_ordinal_symbol_template(dll_name, ordinal, path_to_root, db_pool)))
+}
+
+fn dll_ordinal_symbol_template(dll_name: &str, ordinal: &str, path_to_root: PathToRoot, db_pool: &State<DbPool>) -> TemplateResponder<SymbolTemplate> {
+    // ordinals are stored as SQLite integers, i.e. they fit into an i64;
+    // anything else cannot be the ordinal of an existing symbol
+    let Ok(ordinal) = ordinal.parse::<u64>()
+        else { return TemplateResponder::NotFound };
+    if i64::try_from(ordinal).is_err() {
+        return TemplateResponder::NotFound;
+    }
+
+    let Some(db) = connect_to_database(db_pool)
         else { return TemplateResponder::Failure };
 
     // does this symbol exist? what ID does it have?
@@ -778,7 +3424,8 @@ fn dll_ordinal_symbol_page(dll_name: &str, ordinal: usize) -> TemplateResponder<
                 dll_name,
                 ordinal,
                 friendly_name,
-                is_meta_func
+                is_meta_func,
+                deprecated
             FROM
                 symbols
             WHERE
@@ -798,32 +3445,61 @@ fn dll_ordinal_symbol_page(dll_name: &str, ordinal: usize) -> TemplateResponder<
         Some(mut v) => v.swap_remove(0),
     };
 
-    finish_dlls(&db, sym_id, sym_part, "../../../../")
+    finish_dlls(&db, sym_id, sym_part, path_to_root.0)
 }
 
-#[rocket::get("/dll/<dll_name>")]
-fn dll_page(dll_name: &str) -> TemplateResponder<DllTemplate> {
-    let Some(db) = connect_to_database()
-        else { return TemplateResponder::Failure };
-
-    // does this DLL exist? what ID does it have?
-    let dll_info_rows_opt = prepare_and_query_database(
+/// Redirects from a short link identifying a symbol by its ID to the symbol's page.
+///
+/// Unlike the URL of the symbol's page, the short link does not depend on how the name of the
+/// symbol is encoded.
+#[rocket::get("/s/<sym_id>")]
+#[allow(clippy::result_large_err)] // the error is a responder handed straight to Rocket
+fn symbol_permalink(sym_id: i64, route_span: RouteSpan, db_pool: &State<DbPool>) -> Result<Redirect, TextResponder> {
+    let _entered = route_span.enter();
+    let Some(db) = connect_to_database(db_pool)
+        else { return Err(TextResponder::Failure) };
+
+    let sym_rows_opt = prepare_and_query_database(
         &db,
         "
             SELECT
-                dll_id, path, secondary_platform
+                raw_name,
+                friendly_name,
+                dll_name,
+                ordinal,
+                is_meta_func,
+                deprecated
             FROM
-                dlls
+                symbols
             WHERE
-                path = ?1
+                sym_id = ?1
         ",
-        [dll_name],
-        |row| {
-            let dll_id: i64 = row.get(0)?;
-            let dll_part = DllPart::try_from_row(1, row)?;
-            Ok((dll_id, dll_part))
-        },
+        [sym_id],
+        |row| SymbolPart::try_from_row(0, row),
     );
+    let symbol = match sym_rows_opt {
+        None => return Err(TextResponder::Failure),
+        Some(v) if v.is_empty() => return Err(TextResponder::NotFound),
+        Some(mut v) => v.swap_remove(0),
+    };
+
+    // we are at s/<sym_id>; the symbol's page is relative to the root
+    Ok(Redirect::moved(format!("../{}", symbol.url_path())))
+}
+
+#[rocket::get("/dll/<dll_name>?<sort>&<limit>")]
+fn dll_page(dll_name: &str, sort: Option<SymbolSort>, limit: Option<u64>, path_to_root: PathToRoot, route_span: RouteSpan, config: &State<WebConfig>, db_pool: &State<DbPool>) -> StreamedTemplateResponder<DllTemplate> {
+    let _entered = route_span.enter();
+    let row_count = row_limit(limit, config.max_rows, config.max_rows);
+    StreamedTemplateResponder(dll_template(dll_name, sort.unwrap_or_default(), row_count, path_to_root, db_pool))
+}
+
+fn dll_template(dll_name: &str, sort: SymbolSort, row_count: u64, path_to_root: PathToRoot, db_pool: &State<DbPool>) -> TemplateResponder<DllTemplate> {
+    let Some(db) = connect_to_database(db_pool)
+        else { return TemplateResponder::Failure };
+
+    // does this DLL exist? what ID does it have?
+    let dll_info_rows_opt = query_dll_by_path(&db, dll_name);
     let (dll_id, dll_part) = match dll_info_rows_opt {
         None => return TemplateResponder::Failure,
         Some(v) if v.len() == 0 => return TemplateResponder::NotFound,
@@ -837,7 +3513,8 @@ fn dll_page(dll_name: &str) -> TemplateResponder<DllTemplate> {
             SELECT
                 os.short_name,
                 os.long_name,
-                os.has_icon
+                os.has_icon,
+                os.build_number
             FROM
                 operating_systems os
             WHERE
@@ -848,7 +3525,7 @@ fn dll_page(dll_name: &str) -> TemplateResponder<DllTemplate> {
                     AND sdo.os_id = os.os_id
                 )
             ORDER BY
-                os.release_date ASC NULLS LAST
+                os.sort_order ASC
         ",
         [dll_id],
         |row| OperatingSystemPart::try_from_row(0, row),
@@ -858,84 +3535,134 @@ fn dll_page(dll_name: &str) -> TemplateResponder<DllTemplate> {
         None => return TemplateResponder::Failure,
     };
 
+    // (the ordinal of a symbol may differ between operating systems; take the lowest one)
+    let order = match sort {
+        SymbolSort::Friendly => "COALESCE(sym.friendly_name, sym.raw_name) ASC NULLS LAST, sym.dll_name, sym.ordinal",
+        SymbolSort::Raw => "sym.raw_name ASC NULLS LAST, sym.friendly_name ASC NULLS LAST, sym.dll_name, sym.ordinal",
+        SymbolSort::Dll => "MIN(sdo.ordinal) ASC NULLS LAST, sym.raw_name ASC NULLS LAST, sym.dll_name, sym.ordinal",
+    };
+
     // find the symbols in the DLL, named or ordinal;
     // meta-functions last
+    // (fetch one more than the row limit to find out whether the symbols have been truncated)
     let syms_opt = prepare_and_query_database(
+        &db,
+        &format!(
+            "
+                SELECT
+                    sym.sym_id,
+                    sym.raw_name,
+                    sym.friendly_name,
+                    sym.dll_name,
+                    sym.ordinal,
+                    sym.is_meta_func,
+                    sym.deprecated,
+                    COUNT(DISTINCT sdo.ordinal) > 1
+                FROM
+                    dlls d
+                    INNER JOIN symbol_dll_os sdo
+                        ON sdo.dll_id = d.dll_id
+                    INNER JOIN symbols sym
+                        ON sym.sym_id = sdo.sym_id
+                WHERE
+                    d.dll_id = ?1
+                GROUP BY
+                    sym.sym_id
+                ORDER BY
+                    sym.is_meta_func,
+                    {}
+                LIMIT ?2
+            ",
+            order,
+        ),
+        (dll_id, row_count.saturating_add(1)),
+        |row| {
+            let sym_id: i64 = row.get(0)?;
+            let sym_part = SymbolPart::try_from_row(1, row)?;
+            let ordinal_varies: bool = row.get(7)?;
+            Ok((sym_id, sym_part, ordinal_varies))
+        },
+    );
+    let Some(mut syms) = syms_opt
+        else { return TemplateResponder::Failure };
+    let truncated = exceeds_row_limit(&mut syms, row_count);
+
+    // find the operating systems of all the symbols at once
+    // (in any DLL, not only this one)
+    let sym_oses_opt = prepare_and_query_database(
         &db,
         "
             SELECT DISTINCT
-                sym.sym_id,
-                sym.raw_name,
-                sym.friendly_name,
-                sym.dll_name,
-                sym.ordinal,
-                sym.is_meta_func
+                sdo.sym_id,
+                os.short_name,
+                COALESCE(os.long_name, os.short_name),
+                os.has_icon,
+                os.build_number,
+                os.release_date
             FROM
-                dlls d
+                operating_systems os
                 INNER JOIN symbol_dll_os sdo
-                    ON sdo.dll_id = d.dll_id
-                INNER JOIN symbols sym
-                    ON sym.sym_id = sdo.sym_id
+                    ON sdo.os_id = os.os_id
             WHERE
-                d.dll_id = ?1
+                sdo.sym_id IN (
+                    SELECT dll_sdo.sym_id
+                    FROM symbol_dll_os dll_sdo
+                    WHERE dll_sdo.dll_id = ?1
+                )
             ORDER BY
-                6,
-                2 ASC NULLS LAST,
-                3 ASC NULLS LAST,
-                4,
-                5
+                os.sort_order ASC,
+                3
         ",
         [dll_id],
         |row| {
             let sym_id: i64 = row.get(0)?;
-            let sym_part = SymbolPart::try_from_row(1, row)?;
-            Ok((sym_id, sym_part))
+            let os_part = OperatingSystemPart::try_from_row(1, row)?;
+            Ok((sym_id, os_part))
         },
     );
-    let Some(syms) = syms_opt
+    let Some(sym_oses) = sym_oses_opt
         else { return TemplateResponder::Failure };
 
-    // find the operating systems per symbol
-    const OS_QUERY: &'static str = "
-        SELECT DISTINCT
-            os.short_name,
-            COALESCE(os.long_name, os.short_name),
-            has_icon
-        FROM
-            operating_systems os
-            INNER JOIN symbol_dll_os sdo
-                ON sdo.os_id = os.os_id
-        WHERE
-            sdo.sym_id = ?1
-        ORDER BY
-            os.release_date ASC NULLS LAST
-    ";
-    let Some(mut os_statement) = prepare(&db, OS_QUERY)
-        else { return TemplateResponder::Failure };
+    let mut sym_id_to_oses: BTreeMap<i64, Vec<OperatingSystemPart>> = BTreeMap::new();
+    for (sym_id, os) in sym_oses {
+        sym_id_to_oses
+            .entry(sym_id)
+            .or_default()
+            .push(os);
+    }
 
-    let mut symbols_oses = Vec::with_capacity(syms.len());
-    for (sym_id, sym_part) in syms {
-        let oses_opt = query_database(
-            &mut os_statement,
-            [sym_id],
-            |row| OperatingSystemPart::try_from_row(0, row),
-        );
-        let Some(oses) = oses_opt
-            else { return TemplateResponder::Failure };
-        symbols_oses.push((sym_part, oses));
+    let mut symbols = Vec::with_capacity(syms.len());
+    for (sym_id, symbol, ordinal_varies) in syms {
+        let oses = sym_id_to_oses
+            .remove(&sym_id)
+            .unwrap_or_default();
+        symbols.push(DllSymbolPart {
+            symbol,
+            oses,
+            ordinal_varies,
+        });
     }
 
     let template = DllTemplate {
+        path_to_root: path_to_root.0,
         dll: dll_part,
         dll_operating_systems: dll_oses,
-        symbols_oses,
+        index: ListIndex::from_symbols(symbols.iter().map(|dll_symbol| &dll_symbol.symbol), sort),
+        symbols,
+        sort,
+        truncated,
     };
     TemplateResponder::Template(template)
 }
 
 #[rocket::get("/dlls/<dll_prefix>")]
-fn alpha_dll_page(dll_prefix: &str) -> TemplateResponder<AlphabeticalDllListTemplate> {
-    let Some(db) = connect_to_database()
+fn alpha_dll_page(dll_prefix: &str, path_to_root: PathToRoot, freshness: Freshness, route_span: RouteSpan, db_pool: &State<DbPool>) -> Conditional<TemplateResponder<AlphabeticalDllListTemplate>> {
+    let _entered = route_span.enter();
+    freshness.respond(|| alpha_dll_template(dll_prefix, path_to_root, db_pool))
+}
+
+fn alpha_dll_template(dll_prefix: &str, path_to_root: PathToRoot, db_pool: &State<DbPool>) -> TemplateResponder<AlphabeticalDllListTemplate> {
+    let Some(db) = connect_to_database(db_pool)
         else { return TemplateResponder::Failure };
 
     let prefix_len_chars = dll_prefix.chars().count();
@@ -977,7 +3704,8 @@ fn alpha_dll_page(dll_prefix: &str) -> TemplateResponder<AlphabeticalDllListTemp
         SELECT
             os.short_name,
             os.long_name,
-            os.has_icon
+            os.has_icon,
+            os.build_number
         FROM
             operating_systems os
         WHERE
@@ -988,7 +3716,7 @@ fn alpha_dll_page(dll_prefix: &str) -> TemplateResponder<AlphabeticalDllListTemp
                 AND sdo.dll_id = ?1
             )
         ORDER BY
-            os.release_date ASC NULLS LAST
+            os.sort_order ASC
     ";
     let Some(mut dll_os_query) = prepare(&db, DLL_OS_QUERY)
         else { return TemplateResponder::Failure };
@@ -1010,103 +3738,438 @@ fn alpha_dll_page(dll_prefix: &str) -> TemplateResponder<AlphabeticalDllListTemp
         .collect();
 
     let template = AlphabeticalDllListTemplate {
+        path_to_root: path_to_root.0,
         dll_parts,
     };
     TemplateResponder::Template(template)
 }
 
-#[rocket::get("/funcs/<sym_raw_prefix>")]
-fn funcs_page(sym_raw_prefix: &str) -> TemplateResponder<AlphabeticalSymbolListTemplate> {
-    let Some(db) = connect_to_database()
+/// Renders the page listing the symbols whose raw or friendly names start with the given prefix.
+///
+/// A prefix matching no symbols results in an empty list (explained by a hint) and not in a 404.
+///
+/// Deprecated symbols are omitted if `hide_deprecated` is true.
+#[rocket::get("/funcs/<sym_raw_prefix>?<page>&<per_page>&<hide_deprecated>&<limit>&<after>")]
+#[allow(clippy::too_many_arguments)] // Rocket passes each parameter and guard separately
+fn funcs_page(sym_raw_prefix: &str, page: Option<u64>, per_page: Option<u64>, hide_deprecated: Option<bool>, limit: Option<u64>, after: Option<&str>, path_to_root: PathToRoot, freshness: Freshness, route_span: RouteSpan, config: &State<WebConfig>, db_pool: &State<DbPool>) -> Conditional<TemplateResponder<AlphabeticalSymbolListTemplate>> {
+    let _entered = route_span.enter();
+    freshness.respond(|| funcs_template(sym_raw_prefix, page, per_page, hide_deprecated, limit, after, path_to_root, config, db_pool))
+}
+
+#[allow(clippy::too_many_arguments)]
+fn funcs_template(sym_raw_prefix: &str, page: Option<u64>, per_page: Option<u64>, hide_deprecated: Option<bool>, limit: Option<u64>, after: Option<&str>, path_to_root: PathToRoot, config: &State<WebConfig>, db_pool: &State<DbPool>) -> TemplateResponder<AlphabeticalSymbolListTemplate> {
+    let hide_deprecated = hide_deprecated.unwrap_or(false);
+
+    if is_mangled_query_too_short(sym_raw_prefix, MIN_MANGLED_PREFIX_CHARS) {
+        let template = AlphabeticalSymbolListTemplate {
+            path_to_root: path_to_root.0,
+            symbols: Vec::new(),
+            index: ListIndex::default(),
+            hint: Some(MANGLED_QUERY_TOO_SHORT_HINT),
+            pagination: None,
+            hide_deprecated: None,
+            truncated: false,
+        };
+        return TemplateResponder::Template(template);
+    }
+    if let Some(a) = after && !NAMED_SYMBOL_KEY.is_valid_token(a) {
+        let template = AlphabeticalSymbolListTemplate {
+            path_to_root: path_to_root.0,
+            symbols: Vec::new(),
+            index: ListIndex::default(),
+            hint: Some(INVALID_AFTER_HINT),
+            pagination: None,
+            hide_deprecated: None,
+            truncated: false,
+        };
+        return TemplateResponder::BadRequest(template);
+    }
+
+    let Some(db) = connect_to_database(db_pool)
+        else { return TemplateResponder::Failure };
+
+    let prefix_len_chars = sym_raw_prefix.chars().count();
+
+    // count the symbols with that raw-name prefix;
+    // no meta-functions though
+    let count_rows_opt = prepare_and_query_database(
+        &db,
+        "
+            SELECT
+                COUNT(*)
+            FROM
+                symbols
+            WHERE
+                raw_name IS NOT NULL
+                AND (
+                    SUBSTR(raw_name, 1, ?1) = ?2
+                    OR SUBSTR(friendly_name, 1, ?1) = ?2
+                )
+                AND is_meta_func = 0
+                AND (NOT ?3 OR deprecated = 0)
+        ",
+        (prefix_len_chars, sym_raw_prefix, hide_deprecated),
+        |row| row.get(0),
+    );
+    let total_items: u64 = match count_rows_opt {
+        None => return TemplateResponder::Failure,
+        Some(v) if v.is_empty() || v[0] == 0 => return empty_prefix_page(path_to_root),
+        Some(v) => v[0],
+    };
+
+    // only the symbols within the row limit are reachable through the pages
+    let row_count = row_limit(limit, config.max_rows, config.max_rows);
+    let truncated = total_items > row_count;
+    let mut pagination = Pagination::new(page, per_page, DEFAULT_SYMBOLS_PER_PAGE, total_items.min(row_count));
+    if hide_deprecated {
+        pagination = pagination.with_extra_params(&[("hide_deprecated", "true")]);
+    }
+    if limit.is_some() {
+        pagination = pagination.with_extra_param("limit", row_count.to_string());
+    }
+    pagination.after = after.map(|a| a.to_owned());
+
+    // find the symbols on this page
+    let (query_limit, query_offset) = pagination.query_limit_offset();
+    let sym_info_rows_opt = prepare_and_query_database(
+        &db,
+        &format!(
+            "
+                SELECT
+                    raw_name,
+                    friendly_name,
+                    is_meta_func,
+                    deprecated,
+                    {}
+                FROM
+                    symbols
+                WHERE
+                    raw_name IS NOT NULL
+                    AND (
+                        SUBSTR(raw_name, 1, ?1) = ?2
+                        OR SUBSTR(friendly_name, 1, ?1) = ?2
+                    )
+                    AND is_meta_func = 0
+                    AND (NOT ?5 OR deprecated = 0)
+                    AND {}
+                ORDER BY
+                    {}
+                LIMIT ?3 OFFSET ?4
+            ",
+            NAMED_SYMBOL_KEY.token_expression(),
+            NAMED_SYMBOL_KEY.after_condition(6),
+            NAMED_SYMBOL_KEY.order_by(),
+        ),
+        (prefix_len_chars, sym_raw_prefix, query_limit, query_offset, hide_deprecated, after),
+        |row| {
+            let symbol = SymbolPart::try_named_from_row(0, row)?;
+            let token: String = row.get(4)?;
+            Ok((symbol, token))
+        },
+    );
+    let symbols = match sym_info_rows_opt {
+        None => return TemplateResponder::Failure,
+        Some(v) => pagination.finish_page(v),
+    };
+
+    let template = AlphabeticalSymbolListTemplate {
+        path_to_root: path_to_root.0,
+        index: ListIndex::from_symbols(symbols.iter(), SymbolSort::Raw),
+        symbols,
+        hint: None,
+        pagination: Some(pagination),
+        hide_deprecated: Some(hide_deprecated),
+        truncated,
+    };
+    TemplateResponder::Template(template)
+}
+
+/// Renders the page listing the ordinal-only symbols of the DLLs whose file names (without the
+/// directory) start with the given prefix, matching the buckets on the root page.
+///
+/// A prefix matching no symbols results in an empty list (explained by a hint) and not in a 404.
+#[rocket::get("/funcs/ordinal-only/<dll_name_prefix>?<page>&<per_page>")]
+fn ordinal_only_funcs_page(dll_name_prefix: &str, page: Option<u64>, per_page: Option<u64>, path_to_root: PathToRoot, freshness: Freshness, route_span: RouteSpan, db_pool: &State<DbPool>) -> Conditional<TemplateResponder<AlphabeticalSymbolListTemplate>> {
+    let _entered = route_span.enter();
+    freshness.respond(|| ordinal_only_funcs_template(dll_name_prefix, page, per_page, path_to_root, db_pool))
+}
+
+fn ordinal_only_funcs_template(dll_name_prefix: &str, page: Option<u64>, per_page: Option<u64>, path_to_root: PathToRoot, db_pool: &State<DbPool>) -> TemplateResponder<AlphabeticalSymbolListTemplate> {
+    let Some(db) = connect_to_database(db_pool)
+        else { return TemplateResponder::Failure };
+
+    let dll_name_prefix_len = dll_name_prefix.chars().count();
+
+    // count the ordinal-only symbols
+    let count_rows_opt = prepare_and_query_database(
+        &db,
+        "
+            SELECT
+                COUNT(*)
+            FROM
+                symbols
+            WHERE
+                dll_name IS NOT NULL
+                AND SUBSTR(dll_name, LENGTH(RTRIM(dll_name, REPLACE(dll_name, '\\', ''))) + 1, ?1) = ?2
+                AND ordinal IS NOT NULL
+        ",
+        (dll_name_prefix_len, dll_name_prefix),
+        |row| row.get(0),
+    );
+    let total_items: u64 = match count_rows_opt {
+        None => return TemplateResponder::Failure,
+        Some(v) if v.is_empty() || v[0] == 0 => return empty_prefix_page(path_to_root),
+        Some(v) => v[0],
+    };
+    let pagination = Pagination::new(page, per_page, DEFAULT_SYMBOLS_PER_PAGE, total_items);
+
+    // find the ordinal-only symbols on this page
+    let sym_info_rows_opt = prepare_and_query_database(
+        &db,
+        "
+            SELECT
+                dll_name,
+                ordinal,
+                friendly_name,
+                is_meta_func,
+                deprecated
+            FROM
+                symbols
+            WHERE
+                dll_name IS NOT NULL
+                AND SUBSTR(dll_name, LENGTH(RTRIM(dll_name, REPLACE(dll_name, '\\', ''))) + 1, ?1) = ?2
+                AND ordinal IS NOT NULL
+            ORDER BY
+                3 ASC NULLS LAST, 1, 2
+            LIMIT ?3 OFFSET ?4
+        ",
+        (dll_name_prefix_len, dll_name_prefix, pagination.per_page, pagination.offset()),
+        |row| SymbolPart::try_ordinal_from_row(0, row),
+    );
+    let symbols = match sym_info_rows_opt {
+        None => return TemplateResponder::Failure,
+        Some(v) => v,
+    };
+
+    let template = AlphabeticalSymbolListTemplate {
+        path_to_root: path_to_root.0,
+        index: ListIndex::from_symbols(symbols.iter(), SymbolSort::Raw),
+        symbols,
+        hint: None,
+        pagination: Some(pagination),
+        hide_deprecated: None,
+        truncated: false,
+    };
+    TemplateResponder::Template(template)
+}
+
+/// Renders an alphabetical symbol list stating that no symbols match the requested prefix.
+fn empty_prefix_page(path_to_root: PathToRoot) -> TemplateResponder<AlphabeticalSymbolListTemplate> {
+    let template = AlphabeticalSymbolListTemplate {
+        path_to_root: path_to_root.0,
+        symbols: Vec::new(),
+        index: ListIndex::default(),
+        hint: Some(NO_SYMBOLS_FOR_PREFIX_HINT),
+        pagination: None,
+        hide_deprecated: None,
+        truncated: false,
+    };
+    TemplateResponder::Template(template)
+}
+
+/// Searches for symbols whose raw or friendly name contains the query.
+///
+/// The search may be restricted to the symbols exported by the DLL with the path `dll` and/or to
+/// those available in the operating system with the short name `os`; a DLL or operating system
+/// that does not exist results in a 404.
+#[rocket::get("/search?<q>&<dll>&<os>&<limit>")]
+#[allow(clippy::too_many_arguments)] // Rocket passes each parameter and guard separately
+fn search_page(q: Option<&str>, dll: Option<&str>, os: Option<&str>, limit: Option<u64>, path_to_root: PathToRoot, freshness: Freshness, route_span: RouteSpan, config: &State<WebConfig>, db_pool: &State<DbPool>, os_cache: &State<OsCache>) -> Conditional<TemplateResponder<SearchTemplate>> {
+    let _entered = route_span.enter();
+    freshness.respond(|| search_template(q, dll, os, limit, path_to_root, config, db_pool, os_cache))
+}
+
+#[allow(clippy::too_many_arguments)]
+fn search_template(q: Option<&str>, dll: Option<&str>, os: Option<&str>, limit: Option<u64>, path_to_root: PathToRoot, config: &State<WebConfig>, db_pool: &State<DbPool>, os_cache: &State<OsCache>) -> TemplateResponder<SearchTemplate> {
+    let q = q.unwrap_or("").trim();
+    let dll = dll.filter(|d| !d.is_empty());
+    let os = os.filter(|o| !o.is_empty());
+
+    let Some(db) = connect_to_database(db_pool)
         else { return TemplateResponder::Failure };
 
-    let prefix_len_chars = sym_raw_prefix.chars().count();
+    // restrict the search to the given DLL and operating system, if any
+    let (dll_id, dll_part) = match dll {
+        None => (None, None),
+        Some(dll_path) => match query_dll_by_path(&db, dll_path) {
+            None => return TemplateResponder::Failure,
+            Some(v) if v.is_empty() => return TemplateResponder::NotFound,
+            Some(mut v) => {
+                let (dll_id, dll_part) = v.swap_remove(0);
+                (Some(dll_id), Some(dll_part))
+            },
+        },
+    };
+    let (os_id, os_part) = match os {
+        None => (None, None),
+        Some(os_name) => match query_os_by_short_name(&db, os_cache, os_name) {
+            None => return TemplateResponder::Failure,
+            Some(v) if v.is_empty() => return TemplateResponder::NotFound,
+            Some(mut v) => {
+                let (os_id, os_part) = v.swap_remove(0);
+                (Some(os_id), Some(os_part))
+            },
+        },
+    };
+
+    let query_hint = if q.is_empty() {
+        Some(EMPTY_SEARCH_HINT)
+    } else if is_mangled_query_too_short(q, MIN_MANGLED_SEARCH_CHARS) {
+        Some(MANGLED_QUERY_TOO_SHORT_HINT)
+    } else {
+        None
+    };
+    if let Some(hint) = query_hint {
+        let template = SearchTemplate {
+            path_to_root: path_to_root.0,
+            query: q.to_owned(),
+            dll: dll_part,
+            os: os_part,
+            symbols: Vec::new(),
+            nearest_symbols: Vec::new(),
+            hint: Some(hint),
+            truncated: false,
+        };
+        return TemplateResponder::BadRequest(template);
+    }
 
-    // find the symbols with that raw-name prefix;
+    // find the symbols containing the query in their raw or friendly name;
     // no meta-functions though
+    // (fetch one more than the row limit to find out whether the results have been truncated)
+    let row_count = row_limit(limit, DEFAULT_SEARCH_RESULTS, config.max_rows);
     let sym_info_rows_opt = prepare_and_query_database(
         &db,
         "
             SELECT
                 raw_name,
                 friendly_name,
-                is_meta_func
+                dll_name,
+                ordinal,
+                is_meta_func,
+                deprecated
             FROM
                 symbols
             WHERE
-                raw_name IS NOT NULL
-                AND (
-                    SUBSTR(raw_name, 1, ?1) = ?2
-                    OR SUBSTR(friendly_name, 1, ?1) = ?2
+                (
+                    raw_name LIKE '%' || ?1 || '%' ESCAPE '\\'
+                    OR friendly_name LIKE '%' || ?1 || '%' ESCAPE '\\'
                 )
                 AND is_meta_func = 0
+                AND (
+                    (?3 IS NULL AND ?4 IS NULL)
+                    OR EXISTS (
+                        SELECT 1
+                        FROM symbol_dll_os sdo
+                        WHERE sdo.sym_id = symbols.sym_id
+                        AND (?3 IS NULL OR sdo.dll_id = ?3)
+                        AND (?4 IS NULL OR sdo.os_id = ?4)
+                    )
+                )
             ORDER BY
-                raw_name
+                1 ASC NULLS LAST,
+                2 ASC NULLS LAST,
+                3,
+                4
+            LIMIT ?2
         ",
-        (prefix_len_chars, sym_raw_prefix),
-        |row| SymbolPart::try_named_from_row(0, row),
+        (escape_like(q), row_count.saturating_add(1), dll_id, os_id),
+        |row| SymbolPart::try_from_row(0, row),
     );
-    let symbols = match sym_info_rows_opt {
+    let mut symbols = match sym_info_rows_opt {
         None => return TemplateResponder::Failure,
-        Some(v) if v.len() == 0 => return TemplateResponder::NotFound,
         Some(v) => v,
     };
+    let truncated = exceeds_row_limit(&mut symbols, row_count);
 
-    let template = AlphabeticalSymbolListTemplate {
-        path_to_root: "../",
+    // nothing contains the query; perhaps it is a misspelling of an existing name
+    let nearest_symbols = if symbols.is_empty() {
+        match find_nearest_symbols(&db, q, dll_id, os_id) {
+            None => return TemplateResponder::Failure,
+            Some(v) => v,
+        }
+    } else {
+        Vec::new()
+    };
+
+    let template = SearchTemplate {
+        path_to_root: path_to_root.0,
+        query: q.to_owned(),
+        dll: dll_part,
+        os: os_part,
         symbols,
+        nearest_symbols,
+        hint: None,
+        truncated,
     };
     TemplateResponder::Template(template)
 }
 
-#[rocket::get("/funcs/ordinal-only/<dll_path_prefix>")]
-fn ordinal_only_funcs_page(dll_path_prefix: &str) -> TemplateResponder<AlphabeticalSymbolListTemplate> {
-    let Some(db) = connect_to_database()
-        else { return TemplateResponder::Failure };
+#[rocket::get("/api/autocomplete?<q>&<limit>")]
+fn api_autocomplete(q: Option<&str>, limit: Option<u64>, route_span: RouteSpan, db_pool: &State<DbPool>) -> JsonResponder<AutocompleteApiPart> {
+    let _entered = route_span.enter();
+    let q = q.unwrap_or("");
+    let limit = limit
+        .unwrap_or(DEFAULT_AUTOCOMPLETE_LIMIT)
+        .min(MAX_AUTOCOMPLETE_LIMIT);
+    if q.is_empty() || is_mangled_query_too_short(q, MIN_MANGLED_SEARCH_CHARS) || limit == 0 {
+        return JsonResponder::Json(AutocompleteApiPart::default());
+    }
 
-    let dll_path_prefix_len = dll_path_prefix.chars().count();
+    let Some(db) = connect_to_database(db_pool)
+        else { return JsonResponder::Failure };
 
-    // find the ordinal-only symbols
-    let sym_info_rows_opt = prepare_and_query_database(
+    // every string with the prefix q sorts at or after q and before q followed by the greatest
+    // code point, which allows the display-name index to be used
+    let upper_bound = format!("{}{}", q, char::MAX);
+    let names_opt = prepare_and_query_database(
         &db,
         "
-            SELECT
-                dll_name,
-                ordinal,
-                friendly_name,
-                is_meta_func
+            SELECT DISTINCT
+                COALESCE(friendly_name, raw_name)
             FROM
                 symbols
             WHERE
-                dll_name IS NOT NULL
-                AND SUBSTR(dll_name, 1, ?1) = ?2
-                AND ordinal IS NOT NULL
+                COALESCE(friendly_name, raw_name) >= ?1
+                AND COALESCE(friendly_name, raw_name) < ?2
+                AND is_meta_func = 0
             ORDER BY
-                3 ASC NULLS LAST, 1, 2
+                1
+            LIMIT ?3
         ",
-        (dll_path_prefix_len, dll_path_prefix),
-        |row| SymbolPart::try_ordinal_from_row(0, row),
+        (q, upper_bound, limit),
+        |row| row.get(0),
     );
-    let symbols = match sym_info_rows_opt {
-        None => return TemplateResponder::Failure,
-        Some(v) if v.len() == 0 => return TemplateResponder::NotFound,
-        Some(v) => v,
-    };
-
-    let template = AlphabeticalSymbolListTemplate {
-        path_to_root: "../../",
-        symbols,
-    };
-    TemplateResponder::Template(template)
+    match names_opt {
+        None => JsonResponder::Failure,
+        Some(names) => JsonResponder::Json(AutocompleteApiPart(names)),
+    }
 }
 
-#[rocket::get("/compare-os?<old>&<new>")]
-fn compare_os_redirect(old: &str, new: &str) -> Redirect {
+#[rocket::get("/compare-os?<old>&<new>&<merge_editions>")]
+fn compare_os_redirect(old: &str, new: &str, merge_editions: Option<bool>) -> Redirect {
     // construct a permanent redirect to our preferred URL
 
     let old_percent: String = utf8_percent_encode(old, &URL_UNRESERVED).collect();
+    if old == new {
+        // there is nothing to compare; show the operating system instead
+        return Redirect::to(format!("os/{}", old_percent));
+    }
+
     let new_percent: String = utf8_percent_encode(new, &URL_UNRESERVED).collect();
-    let new_url = format!("os/{}/compare/{}", old_percent, new_percent);
+    let mut new_url = format!("os/{}/compare/{}", old_percent, new_percent);
+    if merge_editions == Some(true) {
+        new_url.push_str("?merge_editions=true");
+    }
 
     Redirect::permanent(new_url)
 }
@@ -1123,78 +4186,137 @@ fn compare_os_dll_redirect(old: &str, new: &str, dll: &str) -> Redirect {
     Redirect::permanent(new_url)
 }
 
-#[rocket::get("/os/<old>/compare/<new>")]
-fn compare_os(old: &str, new: &str) -> TemplateResponder<CompareOsTemplate> {
-    let Some(db) = connect_to_database()
+#[rocket::get("/os/<old>/compare-dlls/<new>?<merge_editions>")]
+#[allow(clippy::too_many_arguments)] // Rocket passes each parameter and guard separately
+fn compare_dlls(old: &str, new: &str, merge_editions: Option<bool>, path_to_root: PathToRoot, freshness: Freshness, route_span: RouteSpan, db_pool: &State<DbPool>, os_cache: &State<OsCache>) -> Conditional<TemplateResponder<CompareDllsTemplate>> {
+    let _entered = route_span.enter();
+    freshness.respond(|| compare_dlls_template(old, new, merge_editions, path_to_root, db_pool, os_cache))
+}
+
+fn compare_dlls_template(old: &str, new: &str, merge_editions: Option<bool>, path_to_root: PathToRoot, db_pool: &State<DbPool>, os_cache: &State<OsCache>) -> TemplateResponder<CompareDllsTemplate> {
+    let Some(db) = connect_to_database(db_pool)
         else { return TemplateResponder::Failure };
+    let merge_editions = merge_editions.unwrap_or(false);
 
-    const FIND_OS_QUERY: &str = "
-        SELECT
-            os_id,
-            short_name,
-            COALESCE(long_name, short_name),
-            has_icon
-        FROM
-            operating_systems
-        WHERE
-            short_name = ?1
-    ";
-    let Some(mut find_os_stmt) = prepare(&db, FIND_OS_QUERY)
+    let ((old_os_id, old_os_part), (new_os_id, new_os_part)) = match find_compared_oses(&db, os_cache, old, new) {
+        Ok(oses) => oses,
+        Err(responder) => return responder,
+    };
+
+    let Some(mut dll_diff_stmt) = prepare(&db, OS_DLL_DIFF_QUERY)
+        else { return TemplateResponder::Failure };
+
+    // find DLLs which are in old but not in new
+    let removed_dlls_opt = query_database(
+        &mut dll_diff_stmt,
+        (old_os_id, new_os_id, merge_editions),
+        |row| DllPart::try_from_row(0, row),
+    );
+    let Some(removed_dlls) = removed_dlls_opt
+        else { return TemplateResponder::Failure };
+
+    // find DLLs which are in new but not in old
+    let added_dlls_opt = query_database(
+        &mut dll_diff_stmt,
+        (new_os_id, old_os_id, merge_editions),
+        |row| DllPart::try_from_row(0, row),
+    );
+    let Some(added_dlls) = added_dlls_opt
         else { return TemplateResponder::Failure };
 
-    let os_ify = |row: &Row<'_>| {
-        let os_id: i64 = row.get(0)?;
-        let os_part = OperatingSystemPart::try_from_row(1, row)?;
-        Ok((os_id, os_part))
+    let template = CompareDllsTemplate {
+        path_to_root: path_to_root.0,
+        old_os: old_os_part,
+        new_os: new_os_part,
+        merge_editions,
+        added_dlls,
+        removed_dlls,
+    };
+    TemplateResponder::Template(template)
+}
+
+#[rocket::get("/os/<old>/compare/<new>?<merge_editions>&<include_common>", rank = 2)]
+#[allow(clippy::too_many_arguments)] // Rocket passes each parameter and guard separately
+fn compare_os(old: &str, new: &str, merge_editions: Option<bool>, include_common: Option<bool>, format: ResponseFormat, uri: &Origin<'_>, path_to_root: PathToRoot, route_span: RouteSpan, db_pool: &State<DbPool>, os_cache: &State<OsCache>) -> OsPageResponder<NegotiatedResponder<StreamedTemplateResponder<CompareOsTemplate>, CompareOsApiPart>> {
+    let _entered = route_span.enter();
+    let negotiate = |template| NegotiatedResponder::from_template(format, template)
+        .map_html(StreamedTemplateResponder);
+    let Some(db) = connect_to_database(db_pool)
+        else { return OsPageResponder::Page(negotiate(TemplateResponder::Failure)) };
+    let old_os = match resolve_page_os(&db, os_cache, uri, 1, old) {
+        Ok(os) => os,
+        Err(responder) => return responder.map(negotiate),
+    };
+    let new_os = match resolve_page_os(&db, os_cache, uri, 3, new) {
+        Ok(os) => os,
+        Err(responder) => return responder.map(negotiate),
     };
+    OsPageResponder::Page(negotiate(compare_os_template(&db, old_os, new_os, merge_editions.unwrap_or(false), include_common.unwrap_or(false), path_to_root.0)))
+}
+
+/// Drops the symbols that are neither named nor have an ordinal from a comparison, logging each.
+///
+/// Such symbols can only stem from an inconsistent database; skipping them keeps a single bad row
+/// from breaking the whole comparison.
+fn skip_unidentifiable_symbols<T>(symbols: Vec<Option<T>>, old: &str, new: &str) -> Vec<T> {
+    let mut identifiable = Vec::with_capacity(symbols.len());
+    for symbol in symbols {
+        match symbol {
+            Some(s) => identifiable.push(s),
+            None => error!("skipping symbol that is neither named nor ordinal when comparing {:?} to {:?}", old, new),
+        }
+    }
+    identifiable
+}
 
+/// Finds the two operating systems to compare, returning their IDs and parts.
+///
+/// Fails with the response to return if either operating system does not exist or the database
+/// cannot be queried.
+fn find_compared_oses<T: Template + Debug>(db: &Connection, os_cache: &OsCache, old: &str, new: &str) -> Result<(IdentifiedOs, IdentifiedOs), TemplateResponder<T>> {
     // find old OS
-    let old_rows_res = query_database(
-        &mut find_os_stmt,
-        [old],
-        os_ify,
-    );
-    let (old_os_id, old_os_part) = match old_rows_res {
-        None => return TemplateResponder::Failure,
-        Some(v) if v.len() == 0 => return TemplateResponder::NotFound,
+    let old_os = match query_os_by_short_name(db, os_cache, old) {
+        None => return Err(TemplateResponder::Failure),
+        Some(v) if v.is_empty() => return Err(TemplateResponder::NotFound),
         Some(mut v) => v.swap_remove(0),
     };
 
     // find new OS
-    let new_rows_res = query_database(
-        &mut find_os_stmt,
-        [new],
-        os_ify,
-    );
-    let (new_os_id, new_os_part) = match new_rows_res {
-        None => return TemplateResponder::Failure,
-        Some(v) if v.len() == 0 => return TemplateResponder::NotFound,
+    let new_os = match query_os_by_short_name(db, os_cache, new) {
+        None => return Err(TemplateResponder::Failure),
+        Some(v) if v.is_empty() => return Err(TemplateResponder::NotFound),
         Some(mut v) => v.swap_remove(0),
     };
 
-    // prepare a DLL-difference query
-    const DLL_DIFF_QUERY: &str = "
-        SELECT
-            dll.path
-        FROM
-            dlls dll
-        WHERE
-            EXISTS (
-                SELECT 1
-                FROM symbol_dll_os y_sdo
-                WHERE y_sdo.os_id = ?1
-                AND y_sdo.dll_id = dll.dll_id
-            )
-            AND NOT EXISTS (
-                SELECT 1
-                FROM symbol_dll_os n_sdo
-                WHERE n_sdo.os_id = ?2
-                AND n_sdo.dll_id = dll.dll_id
-            )
-        ORDER BY
-            1
-    ";
-    let Some(mut dll_diff_stmt) = prepare(&db, DLL_DIFF_QUERY)
+    Ok((old_os, new_os))
+}
+
+/// Computes the differences between two operating systems, as shown on the comparison page.
+///
+/// The symbols common to both operating systems can be numerous, so they are only collected if
+/// `include_common` is set.
+fn compare_os_template(db: &Connection, old_os: IdentifiedOs, new_os: IdentifiedOs, merge_editions: bool, include_common: bool, path_to_root: String) -> TemplateResponder<CompareOsTemplate> {
+    let ((old_os_id, old_os_part), (new_os_id, new_os_part)) = (old_os, new_os);
+    let old = old_os_part.short_name.clone();
+    let new = new_os_part.short_name.clone();
+    if old_os_id == new_os_id {
+        // the differences would be empty, which is misleading
+        let template = CompareOsTemplate {
+            path_to_root,
+            old_os: old_os_part,
+            new_os: new_os_part,
+            merge_editions,
+            added_dlls: Vec::new(),
+            removed_dlls: Vec::new(),
+            added_symbols: Vec::new(),
+            removed_symbols: Vec::new(),
+            common_symbols: None,
+            hint: Some(SAME_OS_COMPARISON_HINT),
+        };
+        return TemplateResponder::BadRequest(template);
+    }
+
+    let Some(mut dll_diff_stmt) = prepare(db, OS_DLL_DIFF_QUERY)
         else { return TemplateResponder::Failure };
 
     let dll_ify = |row: &Row<'_>| {
@@ -1205,7 +4327,7 @@ fn compare_os(old: &str, new: &str) -> TemplateResponder<CompareOsTemplate> {
     // find DLLs which are in old but not in new
     let removed_dlls_opt = query_database(
         &mut dll_diff_stmt,
-        [old_os_id, new_os_id],
+        (old_os_id, new_os_id, merge_editions),
         dll_ify,
     );
     let removed_dlls = match removed_dlls_opt {
@@ -1216,7 +4338,7 @@ fn compare_os(old: &str, new: &str) -> TemplateResponder<CompareOsTemplate> {
     // find DLLs which are in new but not in old
     let added_dlls_opt = query_database(
         &mut dll_diff_stmt,
-        [new_os_id, old_os_id],
+        (new_os_id, old_os_id, merge_editions),
         dll_ify,
     );
     let added_dlls = match added_dlls_opt {
@@ -1224,120 +4346,390 @@ fn compare_os(old: &str, new: &str) -> TemplateResponder<CompareOsTemplate> {
         Some(v) => v,
     };
 
-    // prepare a symbol-difference query, for both named and ordinal symbols
-    // but not for meta-functions
-    const SYMBOL_DIFF_QUERY: &str = "
-        SELECT
-            sym.raw_name,
-            sym.friendly_name,
-            sym.dll_name,
-            sym.ordinal,
-            sym.is_meta_func
-        FROM
-            symbols sym
-        WHERE
-            sym.is_meta_func = 0
-            AND EXISTS (
-                SELECT 1
-                FROM symbol_dll_os y_sdo
-                WHERE y_sdo.os_id = ?1
-                AND y_sdo.sym_id = sym.sym_id
-            )
-            AND NOT EXISTS (
-                SELECT 1
-                FROM symbol_dll_os n_sdo
-                WHERE n_sdo.os_id = ?2
-                AND n_sdo.sym_id = sym.sym_id
-            )
-        ORDER BY
-            1 ASC NULLS LAST,
-            2 ASC NULLS LAST,
-            3,
-            4
-    ";
-    let Some(mut symbol_diff_stmt) = prepare(&db, SYMBOL_DIFF_QUERY)
+    let Some(mut symbol_diff_stmt) = prepare(db, OS_SYMBOL_DIFF_QUERY)
         else { return TemplateResponder::Failure };
 
+    let symbol_dlls_ify = |row: &Row<'_>| {
+        let symbol_opt = SymbolPart::try_opt_from_row(0, row)?;
+        let dlls = DllPart::try_vec_from_json_row(6, row)?;
+        Ok(symbol_opt.map(|symbol| (symbol, dlls)))
+    };
+
     // find symbols which are in old but not in new
     let removed_symbol_rows_opt = query_database(
         &mut symbol_diff_stmt,
-        [old_os_id, new_os_id],
-        |row| SymbolPart::try_from_row(0, row),
+        (old_os_id, new_os_id, merge_editions),
+        symbol_dlls_ify,
     );
     let removed_symbols = match removed_symbol_rows_opt {
         None => return TemplateResponder::Failure,
-        Some(v) => v,
+        Some(v) => skip_unidentifiable_symbols(v, &old, &new),
     };
 
     // find symbols which are in new but not old
     let added_symbols_rows_opt = query_database(
         &mut symbol_diff_stmt,
-        [new_os_id, old_os_id],
-        |row| SymbolPart::try_from_row(0, row),
+        (new_os_id, old_os_id, merge_editions),
+        symbol_dlls_ify,
     );
     let added_symbols = match added_symbols_rows_opt {
         None => return TemplateResponder::Failure,
+        Some(v) => skip_unidentifiable_symbols(v, &old, &new),
+    };
+
+    let common_symbols = if include_common {
+        // (if ?3 is true, the editions of each operating system are considered part of it)
+        const COMMON_SYMBOL_QUERY: &str = "
+            SELECT
+                sym.raw_name,
+                sym.friendly_name,
+                sym.dll_name,
+                sym.ordinal,
+                sym.is_meta_func,
+                sym.deprecated
+            FROM
+                symbols sym
+            WHERE
+                sym.is_meta_func = 0
+                AND EXISTS (
+                    SELECT 1
+                    FROM symbol_dll_os o_sdo
+                    WHERE o_sdo.os_id IN (
+                        SELECT os_id
+                        FROM operating_systems
+                        WHERE os_id = ?1
+                        OR (?3 AND parent_os_id = ?1)
+                    )
+                    AND o_sdo.sym_id = sym.sym_id
+                )
+                AND EXISTS (
+                    SELECT 1
+                    FROM symbol_dll_os n_sdo
+                    WHERE n_sdo.os_id IN (
+                        SELECT os_id
+                        FROM operating_systems
+                        WHERE os_id = ?2
+                        OR (?3 AND parent_os_id = ?2)
+                    )
+                    AND n_sdo.sym_id = sym.sym_id
+                )
+            ORDER BY
+                1 ASC NULLS LAST,
+                2 ASC NULLS LAST,
+                3,
+                4
+        ";
+        let Some(mut common_symbol_stmt) = prepare(db, COMMON_SYMBOL_QUERY)
+            else { return TemplateResponder::Failure };
+        let common_symbol_rows_opt = query_database(
+            &mut common_symbol_stmt,
+            (old_os_id, new_os_id, merge_editions),
+            |row| SymbolPart::try_opt_from_row(0, row),
+        );
+        match common_symbol_rows_opt {
+            None => return TemplateResponder::Failure,
+            Some(v) => Some(skip_unidentifiable_symbols(v, &old, &new)),
+        }
+    } else {
+        None
+    };
+
+    let template = CompareOsTemplate {
+        path_to_root,
+        old_os: old_os_part,
+        new_os: new_os_part,
+        merge_editions,
+        added_dlls,
+        removed_dlls,
+        added_symbols,
+        removed_symbols,
+        common_symbols,
+        hint: None,
+    };
+    TemplateResponder::Template(template)
+}
+
+#[rocket::get("/compare-matrix?<os>")]
+fn compare_matrix(os: Vec<&str>, path_to_root: PathToRoot, route_span: RouteSpan, db_pool: &State<DbPool>) -> StreamedTemplateResponder<CompareMatrixTemplate> {
+    let _entered = route_span.enter();
+    StreamedTemplateResponder(compare_matrix_template(os, path_to_root, db_pool))
+}
+
+fn compare_matrix_template(os: Vec<&str>, path_to_root: PathToRoot, db_pool: &State<DbPool>) -> TemplateResponder<CompareMatrixTemplate> {
+    let mut os_names = os;
+    os_names.sort_unstable();
+    os_names.dedup();
+    if os_names.is_empty() {
+        let template = CompareMatrixTemplate {
+            path_to_root: path_to_root.0,
+            hint: Some(EMPTY_MATRIX_HINT),
+            operating_systems: Vec::new(),
+            symbols: Vec::new(),
+        };
+        return TemplateResponder::BadRequest(template);
+    }
+
+    let Some(db) = connect_to_database(db_pool)
+        else { return TemplateResponder::Failure };
+
+    // the number of operating systems varies, so generate the placeholders
+    let placeholders: Vec<String> = (1..=os_names.len())
+        .map(|i| format!("?{}", i))
+        .collect();
+    let placeholder_list = placeholders.join(", ");
+
+    // find the operating systems in chronological order
+    let os_query = format!(
+        "
+            SELECT
+                os_id,
+                short_name,
+                COALESCE(long_name, short_name),
+                has_icon,
+                build_number
+            FROM
+                operating_systems
+            WHERE
+                short_name IN ({})
+            ORDER BY
+                sort_order ASC,
+                3
+        ",
+        placeholder_list,
+    );
+    let os_rows_opt = prepare_and_query_database(
+        &db,
+        &os_query,
+        rusqlite::params_from_iter(os_names.iter()),
+        |row| {
+            let os_id: i64 = row.get(0)?;
+            let os_part = OperatingSystemPart::try_from_row(1, row)?;
+            Ok((os_id, os_part))
+        },
+    );
+    let os_rows = match os_rows_opt {
+        None => return TemplateResponder::Failure,
+        Some(v) if v.len() < os_names.len() => return TemplateResponder::NotFound,
         Some(v) => v,
     };
+    let os_ids: Vec<i64> = os_rows.iter()
+        .map(|(os_id, _os_part)| *os_id)
+        .collect();
+
+    // find the symbols present in any of the operating systems, along with the operating systems
+    // in which they are present; no meta-functions though
+    let symbol_query = format!(
+        "
+            SELECT DISTINCT
+                sym.raw_name,
+                sym.friendly_name,
+                sym.dll_name,
+                sym.ordinal,
+                sym.is_meta_func,
+                sym.deprecated,
+                sdo.os_id
+            FROM
+                symbols sym
+                INNER JOIN symbol_dll_os sdo
+                    ON sdo.sym_id = sym.sym_id
+            WHERE
+                sym.is_meta_func = 0
+                AND sdo.os_id IN ({})
+            ORDER BY
+                1 ASC NULLS LAST,
+                2 ASC NULLS LAST,
+                3,
+                4
+        ",
+        placeholder_list,
+    );
+    let symbol_rows_opt = prepare_and_query_database(
+        &db,
+        &symbol_query,
+        rusqlite::params_from_iter(os_ids.iter()),
+        |row| {
+            let sym_part = SymbolPart::try_from_row(0, row)?;
+            let os_id: i64 = row.get(6)?;
+            Ok((sym_part, os_id))
+        },
+    );
+    let Some(symbol_rows) = symbol_rows_opt
+        else { return TemplateResponder::Failure };
+
+    // the rows of each symbol are consecutive; merge them into one row of the matrix
+    let mut symbols: Vec<(SymbolPart, Vec<bool>)> = Vec::new();
+    for (sym_part, os_id) in symbol_rows {
+        let os_index = os_ids.iter()
+            .position(|oi| *oi == os_id)
+            .unwrap();
+        let is_new_symbol = symbols.last()
+            .map(|(last_sym_part, _presence)| *last_sym_part != sym_part)
+            .unwrap_or(true);
+        if is_new_symbol {
+            symbols.push((sym_part, vec![false; os_ids.len()]));
+        }
+        symbols.last_mut().unwrap().1[os_index] = true;
+    }
 
-    let template = CompareOsTemplate {
-        old_os: old_os_part,
-        new_os: new_os_part,
-        added_dlls,
-        removed_dlls,
-        added_symbols,
-        removed_symbols,
+    let template = CompareMatrixTemplate {
+        path_to_root: path_to_root.0,
+        hint: None,
+        operating_systems: os_rows.into_iter()
+            .map(|(_os_id, os_part)| os_part)
+            .collect(),
+        symbols,
     };
     TemplateResponder::Template(template)
 }
 
-#[rocket::get("/os/<old_os>/compare/<new_os>/dll/<dll>")]
-fn compare_os_dll(old_os: &str, new_os: &str, dll: &str) -> TemplateResponder<CompareOsDllTemplate> {
-    let Some(db) = connect_to_database()
-        else { return TemplateResponder::Failure };
+/// A path segment naming a JSON document, `<name>.json`.
+struct JsonFileName<'a>(&'a str);
+impl<'a> FromParam<'a> for JsonFileName<'a> {
+    type Error = &'a str;
 
-    const FIND_OS_QUERY: &str = "
-        SELECT
-            os_id,
-            short_name,
-            COALESCE(long_name, short_name),
-            has_icon
-        FROM
-            operating_systems
-        WHERE
-            short_name = ?1
-    ";
-    let Some(mut find_os_stmt) = prepare(&db, FIND_OS_QUERY)
-        else { return TemplateResponder::Failure };
+    fn from_param(param: &'a str) -> Result<Self, Self::Error> {
+        param
+            .strip_suffix(".json")
+            .map(JsonFileName)
+            .ok_or(param)
+    }
+}
 
-    let os_ify = |row: &Row<'_>| {
-        let os_id: i64 = row.get(0)?;
-        let os_part = OperatingSystemPart::try_from_row(1, row)?;
-        Ok((os_id, os_part))
+/// Redirects permanently from the former JSON variant of the comparison page to the comparison page
+/// itself, which returns JSON to clients that prefer it (see [`ResponseFormat`]).
+// ranked before compare_os, which receives the requests whose last segment does not end in .json
+#[rocket::get("/os/<_old>/compare/<new>", rank = 1)]
+fn compare_os_json(_old: &str, new: JsonFileName<'_>, uri: &Origin<'_>) -> Redirect {
+    // the comparison page is in the same directory
+    let mut target: String = utf8_percent_encode(new.0, URL_UNRESERVED).collect();
+    if let Some(query) = uri.query() {
+        target.push('?');
+        target.push_str(query.as_str());
+    }
+    Redirect::permanent(target)
+}
+
+/// Counts the symbols removed and added between two operating systems without listing them.
+///
+/// The symbols are counted using [`OS_SYMBOL_DIFF_QUERY`] itself, so the numbers always match the
+/// lengths of the lists on the comparison page.
+#[rocket::get("/os/<old>/compare/<new>/count?<merge_editions>")]
+fn compare_os_count(old: &str, new: &str, merge_editions: Option<bool>, route_span: RouteSpan, db_pool: &State<DbPool>, os_cache: &State<OsCache>) -> JsonResponder<CompareOsCountApiPart> {
+    let _entered = route_span.enter();
+    let merge_editions = merge_editions.unwrap_or(false);
+    let Some(db) = connect_to_database(db_pool)
+        else { return JsonResponder::Failure };
+    let ((old_os_id, _old_os_part), (new_os_id, _new_os_part)) = match find_compared_oses::<CompareOsTemplate>(&db, os_cache, old, new) {
+        Ok(oses) => oses,
+        Err(TemplateResponder::NotFound) => return JsonResponder::NotFound,
+        Err(_) => return JsonResponder::Failure,
     };
 
-    // find old OS
-    let old_rows_res = query_database(
-        &mut find_os_stmt,
-        [old_os],
-        os_ify,
+    // symbols that are neither named nor ordinal are skipped on the comparison page, so skip them
+    // here too
+    let count_query = format!(
+        "
+            SELECT COUNT(*)
+            FROM ({}) diff
+            WHERE
+                diff.raw_name IS NOT NULL
+                OR (diff.dll_name IS NOT NULL AND diff.ordinal IS NOT NULL)
+        ",
+        OS_SYMBOL_DIFF_QUERY,
     );
-    let (old_os_id, old_os_part) = match old_rows_res {
-        None => return TemplateResponder::Failure,
-        Some(v) if v.len() == 0 => return TemplateResponder::NotFound,
-        Some(mut v) => v.swap_remove(0),
+    let Some(mut count_stmt) = prepare(&db, &count_query)
+        else { return JsonResponder::Failure };
+    let removed_opt = query_database(
+        &mut count_stmt,
+        (old_os_id, new_os_id, merge_editions),
+        |row| row.get::<_, u64>(0),
+    );
+    let Some(removed) = removed_opt.and_then(|mut v| v.pop())
+        else { return JsonResponder::Failure };
+    let added_opt = query_database(
+        &mut count_stmt,
+        (new_os_id, old_os_id, merge_editions),
+        |row| row.get::<_, u64>(0),
+    );
+    let Some(added) = added_opt.and_then(|mut v| v.pop())
+        else { return JsonResponder::Failure };
+
+    JsonResponder::Json(CompareOsCountApiPart {
+        removed,
+        added,
+    })
+}
+
+#[rocket::get("/os/<old>/compare/<new>/export.md?<merge_editions>")]
+fn compare_os_export_markdown(old: &str, new: &str, merge_editions: Option<bool>, base_url: BaseUrl, route_span: RouteSpan, db_pool: &State<DbPool>, os_cache: &State<OsCache>) -> TextResponder {
+    let _entered = route_span.enter();
+    let Some(db) = connect_to_database(db_pool)
+        else { return TextResponder::Failure };
+    let (old_os, new_os) = match find_compared_oses::<CompareOsTemplate>(&db, os_cache, old, new) {
+        Ok(oses) => oses,
+        Err(TemplateResponder::NotFound) => return TextResponder::NotFound,
+        Err(_) => return TextResponder::Failure,
     };
 
-    // find new OS
-    let new_rows_res = query_database(
-        &mut find_os_stmt,
-        [new_os],
-        os_ify,
+    // the comparison is not rendered as HTML, so the path to the root is irrelevant
+    let comparison = match compare_os_template(&db, old_os, new_os, merge_editions.unwrap_or(false), false, String::new()) {
+        TemplateResponder::Template(c) => c,
+        TemplateResponder::BadRequest(c) => return TextResponder::BadRequest(c.hint.unwrap_or("bad request")),
+        TemplateResponder::NotFound => return TextResponder::NotFound,
+        TemplateResponder::NotFoundExplained(_) => return TextResponder::NotFound,
+        TemplateResponder::Failure => return TextResponder::Failure,
+    };
+
+    let mut markdown = format!(
+        "# Changes from {} to {}\n",
+        escape_markdown(&comparison.old_os.long_name),
+        escape_markdown(&comparison.new_os.long_name),
     );
-    let (new_os_id, new_os_part) = match new_rows_res {
-        None => return TemplateResponder::Failure,
-        Some(v) if v.len() == 0 => return TemplateResponder::NotFound,
-        Some(mut v) => v.swap_remove(0),
+    let sections = [
+        ("Added", &comparison.added_symbols),
+        ("Removed", &comparison.removed_symbols),
+    ];
+    for (title, symbols) in sections {
+        markdown.push_str("\n## ");
+        markdown.push_str(title);
+        markdown.push_str("\n\n");
+        if symbols.is_empty() {
+            markdown.push_str("_None._\n");
+        }
+        for (symbol, _dlls) in symbols {
+            markdown.push_str(&format!(
+                "- [{}]({}{})\n",
+                escape_markdown(&symbol.friendly_name_or_generate()),
+                base_url.0,
+                symbol.url_path(),
+            ));
+        }
+    }
+
+    TextResponder::Text {
+        content_type: ContentType::Markdown,
+        body: markdown,
+        attachment_file_name: Some(format!(
+            "{}-to-{}.md",
+            comparison.old_os.short_name, comparison.new_os.short_name,
+        )),
+    }
+}
+
+#[rocket::get("/os/<old_os>/compare/<new_os>/dll/<dll>")]
+#[allow(clippy::too_many_arguments)] // Rocket passes each parameter and guard separately
+fn compare_os_dll(old_os: &str, new_os: &str, dll: &str, path_to_root: PathToRoot, freshness: Freshness, route_span: RouteSpan, db_pool: &State<DbPool>, os_cache: &State<OsCache>) -> Conditional<TemplateResponder<CompareOsDllTemplate>> {
+    let _entered = route_span.enter();
+    freshness.respond(|| compare_os_dll_template(old_os, new_os, dll, path_to_root, db_pool, os_cache))
+}
+
+fn compare_os_dll_template(old_os: &str, new_os: &str, dll: &str, path_to_root: PathToRoot, db_pool: &State<DbPool>, os_cache: &State<OsCache>) -> TemplateResponder<CompareOsDllTemplate> {
+    let Some(db) = connect_to_database(db_pool)
+        else { return TemplateResponder::Failure };
+
+    let ((old_os_id, old_os_part), (new_os_id, new_os_part)) = match find_compared_oses(&db, os_cache, old_os, new_os) {
+        Ok(oses) => oses,
+        Err(responder) => return responder,
     };
 
     // find DLL
@@ -1391,7 +4783,8 @@ fn compare_os_dll(old_os: &str, new_os: &str, dll: &str) -> TemplateResponder<Co
             sym.friendly_name,
             sym.dll_name,
             sym.ordinal,
-            sym.is_meta_func
+            sym.is_meta_func,
+            sym.deprecated
         FROM
             symbols sym
         WHERE
@@ -1442,6 +4835,7 @@ fn compare_os_dll(old_os: &str, new_os: &str, dll: &str) -> TemplateResponder<Co
     };
 
     let template = CompareOsDllTemplate {
+        path_to_root: path_to_root.0,
         old_os: old_os_part,
         new_os: new_os_part,
         dll_path,
@@ -1452,104 +4846,330 @@ fn compare_os_dll(old_os: &str, new_os: &str, dll: &str) -> TemplateResponder<Co
 }
 
 
-#[rocket::get("/")]
-fn root() -> TemplateResponder<RootTemplate> {
-    let Some(db) = connect_to_database()
-        else { return TemplateResponder::Failure };
+#[rocket::get("/feed.atom")]
+fn new_symbols_feed(base_url: BaseUrl, freshness: Freshness, route_span: RouteSpan, db_pool: &State<DbPool>) -> Conditional<TextResponder> {
+    let _entered = route_span.enter();
+    freshness.respond(|| new_symbols_feed_text(base_url, db_pool))
+}
 
-    // obtain operating systems
-    let operating_systems_opt = prepare_and_query_database(
+fn new_symbols_feed_text(base_url: BaseUrl, db_pool: &State<DbPool>) -> TextResponder {
+    let Some(db) = connect_to_database(db_pool)
+        else { return TextResponder::Failure };
+
+    // find the two most recently released operating systems (not counting editions)
+    let latest_os_rows_opt = prepare_and_query_database(
         &db,
         "
             SELECT
+                os_id,
+                release_date,
                 short_name,
                 COALESCE(long_name, short_name),
-                has_icon
-            FROM operating_systems
+                has_icon,
+                build_number
+            FROM
+                operating_systems
+            WHERE
+                release_date IS NOT NULL
+                AND parent_os_id IS NULL
             ORDER BY
-                release_date ASC NULLS LAST,
-                2
+                sort_order DESC,
+                4
+            LIMIT 2
         ",
         [],
-        |row| OperatingSystemPart::try_from_row(0, row),
+        |row| {
+            let os_id: i64 = row.get(0)?;
+            let release_date: String = row.get(1)?;
+            let os_part = OperatingSystemPart::try_from_row(2, row)?;
+            Ok((os_id, release_date, os_part))
+        },
     );
-    let Some(operating_systems) = operating_systems_opt
-        else { return TemplateResponder::Failure };
+    let Some(mut latest_os_rows) = latest_os_rows_opt
+        else { return TextResponder::Failure };
+    if latest_os_rows.is_empty() {
+        // without release dates, there is no latest operating system
+        return TextResponder::NotFound;
+    }
+    let (latest_os_id, latest_release_date, latest_os) = latest_os_rows.swap_remove(0);
+
+    // if there is only one operating system, all its symbols are new
+    let (previous_os_id, previous_os_name) = match latest_os_rows.pop() {
+        Some((os_id, _release_date, os_part)) => (Some(os_id), os_part.short_name),
+        None => (None, String::new()),
+    };
+
+    let added_symbols_opt = prepare_and_query_database(
+        &db,
+        OS_SYMBOL_DIFF_QUERY,
+        (latest_os_id, previous_os_id, false),
+        |row| SymbolPart::try_opt_from_row(0, row),
+    );
+    let mut added_symbols = match added_symbols_opt {
+        None => return TextResponder::Failure,
+        Some(v) => skip_unidentifiable_symbols(v, &previous_os_name, &latest_os.short_name),
+    };
+    added_symbols.truncate(MAX_FEED_ENTRIES);
+
+    let updated = format!("{}T00:00:00Z", latest_release_date);
+    let feed_url = format!("{}feed.atom", base_url.0);
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<feed xmlns=\"http://www.w3.org/2005/Atom\">\n");
+    xml.push_str(&format!("<id>{}</id>\n", escape_xml(&feed_url)));
+    xml.push_str(&format!("<title>New symbols in {}</title>\n", escape_xml(&latest_os.long_name)));
+    xml.push_str(&format!("<updated>{}</updated>\n", escape_xml(&updated)));
+    xml.push_str("<author><name>Windows APIs</name></author>\n");
+    xml.push_str(&format!("<link rel=\"self\" href=\"{}\" />\n", escape_xml(&feed_url)));
+    for symbol in &added_symbols {
+        let symbol_url = format!("{}{}", base_url.0, symbol.url_path());
+        xml.push_str("<entry>\n");
+        // the same symbol may be new again in a later operating system
+        xml.push_str(&format!("<id>{}#{}</id>\n", escape_xml(&symbol_url), escape_xml(&latest_os.short_name)));
+        xml.push_str(&format!("<title>{}</title>\n", escape_xml(&symbol.friendly_name_or_generate())));
+        xml.push_str(&format!("<updated>{}</updated>\n", escape_xml(&updated)));
+        xml.push_str(&format!("<link href=\"{}\" />\n", escape_xml(&symbol_url)));
+        xml.push_str("</entry>\n");
+    }
+    xml.push_str("</feed>\n");
+
+    TextResponder::Text {
+        content_type: ContentType::new("application", "atom+xml").with_params(("charset", "utf-8")),
+        body: xml,
+        attachment_file_name: None,
+    }
+}
+
+/// Serves the icon of an operating system.
+///
+/// Operating systems without an icon are not found, even if a file with their name is available.
+#[rocket::get("/icons/<os_short_name>")]
+fn os_icon(os_short_name: &str, config: &State<WebConfig>, route_span: RouteSpan, db_pool: &State<DbPool>, os_cache: &State<OsCache>) -> BinaryResponder {
+    let _entered = route_span.enter();
+    let Some(db) = connect_to_database(db_pool)
+        else { return BinaryResponder::Failure };
+    let os_part = match query_os_by_short_name(&db, os_cache, os_short_name) {
+        None => return BinaryResponder::Failure,
+        Some(v) if v.is_empty() => return BinaryResponder::NotFound,
+        Some(mut v) => v.swap_remove(0).1,
+    };
+    if !os_part.has_icon {
+        return BinaryResponder::NotFound;
+    }
+
+    // the file name is taken from the database, so it cannot escape the icon directory
+    let icon_path = config.static_path
+        .join("os-icons")
+        .join(format!("{}.png", os_part.short_name));
+    BinaryResponder::from_file(&icon_path, "image/png")
+}
 
-    // obtain first characters of DLL paths
-    let dll_start_chars_opt = prepare_and_query_database(
+#[rocket::get("/favicon.ico")]
+fn favicon(config: &State<WebConfig>) -> BinaryResponder {
+    BinaryResponder::from_file(&config.static_path.join("favicon.ico"), "image/x-icon")
+}
+
+#[rocket::get("/robots.txt")]
+fn robots_txt(base_url: BaseUrl) -> TextResponder {
+    TextResponder::Text {
+        content_type: ContentType::Plain,
+        body: format!("{}\nSitemap: {}sitemap.xml\n", ROBOTS_TXT, base_url.0),
+        attachment_file_name: None,
+    }
+}
+
+#[rocket::get("/sitemap.xml")]
+fn sitemap_index(base_url: BaseUrl, route_span: RouteSpan, db_pool: &State<DbPool>) -> TextResponder {
+    let _entered = route_span.enter();
+    let Some(db) = connect_to_database(db_pool)
+        else { return TextResponder::Failure };
+
+    let count_rows_opt = prepare_and_query_database(
         &db,
         "
-            SELECT DISTINCT
-                SUBSTR(path, 1, 1)
-            FROM dlls
-            ORDER BY
-                1
+            SELECT
+                (SELECT COUNT(*) FROM operating_systems)
+                + (SELECT COUNT(*) FROM dlls)
+                + (SELECT COUNT(*) FROM symbols)
         ",
         [],
-        |row| {
-            let letter: String = row.get(0)?;
-            Ok(letter)
-        },
+        |row| row.get(0),
     );
-    let Some(dll_start_chars) = dll_start_chars_opt
-        else { return TemplateResponder::Failure };
+    let url_count: u64 = match count_rows_opt {
+        Some(mut v) if !v.is_empty() => v.swap_remove(0),
+        _ => return TextResponder::Failure,
+    };
+
+    // always list at least one chunk, even if the database is empty
+    let chunk_count = url_count.div_ceil(SITEMAP_CHUNK_SIZE).max(1);
+    let mut xml = format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<sitemapindex xmlns=\"{}\">\n",
+        SITEMAP_NAMESPACE,
+    );
+    for chunk in 1..=chunk_count {
+        xml.push_str(&format!(
+            "<sitemap><loc>{}</loc></sitemap>\n",
+            escape_xml(&format!("{}sitemaps/{}", base_url.0, chunk)),
+        ));
+    }
+    xml.push_str("</sitemapindex>\n");
+
+    TextResponder::Text {
+        content_type: ContentType::new("application", "xml").with_params(("charset", "utf-8")),
+        body: xml,
+        attachment_file_name: None,
+    }
+}
 
-    // obtain first characters of function names
-    // (raw names for named functions, friendly names for ordinal functions)
-    // except "?", there's a lot of those due to C++ name mangling, take two characters in this case
-    let func_start_chars_opt = prepare_and_query_database(
+/// Lists the pages in the given chunk of the sitemap, with chunks numbered from 1.
+#[rocket::get("/sitemaps/<chunk>")]
+fn sitemap_chunk(chunk: u64, base_url: BaseUrl, route_span: RouteSpan, db_pool: &State<DbPool>) -> TextResponder {
+    let _entered = route_span.enter();
+    if chunk == 0 {
+        return TextResponder::NotFound;
+    }
+
+    let Some(db) = connect_to_database(db_pool)
+        else { return TextResponder::Failure };
+
+    // list the pages of all operating systems, DLLs and symbols in a stable order
+    let limit = i64::try_from(SITEMAP_CHUNK_SIZE).unwrap();
+    let offset = i64::try_from((chunk - 1) * SITEMAP_CHUNK_SIZE).unwrap();
+    let page_rows_opt = prepare_and_query_database(
         &db,
         "
-            WITH symbol_best_name(name) AS (
-                SELECT
-                    CASE
-                        WHEN raw_name IS NOT NULL THEN raw_name
-                        ELSE friendly_name
-                    END name
-                FROM
-                    symbols
-                WHERE
-                    raw_name IS NOT NULL
-                    OR friendly_name IS NOT NULL
+            SELECT kind, name, ordinal
+            FROM (
+                SELECT 0 kind, os_id id, short_name name, NULL ordinal
+                FROM operating_systems
+                UNION ALL
+                SELECT 1 kind, dll_id id, path name, NULL ordinal
+                FROM dlls
+                UNION ALL
+                SELECT 2 kind, sym_id id, COALESCE(raw_name, dll_name) name, ordinal
+                FROM symbols
             )
-            SELECT DISTINCT
-                CASE SUBSTR(name, 1, 1)
-                    WHEN '?' THEN SUBSTR(name, 1, 2)
-                    ELSE SUBSTR(name, 1, 1)
-                END symbol_name
-            FROM symbol_best_name
             ORDER BY
-                1
+                kind,
+                id
+            LIMIT ?1 OFFSET ?2
         ",
-        [],
+        (limit, offset),
         |row| {
-            let letter: String = row.get(0)?;
-            Ok(letter)
+            let kind: i64 = row.get(0)?;
+            let name: String = row.get(1)?;
+            let ordinal: Option<u64> = row.get(2)?;
+            Ok((kind, name, ordinal))
         },
     );
-    let Some(func_start_chars) = func_start_chars_opt
+    let page_rows = match page_rows_opt {
+        None => return TextResponder::Failure,
+        Some(v) if v.is_empty() && chunk > 1 => return TextResponder::NotFound,
+        Some(v) => v,
+    };
+
+    let mut xml = format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<urlset xmlns=\"{}\">\n",
+        SITEMAP_NAMESPACE,
+    );
+    for (kind, name, ordinal) in page_rows {
+        let path = match (kind, ordinal) {
+            (0, _) => format!("os/{}", utf8_percent_encode(&name, URL_UNRESERVED)),
+            (1, _) => format!("dll/{}", utf8_percent_encode(&name, URL_UNRESERVED)),
+            (_, None) => SymbolPart::Named {
+                raw_name: name,
+                friendly_name: None,
+                is_meta_func: false,
+                deprecated: false,
+            }.url_path(),
+            (_, Some(o)) => SymbolPart::DllOrdinal {
+                dll_name: name,
+                ordinal: o,
+                friendly_name: None,
+                is_meta_func: false,
+                deprecated: false,
+            }.url_path(),
+        };
+        xml.push_str(&format!(
+            "<url><loc>{}</loc></url>\n",
+            escape_xml(&format!("{}{}", base_url.0, path)),
+        ));
+    }
+    xml.push_str("</urlset>\n");
+
+    TextResponder::Text {
+        content_type: ContentType::new("application", "xml").with_params(("charset", "utf-8")),
+        body: xml,
+        attachment_file_name: None,
+    }
+}
+
+#[rocket::get("/")]
+fn root(freshness: Freshness, route_span: RouteSpan, db_pool: &State<DbPool>) -> Conditional<TemplateResponder<RootTemplate>> {
+    let _entered = route_span.enter();
+    freshness.respond(|| root_template(db_pool))
+}
+
+fn root_template(db_pool: &State<DbPool>) -> TemplateResponder<RootTemplate> {
+    let Some(db) = connect_to_database(db_pool)
         else { return TemplateResponder::Failure };
 
-    // obtain first characters of DLLs with ordinal-only functions
-    let ordinal_dll_start_chars_opt = prepare_and_query_database(
+    // obtain operating systems and the number of symbols in each (precomputed by the loader)
+    let operating_systems_opt = prepare_and_query_database(
         &db,
         "
-            SELECT DISTINCT
-                SUBSTR(dll_name, 1, 1)
-            FROM symbols
-            WHERE
-                dll_name IS NOT NULL
+            SELECT
+                os.short_name,
+                COALESCE(os.long_name, os.short_name),
+                os.has_icon,
+                os.build_number,
+                os.symbol_count
+            FROM operating_systems os
             ORDER BY
-                1
+                os.sort_order ASC,
+                2
         ",
         [],
         |row| {
-            let letter: String = row.get(0)?;
-            Ok(letter)
+            let os = OperatingSystemPart::try_from_row(0, row)?;
+            let symbol_count: u64 = row.get(4)?;
+            Ok(OsSymbolCountPart {
+                os,
+                symbol_count,
+            })
         },
     );
-    let Some(ordinal_dll_start_chars) = ordinal_dll_start_chars_opt
+    let Some(operating_systems) = operating_systems_opt
+        else { return TemplateResponder::Failure };
+
+    // obtain the first characters of DLL paths, function names and DLLs with ordinal-only functions
+    // (precomputed by the loader)
+    const START_CHARS_QUERY: &str = "
+        SELECT
+            start_chars
+        FROM
+            index_start_chars
+        WHERE
+            category = ?1
+        ORDER BY
+            1
+    ";
+    let Some(mut start_chars_stmt) = prepare(&db, START_CHARS_QUERY)
+        else { return TemplateResponder::Failure };
+    let mut get_start_chars = |category: &str| {
+        query_database(
+            &mut start_chars_stmt,
+            [category],
+            |row| {
+                let start_chars: String = row.get(0)?;
+                Ok(start_chars)
+            },
+        )
+    };
+
+    let Some(dll_start_chars) = get_start_chars("dll")
+        else { return TemplateResponder::Failure };
+    let Some(func_start_chars) = get_start_chars("func")
+        else { return TemplateResponder::Failure };
+    let Some(ordinal_dll_start_chars) = get_start_chars("ordinal_dll")
         else { return TemplateResponder::Failure };
 
     let template = RootTemplate {
@@ -1561,6 +5181,84 @@ fn root() -> TemplateResponder<RootTemplate> {
     TemplateResponder::Template(template)
 }
 
+fn compute_stats(db: &Connection) -> Option<StatsPart> {
+    let stats_opt = prepare_and_query_database(
+        db,
+        "
+            SELECT
+                (SELECT ver FROM schema_version),
+                (SELECT COUNT(*) FROM operating_systems),
+                (SELECT COUNT(*) FROM dlls),
+                (SELECT COUNT(*) FROM symbols WHERE raw_name IS NOT NULL),
+                (SELECT COUNT(*) FROM symbols WHERE raw_name IS NULL),
+                (SELECT COUNT(*) FROM symbol_dll_os),
+                (SELECT MIN(release_date) FROM operating_systems),
+                (SELECT MAX(release_date) FROM operating_systems)
+        ",
+        [],
+        |row| {
+            Ok(StatsPart {
+                schema_version: row.get(0)?,
+                operating_systems: row.get(1)?,
+                dlls: row.get(2)?,
+                named_symbols: row.get(3)?,
+                ordinal_symbols: row.get(4)?,
+                relationships: row.get(5)?,
+                earliest_release_date: row.get(6)?,
+                latest_release_date: row.get(7)?,
+            })
+        },
+    );
+    stats_opt?.pop()
+}
+
+/// Serves the OpenAPI document describing the JSON API.
+#[rocket::get("/api/openapi.json")]
+fn api_openapi() -> JsonResponder<serde_json::Value> {
+    JsonResponder::Json(openapi::document())
+}
+
+#[rocket::get("/api/stats")]
+fn api_stats(stats_cache: &State<StatsCache>, route_span: RouteSpan, db_pool: &State<DbPool>) -> JsonResponder<StatsPart> {
+    let _entered = route_span.enter();
+    let Some(db) = connect_to_database(db_pool)
+        else { return JsonResponder::Failure };
+
+    let reindexed_at_opt = query_reindexed_at(&db);
+    if let Some(reindexed_at) = reindexed_at_opt {
+        let cache_guard = stats_cache.reindexed_at_and_stats
+            .lock().expect("stats cache lock poisoned");
+        if let Some((cached_reindexed_at, cached_stats)) = cache_guard.as_ref()
+            && *cached_reindexed_at == reindexed_at
+        {
+            return JsonResponder::Json(cached_stats.clone());
+        }
+    }
+
+    let Some(stats) = compute_stats(&db)
+        else { return JsonResponder::Failure };
+
+    if let Some(reindexed_at) = reindexed_at_opt {
+        let mut cache_guard = stats_cache.reindexed_at_and_stats
+            .lock().expect("stats cache lock poisoned");
+        *cache_guard = Some((reindexed_at, stats.clone()));
+    }
+    JsonResponder::Json(stats)
+}
+
+#[rocket::get("/healthz")]
+fn healthz(route_span: RouteSpan, config: &State<WebConfig>, db_pool: &State<DbPool>) -> (Status, &'static str) {
+    let _entered = route_span.enter();
+
+    match check_database(&config.database_path, db_pool) {
+        Ok(()) => (Status::Ok, "ok"),
+        Err(problem) => {
+            error!("health check failed: {}", problem);
+            (Status::ServiceUnavailable, problem.short_description())
+        },
+    }
+}
+
 
 fn set_up_tracing() {
     use tracing_subscriber::EnvFilter;
@@ -1577,21 +5275,506 @@ fn set_up_tracing() {
 #[rocket::launch]
 fn rocket_launcher() -> _ {
     set_up_tracing();
+    build_rocket(rocket::build())
+}
+
+/// Sets up the state, fairings, catchers and routes of the web application on the given Rocket
+/// instance, reading the configuration from its figment.
+fn build_rocket(rocket: rocket::Rocket<rocket::Build>) -> rocket::Rocket<rocket::Build> {
+    // the pool is created before launching, so obtain the database path right away
+    let config: WebConfig = rocket.figment().extract()
+        .expect("failed to read configuration");
+    info!("using database {}", config.database_path.display());
+    let db_pool = create_database_pool(&config.database_path);
+    let os_cache = OsCache::default();
+    // don't wait for the pool to give up on connecting if the database is missing; the database
+    // check reports the problem
+    if config.database_path.exists() && let Some(db) = connect_to_database(&db_pool) {
+        // fill the cache right away (the short name does not matter)
+        os_cache.get(&db, "");
+    }
+    match os_cache.len() {
+        Some(os_count) => info!("cached {} operating system(s)", os_count),
+        None => info!("operating systems not cached yet"),
+    }
+
+    rocket
+        .attach(AdHoc::config::<WebConfig>())
+        .attach(fairings::DatabaseCheck {
+            database_path: config.database_path.clone(),
+            require_database: config.require_database,
+        })
+        .attach(fairings::AccessLog)
+        .attach(fairings::Compression)
+        .manage(db_pool)
+        .manage(StatsCache::default())
+        .manage(os_cache)
+        .register("/", rocket::catchers![not_found_catcher, internal_error_catcher])
+        .mount("/", rocket::routes![
+            root,
+            os_page,
+            os_dll_page,
+            os_dll_ordinal_range_page,
+            os_dll_exports_def,
+            all_os_symbols,
+            all_os_symbols_csv,
+            symbol_page,
+            dll_ordinal_symbol_page,
+            symbol_permalink,
+            funcs_page,
+            ordinal_only_funcs_page,
+            search_page,
+            alpha_dll_page,
+            dll_page,
+            compare_os,
+            compare_os_json,
+            compare_dlls,
+            compare_os_export_markdown,
+            compare_os_count,
+            compare_os_redirect,
+            compare_os_dll,
+            compare_os_dll_redirect,
+            compare_matrix,
+            api_stats,
+            healthz,
+            api_symbol,
+            api_os_symbols,
+            api_autocomplete,
+            api_openapi,
+            new_symbols_feed,
+            os_icon,
+            favicon,
+            robots_txt,
+            sitemap_index,
+            sitemap_chunk,
+        ])
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+    use std::time::{Duration, Instant};
+
+    use rocket::http::{Accept, Header, Status};
+    use rocket::local::blocking::Client;
+    use rusqlite::Connection;
+    use tempfile::TempDir;
 
-    rocket::build().mount("/", rocket::routes![
-        root,
-        os_page,
-        os_dll_page,
-        all_os_symbols,
-        symbol_page,
-        dll_ordinal_symbol_page,
-        funcs_page,
-        ordinal_only_funcs_page,
-        alpha_dll_page,
-        dll_page,
-        compare_os,
-        compare_os_redirect,
-        compare_os_dll,
-        compare_os_dll_redirect,
-    ])
+    use super::{build_rocket, skip_unidentifiable_symbols};
+
+    /// Creates a database in the given directory from the schema and the given data statements and
+    /// returns a client of the web application using it.
+    fn client_with_data(dir: &TempDir, data_sql: &str) -> Client {
+        let database_path = dir.path().join("winapi.sqlite3");
+        let db = Connection::open(&database_path).unwrap();
+        db.execute_batch(include_str!("../../db/schema.sql")).unwrap();
+        db.execute_batch(data_sql).unwrap();
+        drop(db);
+        client_for_database(&database_path)
+    }
+
+    fn client_for_database(database_path: &Path) -> Client {
+        let figment = rocket::Config::figment()
+            .merge(("database_path", database_path))
+            .merge(("log_level", "off"));
+        Client::tracked(build_rocket(rocket::custom(figment))).unwrap()
+    }
+
+    fn get_ok(client: &Client, uri: &str) -> String {
+        let response = client.get(uri).dispatch();
+        assert_eq!(response.status(), Status::Ok, "status of {}", uri);
+        response.into_string().unwrap()
+    }
+
+
+    #[test]
+    fn empty_database_shows_placeholder() {
+        let dir = TempDir::new().unwrap();
+        let client = client_with_data(&dir, "");
+
+        let root = get_ok(&client, "/");
+        assert!(root.contains("No data has been loaded into the database yet."));
+        assert!(!root.contains("By Operating System"));
+
+        let response = client.get("/os/win10").dispatch();
+        assert_eq!(response.status(), Status::NotFound);
+        assert!(response.into_string().unwrap().contains("No data has been loaded into the database yet."));
+    }
+
+    #[test]
+    fn unidentifiable_symbols_are_skipped() {
+        let symbols = vec![Some("CreateFileW"), None, Some("DeleteFileW")];
+        assert_eq!(skip_unidentifiable_symbols(symbols, "win95", "win98"), ["CreateFileW", "DeleteFileW"]);
+    }
+
+    #[test]
+    fn comparison_skips_symbol_without_identity() {
+        let dir = TempDir::new().unwrap();
+        let client = client_with_data(&dir, r"
+            PRAGMA ignore_check_constraints = ON;
+            INSERT INTO operating_systems (os_id, short_name) VALUES (1, 'win95'), (2, 'win98');
+            INSERT INTO dlls (dll_id, path, secondary_platform) VALUES (1, 'system\kernel32.dll', 0);
+            INSERT INTO symbols (sym_id, raw_name, dll_name, ordinal) VALUES
+                (1, 'CreateFileA', NULL, NULL),
+                (2, 'CreateFileW', NULL, NULL),
+                (3, NULL, NULL, NULL);
+            INSERT INTO symbol_dll_os (sym_id, dll_id, os_id) VALUES (1, 1, 1), (1, 1, 2), (2, 1, 2), (3, 1, 2);
+        ");
+
+        let comparison = get_ok(&client, "/os/win95/compare/win98");
+        assert!(comparison.contains("CreateFileW"));
+    }
+
+    #[test]
+    fn start_page_link_of_top_level_page_is_not_empty() {
+        let dir = TempDir::new().unwrap();
+        let client = client_with_data(&dir, "");
+
+        let response = client.get("/nonexistent").dispatch();
+        assert_eq!(response.status(), Status::NotFound);
+        assert!(response.into_string().unwrap().contains(r#"<a href="./">Back to the start page</a>"#));
+
+        let response = client.get("/symbol/dll/kernel32.dll/ordinal/1").dispatch();
+        assert_eq!(response.status(), Status::NotFound);
+        assert!(response.into_string().unwrap().contains(r#"<a href="../../../../">Back to the start page</a>"#));
+    }
+
+    #[test]
+    fn csv_export_quotes_fields_and_file_name() {
+        let dir = TempDir::new().unwrap();
+        let client = client_with_data(&dir, r#"
+            INSERT INTO operating_systems (os_id, short_name) VALUES (1, 'win "95"');
+            INSERT INTO dlls (dll_id, path, secondary_platform) VALUES (1, 'system\kernel32.dll', 0);
+            INSERT INTO symbols (sym_id, raw_name, friendly_name, dll_name, ordinal) VALUES
+                (1, '?f@@YAXHH@Z', 'void f(int,int)', NULL, NULL),
+                (2, NULL, NULL, 'system\kernel32.dll', 7);
+            INSERT INTO symbol_dll_os (sym_id, dll_id, os_id) VALUES (1, 1, 1), (2, 1, 1);
+        "#);
+
+        let response = client.get("/os/win%20%2295%22/all-symbols.csv").dispatch();
+        assert_eq!(response.status(), Status::Ok);
+        assert_eq!(
+            response.headers().get_one("Content-Disposition"),
+            Some(r#"attachment; filename="win _95_-symbols.csv"; filename*=UTF-8''win%20%2295%22-symbols.csv"#),
+        );
+        let mut lines: Vec<String> = response.into_string().unwrap()
+            .lines()
+            .map(|l| l.to_owned())
+            .collect();
+        lines.sort();
+        assert_eq!(lines, [
+            r#""void f(int,int)",?f@@YAXHH@Z,,,system\kernel32.dll,false"#,
+            r#",,system\kernel32.dll,7,system\kernel32.dll,false"#,
+            "friendly_name,raw_name,dll_name,ordinal,dll_path,secondary_platform",
+        ]);
+    }
+
+    #[test]
+    fn sitemap_chunks_are_served_under_their_own_prefix() {
+        let dir = TempDir::new().unwrap();
+        let client = client_with_data(&dir, "
+            INSERT INTO operating_systems (os_id, short_name) VALUES (1, 'win95');
+        ");
+
+        let index = get_ok(&client, "/sitemap.xml");
+        assert!(index.contains("/sitemaps/1</loc>"));
+
+        let chunk = get_ok(&client, "/sitemaps/1");
+        assert!(chunk.contains("/os/win95</loc>"));
+
+        assert_eq!(client.get("/sitemaps/0").dispatch().status(), Status::NotFound);
+        assert_eq!(client.get("/sitemaps/2").dispatch().status(), Status::NotFound);
+        assert_eq!(client.get("/sitemap-1.xml").dispatch().status(), Status::NotFound);
+    }
+
+    #[test]
+    fn stats_are_recomputed_once_data_changes() {
+        let dir = TempDir::new().unwrap();
+        let client = client_with_data(&dir, r"
+            PRAGMA journal_mode = WAL;
+            INSERT INTO operating_systems (os_id, short_name) VALUES (1, 'win95');
+            UPDATE schema_version SET reindexed_at = 1000;
+        ");
+        assert!(get_ok(&client, "/api/stats").contains(r#""operating_systems":1,"#));
+
+        // the change remains in the write-ahead log while the connection is open
+        let db = Connection::open(dir.path().join("winapi.sqlite3")).unwrap();
+        db.execute_batch(r"
+            INSERT INTO operating_systems (os_id, short_name) VALUES (2, 'win98');
+            UPDATE schema_version SET reindexed_at = 2000;
+        ").unwrap();
+        assert!(get_ok(&client, "/api/stats").contains(r#""operating_systems":2,"#));
+    }
+
+    #[test]
+    fn last_modified_is_time_of_last_reindex() {
+        let dir = TempDir::new().unwrap();
+        let client = client_with_data(&dir, r"
+            INSERT INTO operating_systems (os_id, short_name) VALUES (1, 'win95');
+            UPDATE schema_version SET reindexed_at = 784111777123;
+        ");
+
+        let response = client.get("/").dispatch();
+        assert_eq!(response.status(), Status::Ok);
+        assert_eq!(response.headers().get_one("Last-Modified"), Some("Sun, 06 Nov 1994 08:49:37 GMT"));
+
+        let response = client.get("/")
+            .header(Header::new("If-Modified-Since", "Sun, 06 Nov 1994 08:49:37 GMT"))
+            .dispatch();
+        assert_eq!(response.status(), Status::NotModified);
+    }
+
+    #[test]
+    fn ordinal_above_u32_resolves() {
+        let dir = TempDir::new().unwrap();
+        let client = client_with_data(&dir, r"
+            INSERT INTO operating_systems (os_id, short_name) VALUES (1, 'win95');
+            INSERT INTO dlls (dll_id, path, secondary_platform) VALUES (1, 'system\kernel32.dll', 0);
+            INSERT INTO symbols (sym_id, dll_name, ordinal) VALUES (1, 'system\kernel32.dll', 4294967300);
+            INSERT INTO symbol_dll_os (sym_id, dll_id, os_id, ordinal) VALUES (1, 1, 1, 4294967300);
+        ");
+
+        let page = get_ok(&client, "/symbol/dll/system%5Ckernel32.dll/ordinal/4294967300");
+        assert!(page.contains("4294967300"));
+
+        for ordinal in ["4294967301", "-1", "twelve", "18446744073709551615"] {
+            let uri = format!("/symbol/dll/system%5Ckernel32.dll/ordinal/{}", ordinal);
+            assert_eq!(client.get(uri.as_str()).dispatch().status(), Status::NotFound, "status of {}", uri);
+        }
+    }
+
+    #[test]
+    fn symbol_of_multiple_architectures_is_listed_once_per_dll() {
+        let dir = TempDir::new().unwrap();
+        let client = client_with_data(&dir, r"
+            INSERT INTO operating_systems (os_id, short_name) VALUES (1, 'win11');
+            INSERT INTO dlls (dll_id, path, secondary_platform) VALUES (1, 'system32\kernel32.dll', 0);
+            INSERT INTO symbols (sym_id, raw_name) VALUES (1, 'CreateFileW');
+            INSERT INTO symbol_dll_os (sym_id, dll_id, os_id, architecture) VALUES (1, 1, 1, 'x64'), (1, 1, 1, 'arm64');
+        ");
+
+        let csv = get_ok(&client, "/os/win11/all-symbols.csv");
+        assert_eq!(csv.lines().count(), 2);
+        let page = get_ok(&client, "/os/win11/all-symbols");
+        assert_eq!(page.matches("<li").count(), 1);
+    }
+
+    #[test]
+    fn nearest_symbols_respect_scope_and_case() {
+        let dir = TempDir::new().unwrap();
+        let client = client_with_data(&dir, r"
+            INSERT INTO operating_systems (os_id, short_name) VALUES (1, 'win95'), (2, 'win98');
+            INSERT INTO dlls (dll_id, path, secondary_platform) VALUES (1, 'system\kernel32.dll', 0);
+            INSERT INTO symbols (sym_id, raw_name, is_meta_func) VALUES
+                (1, 'CreateFileW', 0),
+                (2, 'CreateFileV', 0),
+                (3, 'CreateFileU', 1);
+            INSERT INTO symbol_dll_os (sym_id, dll_id, os_id) VALUES (1, 1, 1), (2, 1, 2), (3, 1, 1);
+        ");
+
+        let page = get_ok(&client, "/search?q=cREATEfILEz&os=win95");
+        assert!(page.contains("CreateFileW"));
+        assert!(!page.contains("CreateFileV"));
+        assert!(!page.contains("CreateFileU"));
+
+        let page = get_ok(&client, "/search?q=cREATEfILEz");
+        assert!(page.contains("CreateFileW"));
+        assert!(page.contains("CreateFileV"));
+        assert!(!page.contains("CreateFileU"));
+    }
+
+    #[test]
+    fn only_data_pages_answer_conditional_requests() {
+        let dir = TempDir::new().unwrap();
+        let client = client_with_data(&dir, r"
+            INSERT INTO operating_systems (os_id, short_name) VALUES (1, 'win95');
+            INSERT INTO dlls (dll_id, path, secondary_platform) VALUES (1, 'system\kernel32.dll', 0);
+            INSERT INTO symbols (sym_id, raw_name) VALUES (1, 'CreateFileA');
+            INSERT INTO symbol_dll_os (sym_id, dll_id, os_id) VALUES (1, 1, 1);
+            UPDATE schema_version SET reindexed_at = 784111777123;
+        ");
+        let if_modified_since = Header::new("If-Modified-Since", "Sun, 06 Nov 1994 08:49:37 GMT");
+
+        let response = client.get("/symbol/CreateFileA").header(if_modified_since.clone()).dispatch();
+        assert_eq!(response.status(), Status::NotModified);
+
+        for uri in ["/healthz", "/api/stats"] {
+            let response = client.get(uri).header(if_modified_since.clone()).dispatch();
+            assert_eq!(response.status(), Status::Ok, "status of {}", uri);
+            assert_eq!(response.headers().get_one("Last-Modified"), None, "Last-Modified of {}", uri);
+        }
+    }
+
+    #[test]
+    fn streamed_pages_are_always_rendered_in_full() {
+        let dir = TempDir::new().unwrap();
+        let client = client_with_data(&dir, r"
+            INSERT INTO operating_systems (os_id, short_name) VALUES (1, 'win95'), (2, 'win98');
+            INSERT INTO dlls (dll_id, path, secondary_platform) VALUES (1, 'system\kernel32.dll', 0);
+            INSERT INTO symbols (sym_id, raw_name) VALUES (1, 'CreateFileA');
+            INSERT INTO symbol_dll_os (sym_id, dll_id, os_id) VALUES (1, 1, 1);
+            UPDATE schema_version SET reindexed_at = 784111777123;
+        ");
+        let if_modified_since = Header::new("If-Modified-Since", "Sun, 06 Nov 1994 08:49:37 GMT");
+
+        for uri in [
+            "/os/win95/all-symbols",
+            "/os/win95/dll/system%5Ckernel32.dll",
+            "/dll/system%5Ckernel32.dll",
+            "/os/win95/compare/win98",
+            "/os/win95/all-symbols.csv",
+        ] {
+            let response = client.get(uri).header(if_modified_since.clone()).dispatch();
+            assert_eq!(response.status(), Status::Ok, "status of {}", uri);
+            assert_eq!(response.headers().get_one("Last-Modified"), None, "Last-Modified of {}", uri);
+            assert!(response.into_string().unwrap().contains("CreateFileA"), "body of {}", uri);
+        }
+    }
+
+    #[test]
+    fn operating_system_is_not_compared_to_itself() {
+        let dir = TempDir::new().unwrap();
+        let client = client_with_data(&dir, r"
+            INSERT INTO operating_systems (os_id, short_name) VALUES (1, 'win95'), (2, 'win98');
+        ");
+
+        let response = client.get("/os/win95/compare/win95").dispatch();
+        assert_eq!(response.status(), Status::BadRequest);
+        assert!(response.into_string().unwrap().contains("An operating system cannot be compared to itself."));
+
+        let response = client.get("/compare-os?old=win95&new=win95").dispatch();
+        assert_eq!(response.status(), Status::SeeOther);
+        assert_eq!(response.headers().get_one("Location"), Some("os/win95"));
+
+        let response = client.get("/compare-os?old=win95&new=win98").dispatch();
+        assert_eq!(response.status(), Status::PermanentRedirect);
+        assert_eq!(response.headers().get_one("Location"), Some("os/win95/compare/win98"));
+    }
+
+    #[test]
+    fn operating_system_is_found_ignoring_case_and_whitespace() {
+        let dir = TempDir::new().unwrap();
+        let client = client_with_data(&dir, r"
+            INSERT INTO operating_systems (os_id, short_name) VALUES (1, 'win95'), (2, 'win98'), (3, 'nt4'), (4, 'NT4');
+            INSERT INTO dlls (dll_id, path, secondary_platform) VALUES (1, 'kernel32.dll', 0);
+            INSERT INTO symbols (sym_id, raw_name) VALUES (1, 'CreateFileA');
+            INSERT INTO symbol_dll_os (sym_id, dll_id, os_id) VALUES (1, 1, 1), (1, 1, 2);
+        ");
+
+        let response = client.get("/os/WIN95%20").dispatch();
+        assert_eq!(response.status(), Status::MovedPermanently);
+        assert_eq!(response.headers().get_one("Location"), Some("../os/win95"));
+
+        assert!(get_ok(&client, "/os/WIN95/dll/kernel32.dll/exports.def").contains("CreateFileA"));
+        assert!(get_ok(&client, "/os/WIN95/all-symbols.csv").contains("CreateFileA"));
+        assert!(get_ok(&client, "/api/os/WIN95/symbols").contains("CreateFileA"));
+        get_ok(&client, "/os/WIN95/compare-dlls/Win98");
+
+        // do not guess between operating systems whose short names only differ in case
+        assert_eq!(client.get("/os/Nt4").dispatch().status(), Status::NotFound);
+    }
+
+    #[test]
+    fn keyset_page_is_limited_to_row_count() {
+        let dir = TempDir::new().unwrap();
+        let client = client_with_data(&dir, r"
+            INSERT INTO symbols (sym_id, raw_name) VALUES
+                (1, 'CreateA'), (2, 'CreateB'), (3, 'CreateC'), (4, 'CreateD'), (5, 'CreateE');
+        ");
+
+        let page = get_ok(&client, "/funcs/Create?after=%5B%22CreateA%22%5D&limit=2");
+        assert!(page.contains("CreateB"));
+        assert!(page.contains("CreateC"));
+        assert!(!page.contains("CreateD"));
+    }
+
+    #[test]
+    fn comparison_lists_dlls_of_each_symbol() {
+        let dir = TempDir::new().unwrap();
+        let client = client_with_data(&dir, r"
+            INSERT INTO operating_systems (os_id, short_name) VALUES (1, 'win95'), (2, 'win98');
+            INSERT INTO dlls (dll_id, path, secondary_platform) VALUES (1, 'kernel32.dll', 0), (2, 'user32.dll', 0);
+            INSERT INTO symbols (sym_id, raw_name) VALUES (1, 'CreateFileA'), (2, 'MessageBoxA');
+            INSERT INTO symbol_dll_os (sym_id, dll_id, os_id) VALUES (1, 1, 1), (2, 2, 2);
+        ");
+
+        let page = get_ok(&client, "/os/win95/compare/win98");
+        assert!(page.contains(r#"<span class="symbol-dlls"><a href="../../../dll/kernel32.dll">kernel32.dll</a></span>"#));
+        assert!(page.contains(r#"<span class="symbol-dlls"><a href="../../../dll/user32.dll">user32.dll</a></span>"#));
+
+        let response = client.get("/os/win95/compare/win98")
+            .header(Accept::JSON)
+            .dispatch();
+        assert_eq!(response.status(), Status::Ok);
+        let json = response.into_string().unwrap();
+        assert!(json.contains(r#""raw_name":"MessageBoxA","#));
+        assert!(json.contains(r#""dlls":[{"path":"user32.dll","secondary_platform":false}]"#));
+    }
+
+    #[test]
+    fn health_check_fails_promptly_if_database_cannot_be_opened() {
+        let dir = TempDir::new().unwrap();
+
+        // the path exists, but it is not a database file
+        let client = client_for_database(dir.path());
+        let start_time = Instant::now();
+        let response = client.get("/healthz").dispatch();
+        assert_eq!(response.status(), Status::ServiceUnavailable);
+        assert!(start_time.elapsed() < Duration::from_secs(2));
+    }
+
+    #[test]
+    fn json_suffix_redirects_to_negotiated_comparison() {
+        let dir = TempDir::new().unwrap();
+        let client = client_with_data(&dir, "
+            INSERT INTO operating_systems (os_id, short_name) VALUES (1, 'win95'), (2, 'win98');
+        ");
+
+        let response = client.get("/os/win95/compare/win98.json?merge_editions=true").dispatch();
+        assert_eq!(response.status(), Status::PermanentRedirect);
+        assert_eq!(response.headers().get_one("Location"), Some("win98?merge_editions=true"));
+
+        let document = get_ok(&client, "/api/openapi.json");
+        assert!(document.contains(r#""/os/{old}/compare/{new}":"#));
+        assert!(document.contains(r#""/symbol/{sym_raw_name}":"#));
+        assert!(!document.contains("{new}.json"));
+    }
+
+    #[test]
+    fn autocomplete_returns_friendly_or_raw_names() {
+        let dir = TempDir::new().unwrap();
+        let client = client_with_data(&dir, "
+            INSERT INTO symbols (sym_id, raw_name, friendly_name) VALUES
+                (1, 'CreateFileA', NULL),
+                (2, '?Create@@YAXH@Z', 'void Create(int)'),
+                (3, 'DeleteFileA', NULL);
+        ");
+
+        let names = get_ok(&client, "/api/autocomplete?q=Create");
+        assert_eq!(names, r#"["CreateFileA"]"#);
+        let names = get_ok(&client, "/api/autocomplete?q=void%20Cr");
+        assert_eq!(names, r#"["void Create(int)"]"#);
+
+        let document = get_ok(&client, "/api/openapi.json");
+        assert!(document.contains("friendly name or, if it has none, by its raw name"));
+    }
+
+    #[test]
+    fn operating_systems_are_reloaded_once_data_changes() {
+        let dir = TempDir::new().unwrap();
+        let client = client_with_data(&dir, r"
+            PRAGMA journal_mode = WAL;
+            INSERT INTO operating_systems (os_id, short_name, long_name) VALUES (1, 'win95', 'Windows 95');
+            UPDATE schema_version SET reindexed_at = 1000;
+        ");
+        assert!(get_ok(&client, "/os/win95").contains("Windows 95"));
+
+        let db = Connection::open(dir.path().join("winapi.sqlite3")).unwrap();
+        db.execute_batch(r"
+            UPDATE operating_systems SET long_name = 'Windows 95 OSR2';
+            UPDATE schema_version SET reindexed_at = 2000;
+        ").unwrap();
+        assert!(get_ok(&client, "/os/win95").contains("Windows 95 OSR2"));
+    }
 }
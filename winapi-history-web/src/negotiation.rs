@@ -0,0 +1,34 @@
+//! Choosing between the HTML and JSON representations of a page.
+
+
+use std::convert::Infallible;
+
+use rocket::Request;
+use rocket::request::{FromRequest, Outcome};
+
+
+/// The representation of a page preferred by the client.
+///
+/// Derived from the `Accept` header: JSON is only chosen if the media type the client prefers most
+/// is `application/json`. Browsers prefer `text/html` and generic clients send `*/*` or no `Accept`
+/// header at all, all of which result in HTML.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum ResponseFormat {
+    Html,
+    Json,
+}
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for ResponseFormat {
+    type Error = Infallible;
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        let prefers_json = request.accept()
+            .map(|accept| accept.preferred().media_type().is_json())
+            .unwrap_or(false);
+        if prefers_json {
+            Outcome::Success(ResponseFormat::Json)
+        } else {
+            Outcome::Success(ResponseFormat::Html)
+        }
+    }
+}
@@ -0,0 +1,170 @@
+//! The OpenAPI document describing the JSON API.
+//!
+//! The schemas of the responses are derived from the same types that the endpoints serialize, so
+//! the document cannot drift away from the actual responses.
+
+
+use schemars::{JsonSchema, Schema, SchemaGenerator};
+use schemars::generate::SchemaSettings;
+use serde_json::{json, Value};
+
+use crate::{
+    AutocompleteApiPart, CompareOsApiPart, CompareOsCountApiPart, OsSymbolApiPart, StatsPart,
+    SymbolApiPart,
+};
+
+
+/// The name of the schema of the body of an error response.
+const ERROR_SCHEMA_NAME: &str = "Error";
+
+
+/// A parameter of an endpoint, in its path or in its query string.
+#[derive(Clone, Copy, Debug)]
+struct Parameter {
+    pub name: &'static str,
+    pub in_query: bool,
+    pub schema_type: &'static str,
+    pub description: &'static str,
+}
+impl Parameter {
+    const fn path(name: &'static str, description: &'static str) -> Self {
+        Self { name, in_query: false, schema_type: "string", description }
+    }
+
+    const fn query(name: &'static str, schema_type: &'static str, description: &'static str) -> Self {
+        Self { name, in_query: true, schema_type, description }
+    }
+
+    fn to_json(self) -> Value {
+        json!({
+            "name": self.name,
+            "in": if self.in_query { "query" } else { "path" },
+            "required": !self.in_query,
+            "description": self.description,
+            "schema": { "type": self.schema_type },
+        })
+    }
+}
+
+
+/// Describes a `GET` endpoint returning a JSON body of type `T`.
+fn get_operation<T: JsonSchema>(generator: &mut SchemaGenerator, operation_id: &str, summary: &str, parameters: &[Parameter]) -> Value {
+    let error_response = json!({
+        "description": "An error, explained in the body.",
+        "content": {
+            "application/json": {
+                "schema": { "$ref": format!("#/components/schemas/{}", ERROR_SCHEMA_NAME) },
+            },
+        },
+    });
+    let response_schema: Schema = generator.subschema_for::<T>();
+    json!({
+        "get": {
+            "operationId": operation_id,
+            "summary": summary,
+            "parameters": parameters.iter().map(|p| p.to_json()).collect::<Vec<Value>>(),
+            "responses": {
+                "200": {
+                    "description": "Success.",
+                    "content": {
+                        "application/json": {
+                            "schema": response_schema,
+                        },
+                    },
+                },
+                "400": error_response,
+                "404": error_response,
+                "500": error_response,
+            },
+        },
+    })
+}
+
+/// Generates the OpenAPI document describing the endpoints returning JSON.
+pub fn document() -> Value {
+    let mut generator = SchemaSettings::openapi3()
+        .for_serialize()
+        .into_generator();
+
+    let os_name = Parameter::path("os_name", "The short name of the operating system.");
+    let old = Parameter::path("old", "The short name of the older operating system.");
+    let new = Parameter::path("new", "The short name of the newer operating system.");
+    let merge_editions = Parameter::query("merge_editions", "boolean", "Whether the symbols of all editions of each operating system are compared.");
+
+    let paths = json!({
+        "/api/os/{os_name}/symbols": get_operation::<Vec<OsSymbolApiPart>>(
+            &mut generator,
+            "getOsSymbols",
+            "Lists all symbols of an operating system along with the DLLs containing them.",
+            &[os_name],
+        ),
+        "/api/symbol/{sym_raw_name}": get_operation::<SymbolApiPart>(
+            &mut generator,
+            "getSymbol",
+            "Returns a named symbol along with the operating systems and DLLs containing it.",
+            &[Parameter::path("sym_raw_name", "The raw name of the symbol.")],
+        ),
+        "/api/autocomplete": get_operation::<AutocompleteApiPart>(
+            &mut generator,
+            "autocomplete",
+            "Lists the names of symbols starting with the given prefix. Each symbol is named by its friendly name or, if it has none, by its raw name.",
+            &[
+                Parameter::query("q", "string", "The prefix of the names, matched against the friendly name or, if a symbol has none, the raw name."),
+                Parameter::query("limit", "integer", "The maximum number of names returned."),
+            ],
+        ),
+        "/api/stats": get_operation::<StatsPart>(
+            &mut generator,
+            "getStats",
+            "Returns statistics about the database.",
+            &[],
+        ),
+        "/symbol/{sym_raw_name}": get_operation::<SymbolApiPart>(
+            &mut generator,
+            "getSymbolPage",
+            "Returns a named symbol along with the operating systems and DLLs containing it. Only returns JSON if the request prefers it using `Accept: application/json`; HTML otherwise.",
+            &[Parameter::path("sym_raw_name", "The raw name of the symbol.")],
+        ),
+        "/os/{old}/compare/{new}": get_operation::<CompareOsApiPart>(
+            &mut generator,
+            "compareOs",
+            "Lists the DLLs and symbols removed and added between two operating systems, each symbol along with the DLLs containing it. Only returns JSON if the request prefers it using `Accept: application/json`; HTML otherwise.",
+            &[
+                old,
+                new,
+                merge_editions,
+                Parameter::query("include_common", "boolean", "Whether the symbols common to both operating systems are listed too."),
+            ],
+        ),
+        "/os/{old}/compare/{new}/count": get_operation::<CompareOsCountApiPart>(
+            &mut generator,
+            "countOsComparison",
+            "Counts the symbols removed and added between two operating systems.",
+            &[old, new, merge_editions],
+        ),
+    });
+
+    let mut schemas = generator.take_definitions(true);
+    schemas.insert(
+        ERROR_SCHEMA_NAME.to_owned(),
+        json!({
+            "type": "object",
+            "properties": {
+                "error": { "type": "string" },
+            },
+            "required": ["error"],
+        }),
+    );
+
+    json!({
+        "openapi": "3.0.3",
+        "info": {
+            "title": "winapi-history",
+            "version": env!("CARGO_PKG_VERSION"),
+        },
+        "paths": paths,
+        "components": {
+            "schemas": schemas,
+        },
+    })
+}
@@ -0,0 +1,243 @@
+//! Splitting long lists into pages.
+
+
+use percent_encoding::{utf8_percent_encode, NON_ALPHANUMERIC};
+
+/// The numbers of entries per page that may be chosen.
+pub const PER_PAGE_CHOICES: [u64; 5] = [100, 250, 500, 1000, 2500];
+
+/// The number of pages linked on either side of the current page.
+const WINDOW_RADIUS: u64 = 3;
+
+
+/// The position within a paginated list, as shared by all paginated pages.
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Pagination {
+    /// The current page, starting at 1.
+    pub page: u64,
+
+    /// The number of entries per page.
+    pub per_page: u64,
+
+    /// The total number of entries in the list.
+    pub total_items: u64,
+
+    /// Further query parameters (names and values) preserved when navigating between pages.
+    pub extra_params: Vec<(&'static str, String)>,
+
+    /// The token of the entry after which the current page starts if it was requested by keyset
+    /// (see [`crate::keyset`]) instead of by number.
+    ///
+    /// Pages requested by keyset have no number; only the first and the next page are linked.
+    pub after: Option<String>,
+
+    /// The token of the last entry on the current page if further entries follow it.
+    pub next_after: Option<String>,
+}
+impl Pagination {
+    /// Creates a new pagination context from the given query parameters.
+    ///
+    /// Numbers of entries per page that are not among [`PER_PAGE_CHOICES`] are replaced with
+    /// `default_per_page`; pages beyond either end of the list are clamped to the first or last
+    /// page.
+    pub fn new(page: Option<u64>, per_page: Option<u64>, default_per_page: u64, total_items: u64) -> Self {
+        let per_page = match per_page {
+            Some(pp) if PER_PAGE_CHOICES.contains(&pp) => pp,
+            _ => default_per_page,
+        };
+        let mut pagination = Self {
+            page: 1,
+            per_page,
+            total_items,
+            extra_params: Vec::new(),
+            after: None,
+            next_after: None,
+        };
+        pagination.page = page
+            .unwrap_or(1)
+            .clamp(1, pagination.total_pages());
+        pagination
+    }
+
+    /// Preserves the given query parameters (names and values) when navigating between pages, in
+    /// addition to those already preserved.
+    pub fn with_extra_params(mut self, extra_params: &[(&'static str, &'static str)]) -> Self {
+        self.extra_params.extend(
+            extra_params.iter()
+                .map(|(name, value)| (*name, (*value).to_owned()))
+        );
+        self
+    }
+
+    /// Preserves the given query parameter when navigating between pages, in addition to those
+    /// already preserved.
+    pub fn with_extra_param(mut self, name: &'static str, value: String) -> Self {
+        self.extra_params.push((name, value));
+        self
+    }
+
+    /// The number of pages; an empty list still has one (empty) page.
+    pub fn total_pages(&self) -> u64 {
+        self.total_items.div_ceil(self.per_page).max(1)
+    }
+
+    /// The number of entries preceding the current page, as used with SQL `OFFSET`.
+    pub fn offset(&self) -> u64 {
+        (self.page - 1) * self.per_page
+    }
+
+    /// The number of entries on the current page, as used with SQL `LIMIT`.
+    ///
+    /// Only the last page may have fewer than [`per_page`](Self::per_page) entries.
+    pub fn page_len(&self) -> u64 {
+        self.per_page.min(self.total_items.saturating_sub(self.offset()))
+    }
+
+    /// The `LIMIT` and `OFFSET` of the query for the entries on the current page.
+    ///
+    /// A page requested by keyset starts at the first entry matching the keyset condition and
+    /// fetches one entry more than fits on it to find out whether another page follows; see
+    /// [`finish_page`](Self::finish_page).
+    pub fn query_limit_offset(&self) -> (u64, u64) {
+        if self.after.is_some() {
+            (self.keyset_page_len() + 1, 0)
+        } else {
+            (self.page_len(), self.offset())
+        }
+    }
+
+    /// The number of entries on a page requested by keyset.
+    ///
+    /// Like any other page, it contains no more entries than the whole list, which may have been
+    /// shortened to the maximum number of rows.
+    fn keyset_page_len(&self) -> u64 {
+        self.per_page.min(self.total_items)
+    }
+
+    /// Takes the entries queried for the current page, each along with its token, and returns the
+    /// entries on the page, remembering the token of the last one if another page follows.
+    pub fn finish_page<T>(&mut self, mut rows: Vec<(T, String)>) -> Vec<T> {
+        let has_next = if self.after.is_some() {
+            let page_len = usize::try_from(self.keyset_page_len()).unwrap();
+            let has_next = rows.len() > page_len;
+            rows.truncate(page_len);
+            has_next
+        } else {
+            self.has_next()
+        };
+        if has_next {
+            self.next_after = rows.last().map(|(_entry, token)| token.clone());
+        }
+        rows.into_iter()
+            .map(|(entry, _token)| entry)
+            .collect()
+    }
+
+    pub fn has_prev(&self) -> bool {
+        self.page > 1
+    }
+
+    pub fn has_next(&self) -> bool {
+        self.page < self.total_pages()
+    }
+
+    /// Whether the list is long enough for the pagination controls to be shown.
+    pub fn is_worth_showing(&self) -> bool {
+        self.total_items > PER_PAGE_CHOICES[0]
+    }
+
+    /// The pages surrounding the current page (including it) which should be linked directly.
+    pub fn window(&self) -> Vec<u64> {
+        let first = self.page.saturating_sub(WINDOW_RADIUS).max(1);
+        let last = (self.page + WINDOW_RADIUS).min(self.total_pages());
+        (first..=last).collect()
+    }
+
+    /// Whether pages are omitted between the first page and the window.
+    pub fn has_gap_before_window(&self) -> bool {
+        self.page > WINDOW_RADIUS + 1
+    }
+
+    /// Whether pages are omitted between the window and the last page.
+    pub fn has_gap_after_window(&self) -> bool {
+        self.page + WINDOW_RADIUS < self.total_pages()
+    }
+
+    pub fn per_page_choices(&self) -> &'static [u64] {
+        &PER_PAGE_CHOICES
+    }
+
+    /// The query string linking to the given page with the current number of entries per page.
+    pub fn page_query(&self, page: &u64) -> String {
+        let mut query = format!("?page={}&per_page={}", page, self.per_page);
+        self.push_extra_params(&mut query);
+        query
+    }
+
+    /// The query string linking to the page following the current one by keyset, if further
+    /// entries follow.
+    pub fn next_after_query(&self) -> Option<String> {
+        let next_after = self.next_after.as_ref()?;
+        let mut query = format!(
+            "?after={}&per_page={}",
+            utf8_percent_encode(next_after, NON_ALPHANUMERIC),
+            self.per_page,
+        );
+        self.push_extra_params(&mut query);
+        Some(query)
+    }
+
+    fn push_extra_params(&self, query: &mut String) {
+        for (name, value) in &self.extra_params {
+            query.push_str(&format!("&{}={}", name, utf8_percent_encode(value, NON_ALPHANUMERIC)));
+        }
+    }
+
+    pub fn first_page_query(&self) -> String {
+        self.page_query(&1)
+    }
+
+    pub fn prev_page_query(&self) -> String {
+        self.page_query(&(self.page - 1))
+    }
+
+    pub fn next_page_query(&self) -> String {
+        self.page_query(&(self.page + 1))
+    }
+
+    pub fn last_page_query(&self) -> String {
+        self.page_query(&self.total_pages())
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::Pagination;
+
+    #[test]
+    fn pagination_parameters_are_percent_encoded() {
+        let pagination = Pagination::new(None, None, 100, 1000)
+            .with_extra_param("q", "a&b c".to_owned());
+        assert_eq!(pagination.page_query(&2), "?page=2&per_page=100&q=a%26b%20c");
+    }
+
+    #[test]
+    fn keyset_page_links_to_its_last_entry() {
+        let mut pagination = Pagination::new(None, None, 2, 5)
+            .with_extra_param("q", "a&b".to_owned());
+        pagination.after = Some(r#"["A"]"#.to_owned());
+        assert_eq!(pagination.query_limit_offset(), (3, 0));
+
+        let rows = vec![
+            ("B", r#"["B"]"#.to_owned()),
+            ("C", r#"["C"]"#.to_owned()),
+            ("D", r#"["D"]"#.to_owned()),
+        ];
+        assert_eq!(pagination.finish_page(rows), ["B", "C"]);
+        assert_eq!(
+            pagination.next_after_query().as_deref(),
+            Some("?after=%5B%22C%22%5D&per_page=2&q=a%26b"),
+        );
+    }
+}
@@ -0,0 +1,96 @@
+//! Relative paths between the pages of the site.
+
+
+use std::convert::Infallible;
+
+use rocket::Request;
+use rocket::request::{FromRequest, Outcome};
+
+
+/// Returns the relative path from the given request path to the root of the site.
+///
+/// The path is never empty, since an empty link would point to the current page instead of the
+/// root; for top-level pages, it is `./`.
+pub fn path_to_root_from(path: &str) -> String {
+    let depth = path
+        .trim_start_matches('/')
+        .matches('/')
+        .count();
+    if depth == 0 {
+        "./".to_owned()
+    } else {
+        "../".repeat(depth)
+    }
+}
+
+
+/// The relative path from the requested page to the root of the site.
+///
+/// Derived from the depth of the request path, so that it need not be kept in sync with the
+/// routes by hand.
+pub struct PathToRoot(pub String);
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for PathToRoot {
+    type Error = Infallible;
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        Outcome::Success(PathToRoot(path_to_root_from(request.uri().path().as_str())))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::path_to_root_from;
+
+    /// Resolves a relative link the way a browser does, given the absolute path of the page
+    /// containing it.
+    fn resolve(page_path: &str, link: &str) -> String {
+        let mut segments: Vec<&str> = page_path.split('/').collect();
+        // the last segment is the page itself
+        segments.pop();
+        let mut rest = link;
+        loop {
+            if let Some(r) = rest.strip_prefix("../") {
+                if segments.len() > 1 {
+                    segments.pop();
+                }
+                rest = r;
+            } else if let Some(r) = rest.strip_prefix("./") {
+                rest = r;
+            } else {
+                break;
+            }
+        }
+        format!("{}/{}", segments.join("/"), rest)
+    }
+
+    #[test]
+    fn top_level_path_is_not_empty() {
+        assert_eq!(path_to_root_from("/"), "./");
+        assert_eq!(path_to_root_from("/search"), "./");
+    }
+
+    #[test]
+    fn links_resolve_to_root_at_every_depth() {
+        let page_paths = [
+            "/",
+            "/search",
+            "/os/win10",
+            "/symbol/CreateFileW",
+            "/os/win10/dll/kernel32.dll",
+            "/os/win10/compare/win11/count",
+            "/symbol/dll/kernel32.dll/ordinal/1",
+            "/os/win10/dll/kernel32.dll/ordinals/1/10",
+        ];
+        for page_path in page_paths {
+            let path_to_root = path_to_root_from(page_path);
+            assert_eq!(resolve(page_path, &path_to_root), "/", "root link of {}", page_path);
+            assert_eq!(
+                resolve(page_path, &format!("{}os/win10", path_to_root)),
+                "/os/win10",
+                "OS link of {}", page_path,
+            );
+        }
+    }
+}
@@ -0,0 +1,33 @@
+//! Tracing spans attributing log entries to the routes handling the requests.
+
+
+use std::convert::Infallible;
+
+use rocket::Request;
+use rocket::request::{FromRequest, Outcome};
+use tracing::{debug_span, Span};
+use tracing::span::Entered;
+
+
+/// A `debug`-level span named after the route handling the request.
+///
+/// Entering it at the start of a route attributes the database queries logged while handling the
+/// request (see `query_database`) to that route.
+pub struct RouteSpan(pub Span);
+impl RouteSpan {
+    /// Enters the span until the returned guard is dropped.
+    pub fn enter(&self) -> Entered<'_> {
+        self.0.enter()
+    }
+}
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for RouteSpan {
+    type Error = Infallible;
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        let route_name = request.route()
+            .and_then(|route| route.name.as_deref())
+            .unwrap_or("-");
+        Outcome::Success(RouteSpan(debug_span!("route", name = route_name)))
+    }
+}